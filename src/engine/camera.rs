@@ -0,0 +1,67 @@
+use raylib::math::{Rectangle, Vector2};
+
+/// 2D camera that converts between world and screen coordinates.
+/// The world point `target` is drawn at the screen point `offset`, scaled by `zoom`.
+pub struct Camera {
+    pub target: Vector2,
+    pub offset: Vector2,
+    pub zoom: f32,
+    pub min_zoom: f32,
+    pub max_zoom: f32,
+}
+
+impl Camera {
+    /// Camera that shows the world unscaled with its origin at the top-left of the screen.
+    pub fn new() -> Self {
+        Self {
+            target: Vector2::new(0.0, 0.0),
+            offset: Vector2::new(0.0, 0.0),
+            zoom: 1.0,
+            min_zoom: 0.05,
+            max_zoom: 20.0,
+        }
+    }
+
+    pub fn world_to_screen(&self, world: Vector2) -> Vector2 {
+        (world - self.target) * self.zoom + self.offset
+    }
+
+    pub fn screen_to_world(&self, screen: Vector2) -> Vector2 {
+        (screen - self.offset) / self.zoom + self.target
+    }
+
+    /// Returns the part of the world that is visible on a screen of the given size.
+    pub fn visible_world(&self, screen_width: f32, screen_height: f32) -> Rectangle {
+        let top_left = self.screen_to_world(Vector2::new(0.0, 0.0));
+
+        Rectangle::new(
+            top_left.x,
+            top_left.y,
+            screen_width / self.zoom,
+            screen_height / self.zoom,
+        )
+    }
+
+    /// Moves the camera so that the world follows the mouse by `screen_delta` pixels.
+    pub fn pan(&mut self, screen_delta: Vector2) {
+        self.target -= screen_delta / self.zoom;
+    }
+
+    /// Multiplies the zoom by `factor`, keeping the world point under `screen_point` in place.
+    pub fn zoom_at(&mut self, screen_point: Vector2, factor: f32) {
+        let world_before = self.screen_to_world(screen_point);
+        self.zoom = (self.zoom * factor).clamp(self.min_zoom, self.max_zoom);
+        let world_after = self.screen_to_world(screen_point);
+
+        self.target += world_before - world_after;
+    }
+
+    /// Centres `world` on the screen and zooms so that all of it is visible.
+    pub fn fit(&mut self, world: Rectangle, screen_width: f32, screen_height: f32) {
+        self.zoom = (screen_width / world.width)
+            .min(screen_height / world.height)
+            .clamp(self.min_zoom, self.max_zoom);
+        self.target = Vector2::new(world.x + world.width / 2.0, world.y + world.height / 2.0);
+        self.offset = Vector2::new(screen_width / 2.0, screen_height / 2.0);
+    }
+}
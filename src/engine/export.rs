@@ -0,0 +1,449 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufWriter, Write},
+};
+
+use raylib::color::Color;
+
+/// An RGB picture drawn in memory, so it can be exported without a window.
+/// Alpha is ignored.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pixels: Vec<Color>,
+}
+
+impl Image {
+    pub fn new(width: u32, height: u32, background: Color) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![background; (width * height) as usize],
+        }
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> Color {
+        self.pixels[(x + y * self.width) as usize]
+    }
+
+    pub fn set(&mut self, x: u32, y: u32, color: Color) {
+        self.pixels[(x + y * self.width) as usize] = color;
+    }
+
+    /// Fills a rectangle, clipped to the image.
+    pub fn fill_rect(&mut self, x: u32, y: u32, width: u32, height: u32, color: Color) {
+        for py in y..(y + height).min(self.height) {
+            for px in x..(x + width).min(self.width) {
+                self.set(px, py, color);
+            }
+        }
+    }
+
+    pub fn save_png(&self, path: &str) -> io::Result<()> {
+        write_png(self, BufWriter::new(File::create(path)?))
+    }
+}
+
+/// Writes an image as an 8-bit RGB PNG.
+pub fn write_png(image: &Image, mut writer: impl Write) -> io::Result<()> {
+    writer.write_all(&[0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a])?;
+
+    let mut header = Vec::new();
+    header.extend(image.width.to_be_bytes());
+    header.extend(image.height.to_be_bytes());
+    // Bit depth 8, colour type 2 (RGB), default compression, filter and no interlacing.
+    header.extend([8, 2, 0, 0, 0]);
+    write_png_chunk(&mut writer, b"IHDR", &header)?;
+
+    let mut scanlines = Vec::with_capacity(((image.width * 3 + 1) * image.height) as usize);
+    for y in 0..image.height {
+        // Filter type 0, the rows are stored as they are.
+        scanlines.push(0);
+        for x in 0..image.width {
+            let color = image.get(x, y);
+            scanlines.extend([color.r, color.g, color.b]);
+        }
+    }
+    write_png_chunk(&mut writer, b"IDAT", &zlib_compress(&scanlines))?;
+    write_png_chunk(&mut writer, b"IEND", &[])?;
+
+    writer.flush()
+}
+
+fn write_png_chunk(writer: &mut impl Write, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    writer.write_all(&(data.len() as u32).to_be_bytes())?;
+    writer.write_all(kind)?;
+    writer.write_all(data)?;
+
+    let mut crc_input = kind.to_vec();
+    crc_input.extend_from_slice(data);
+    writer.write_all(&crc32(&crc_input).to_be_bytes())
+}
+
+/// CRC-32 as used by PNG chunks.
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                0xedb8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+        }
+    }
+
+    !crc
+}
+
+/// Adler-32 checksum that ends a zlib stream.
+pub fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+
+    (b << 16) | a
+}
+
+/// Writes bits least significant first, as deflate and GIF's LZW both expect.
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    bits: u32,
+}
+
+impl BitWriter {
+    fn new() -> Self {
+        Self {
+            bytes: Vec::new(),
+            buffer: 0,
+            bits: 0,
+        }
+    }
+
+    fn write(&mut self, value: u32, bits: u32) {
+        self.buffer |= value << self.bits;
+        self.bits += bits;
+        while self.bits >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    /// Writes a Huffman code, which deflate stores most significant bit first.
+    fn write_reversed(&mut self, code: u32, bits: u32) {
+        let reversed = code.reverse_bits() >> (32 - bits);
+        self.write(reversed, bits);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
+
+const LENGTH_BASES: [u32; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA_BITS: [u32; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASES: [u32; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA_BITS: [u32; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+const WINDOW_SIZE: usize = 32768;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+const HASH_BITS: u32 = 15;
+
+/// Compresses data into a zlib stream using a single deflate block with the fixed
+/// Huffman codes. Matches are found with a hash of the next three bytes, which is
+/// enough for the long runs of equal pixels in exported boards.
+pub fn zlib_compress(data: &[u8]) -> Vec<u8> {
+    let mut bits = BitWriter::new();
+    // Final block, fixed Huffman codes.
+    bits.write(1, 1);
+    bits.write(1, 2);
+
+    let mut last_seen = vec![usize::MAX; 1 << HASH_BITS];
+    let mut position = 0;
+    while position < data.len() {
+        let mut match_length = 0;
+        let mut match_distance = 0;
+
+        if position + MIN_MATCH <= data.len() {
+            let hash = hash3(&data[position..]);
+            let candidate = last_seen[hash];
+            last_seen[hash] = position;
+
+            if candidate != usize::MAX && position - candidate <= WINDOW_SIZE {
+                let max_length = MAX_MATCH.min(data.len() - position);
+                let length = (0..max_length)
+                    .take_while(|&i| data[candidate + i] == data[position + i])
+                    .count();
+                if length >= MIN_MATCH {
+                    match_length = length;
+                    match_distance = position - candidate;
+                }
+            }
+        }
+
+        if match_length > 0 {
+            write_length(&mut bits, match_length as u32);
+            write_distance(&mut bits, match_distance as u32);
+            for skipped in position + 1..position + match_length {
+                if skipped + MIN_MATCH <= data.len() {
+                    last_seen[hash3(&data[skipped..])] = skipped;
+                }
+            }
+            position += match_length;
+        } else {
+            write_literal(&mut bits, data[position] as u32);
+            position += 1;
+        }
+    }
+    // End of block.
+    write_literal(&mut bits, 256);
+
+    // Deflate compression with a 32K window and no preset dictionary.
+    let mut stream = vec![0x78, 0x01];
+    stream.extend(bits.finish());
+    stream.extend(adler32(data).to_be_bytes());
+    stream
+}
+
+fn hash3(bytes: &[u8]) -> usize {
+    let value = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+    (value.wrapping_mul(2654435761) >> (32 - HASH_BITS)) as usize
+}
+
+/// Writes a literal/length symbol with the fixed Huffman code.
+fn write_literal(bits: &mut BitWriter, symbol: u32) {
+    match symbol {
+        0..=143 => bits.write_reversed(0x30 + symbol, 8),
+        144..=255 => bits.write_reversed(0x190 + symbol - 144, 9),
+        256..=279 => bits.write_reversed(symbol - 256, 7),
+        _ => bits.write_reversed(0xc0 + symbol - 280, 8),
+    }
+}
+
+fn write_length(bits: &mut BitWriter, length: u32) {
+    let code = LENGTH_BASES
+        .iter()
+        .rposition(|&base| base <= length)
+        .unwrap();
+    write_literal(bits, 257 + code as u32);
+    bits.write(length - LENGTH_BASES[code], LENGTH_EXTRA_BITS[code]);
+}
+
+fn write_distance(bits: &mut BitWriter, distance: u32) {
+    let code = DISTANCE_BASES
+        .iter()
+        .rposition(|&base| base <= distance)
+        .unwrap();
+    bits.write_reversed(code as u32, 5);
+    bits.write(distance - DISTANCE_BASES[code], DISTANCE_EXTRA_BITS[code]);
+}
+
+/// Writes an animated GIF that loops forever, one `Image` per frame.
+/// Every frame gets its own colour table. Frames with more than 256 colours have the
+/// extra colours replaced by the nearest one already in the table.
+pub struct GifEncoder<W: Write> {
+    writer: W,
+    width: u32,
+    height: u32,
+    delay_centiseconds: u16,
+}
+
+impl<W: Write> GifEncoder<W> {
+    /// Fails without writing anything if a dimension does not fit in the 16 bits GIF
+    /// has for it.
+    pub fn new(
+        mut writer: W,
+        width: u32,
+        height: u32,
+        delay_centiseconds: u16,
+    ) -> io::Result<Self> {
+        let (Ok(gif_width), Ok(gif_height)) = (u16::try_from(width), u16::try_from(height)) else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "A GIF can be at most {}x{}, got {}x{}",
+                    u16::MAX,
+                    u16::MAX,
+                    width,
+                    height
+                ),
+            ));
+        };
+
+        writer.write_all(b"GIF89a")?;
+        writer.write_all(&gif_width.to_le_bytes())?;
+        writer.write_all(&gif_height.to_le_bytes())?;
+        // No global colour table, background colour 0, square pixels.
+        writer.write_all(&[0, 0, 0])?;
+
+        // NETSCAPE2.0 application extension, loop count 0 repeats forever.
+        writer.write_all(&[0x21, 0xff, 0x0b])?;
+        writer.write_all(b"NETSCAPE2.0")?;
+        writer.write_all(&[0x03, 0x01, 0x00, 0x00, 0x00])?;
+
+        Ok(Self {
+            writer,
+            width,
+            height,
+            delay_centiseconds,
+        })
+    }
+
+    pub fn add_frame(&mut self, image: &Image) -> io::Result<()> {
+        if image.width != self.width || image.height != self.height {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Frame is {}x{}, expected {}x{}",
+                    image.width, image.height, self.width, self.height
+                ),
+            ));
+        }
+
+        let (palette, indices) = index_colors(image);
+        let table_bits = (palette.len().max(2) as f32).log2().ceil() as u32;
+
+        // Graphic control extension with the frame delay.
+        self.writer.write_all(&[0x21, 0xf9, 0x04, 0x00])?;
+        self.writer
+            .write_all(&self.delay_centiseconds.to_le_bytes())?;
+        self.writer.write_all(&[0x00, 0x00])?;
+
+        // Image descriptor covering the whole screen, followed by its colour table.
+        self.writer.write_all(&[0x2c, 0, 0, 0, 0])?;
+        self.writer.write_all(&(self.width as u16).to_le_bytes())?;
+        self.writer.write_all(&(self.height as u16).to_le_bytes())?;
+        self.writer.write_all(&[0x80 | (table_bits - 1) as u8])?;
+        for i in 0..1usize << table_bits {
+            let color = palette.get(i).copied().unwrap_or(Color::BLACK);
+            self.writer.write_all(&[color.r, color.g, color.b])?;
+        }
+
+        let min_code_size = table_bits.max(2);
+        self.writer.write_all(&[min_code_size as u8])?;
+        for block in lzw_compress(&indices, min_code_size).chunks(255) {
+            self.writer.write_all(&[block.len() as u8])?;
+            self.writer.write_all(block)?;
+        }
+        self.writer.write_all(&[0x00])
+    }
+
+    /// Writes the trailer and returns the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.writer.write_all(&[0x3b])?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Builds a colour table of at most 256 entries and the index of every pixel in it.
+fn index_colors(image: &Image) -> (Vec<Color>, Vec<u8>) {
+    let mut palette: Vec<Color> = Vec::new();
+    let mut lookup: HashMap<(u8, u8, u8), u8> = HashMap::new();
+
+    let indices = image
+        .pixels
+        .iter()
+        .map(|color| {
+            let key = (color.r, color.g, color.b);
+            if let Some(&index) = lookup.get(&key) {
+                return index;
+            }
+
+            let index = if palette.len() < 256 {
+                palette.push(*color);
+                (palette.len() - 1) as u8
+            } else {
+                nearest_color(&palette, *color)
+            };
+            lookup.insert(key, index);
+            index
+        })
+        .collect();
+
+    (palette, indices)
+}
+
+fn nearest_color(palette: &[Color], color: Color) -> u8 {
+    let distance = |other: &Color| {
+        let dr = color.r as i32 - other.r as i32;
+        let dg = color.g as i32 - other.g as i32;
+        let db = color.b as i32 - other.b as i32;
+        dr * dr + dg * dg + db * db
+    };
+
+    palette
+        .iter()
+        .enumerate()
+        .min_by_key(|(_, other)| distance(other))
+        .map_or(0, |(index, _)| index as u8)
+}
+
+const MAX_LZW_CODE: u32 = 4096;
+
+/// GIF flavoured LZW with variable code sizes up to 12 bits.
+pub fn lzw_compress(indices: &[u8], min_code_size: u32) -> Vec<u8> {
+    let clear_code = 1 << min_code_size;
+    let end_code = clear_code + 1;
+
+    let mut bits = BitWriter::new();
+    let mut dictionary: HashMap<(u32, u8), u32> = HashMap::new();
+    let mut next_code = end_code + 1;
+    let mut code_size = min_code_size + 1;
+
+    bits.write(clear_code, code_size);
+
+    let Some((&first, rest)) = indices.split_first() else {
+        bits.write(end_code, code_size);
+        return bits.finish();
+    };
+
+    let mut prefix = first as u32;
+    for &index in rest {
+        if let Some(&code) = dictionary.get(&(prefix, index)) {
+            prefix = code;
+            continue;
+        }
+
+        bits.write(prefix, code_size);
+        if next_code < MAX_LZW_CODE {
+            dictionary.insert((prefix, index), next_code);
+            next_code += 1;
+            if next_code > 1 << code_size && code_size < 12 {
+                code_size += 1;
+            }
+        } else {
+            bits.write(clear_code, code_size);
+            dictionary.clear();
+            next_code = end_code + 1;
+            code_size = min_code_size + 1;
+        }
+        prefix = index as u32;
+    }
+
+    bits.write(prefix, code_size);
+    bits.write(end_code, code_size);
+    bits.finish()
+}
@@ -6,6 +6,12 @@ use std::{
 
 use raylib::{RaylibHandle, RaylibThread};
 
+pub mod camera;
+pub mod export;
+#[cfg(test)]
+mod tests;
+pub mod ui;
+
 /// Game trait that defines the methods that a game must implement.
 pub trait Game {
     fn handle_input(&mut self, rl: &RaylibHandle);
@@ -23,7 +29,6 @@ pub enum GameState {
 /// An entity is a game object that can have components.
 /// For example, a player entity can have a Transform, a Sprite, and a Collider component.
 pub struct Entity {
-    #[allow(unused)]
     id: u32,
     /// Components are stored in a HashMap with the TypeId as the key.
     /// We can store different types of components in the same HashMap.
@@ -31,6 +36,10 @@ pub struct Entity {
 }
 
 impl Entity {
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
     pub fn add_component<T: 'static>(&mut self, component: T) {
         self.components
             .insert(TypeId::of::<T>(), Box::new(component));
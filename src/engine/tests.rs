@@ -0,0 +1,196 @@
+use raylib::{
+    color::Color,
+    math::{Rectangle, Vector2},
+};
+
+use crate::engine::{
+    camera::Camera,
+    export::*,
+    ui::{Ui, UiInput},
+};
+
+#[test]
+fn test_camera_screen_to_world_round_trip() {
+    let mut camera = Camera::new();
+    camera.target = Vector2::new(120.0, -40.0);
+    camera.offset = Vector2::new(400.0, 300.0);
+    camera.zoom = 2.5;
+
+    let world = Vector2::new(33.0, 77.0);
+    let back = camera.screen_to_world(camera.world_to_screen(world));
+
+    assert!((back.x - world.x).abs() < 1e-4);
+    assert!((back.y - world.y).abs() < 1e-4);
+}
+
+#[test]
+fn test_camera_zoom_at_keeps_point_under_cursor() {
+    let mut camera = Camera::new();
+    let cursor = Vector2::new(250.0, 130.0);
+    let world_under_cursor = camera.screen_to_world(cursor);
+
+    camera.zoom_at(cursor, 3.0);
+
+    let after = camera.screen_to_world(cursor);
+    assert_eq!(camera.zoom, 3.0);
+    assert!((after.x - world_under_cursor.x).abs() < 1e-4);
+    assert!((after.y - world_under_cursor.y).abs() < 1e-4);
+}
+
+#[test]
+fn test_camera_zoom_is_clamped() {
+    let mut camera = Camera::new();
+
+    camera.zoom_at(Vector2::new(0.0, 0.0), 1000.0);
+    assert_eq!(camera.zoom, camera.max_zoom);
+
+    camera.zoom_at(Vector2::new(0.0, 0.0), 0.00001);
+    assert_eq!(camera.zoom, camera.min_zoom);
+}
+
+#[test]
+fn test_camera_pan_moves_world_with_mouse() {
+    let mut camera = Camera::new();
+    camera.zoom = 2.0;
+
+    camera.pan(Vector2::new(10.0, -20.0));
+
+    assert_eq!(camera.target, Vector2::new(-5.0, 10.0));
+}
+
+#[test]
+fn test_camera_fit_shows_whole_world() {
+    let mut camera = Camera::new();
+
+    camera.fit(Rectangle::new(0.0, 0.0, 800.0, 800.0), 800.0, 600.0);
+
+    let top_left = camera.world_to_screen(Vector2::new(0.0, 0.0));
+    let bottom_right = camera.world_to_screen(Vector2::new(800.0, 800.0));
+    assert_eq!(camera.zoom, 0.75);
+    assert_eq!(top_left, Vector2::new(100.0, 0.0));
+    assert_eq!(bottom_right, Vector2::new(700.0, 600.0));
+}
+
+#[test]
+fn test_checksums() {
+    assert_eq!(crc32(b"IEND"), 0xae42_6082);
+    assert_eq!(adler32(b"Wikipedia"), 0x11e6_0398);
+}
+
+#[test]
+fn test_png_starts_with_signature_and_header() {
+    let mut image = Image::new(3, 2, Color::BLACK);
+    image.fill_rect(1, 1, 5, 5, Color::WHITE);
+    assert_eq!(image.get(2, 1), Color::WHITE);
+    assert_eq!(image.get(0, 1), Color::BLACK);
+
+    let mut png = Vec::new();
+    write_png(&image, &mut png).unwrap();
+
+    assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+    assert_eq!(&png[12..16], b"IHDR");
+    assert_eq!(&png[16..24], &[0, 0, 0, 3, 0, 0, 0, 2]);
+    assert_eq!(&png[png.len() - 12..], b"\0\0\0\0IEND\xae\x42\x60\x82");
+}
+
+#[test]
+fn test_lzw_compress_repeated_index() {
+    // Clear, 0, 0 0, 0 and end, three bits each.
+    assert_eq!(lzw_compress(&[0, 0, 0, 0], 2), vec![0x84, 0x51]);
+}
+
+#[test]
+fn test_gif_frames() {
+    let mut encoder = GifEncoder::new(Vec::new(), 4, 4, 10).unwrap();
+    encoder.add_frame(&Image::new(4, 4, Color::RED)).unwrap();
+    assert!(encoder.add_frame(&Image::new(2, 2, Color::RED)).is_err());
+
+    let gif = encoder.finish().unwrap();
+    assert_eq!(&gif[..6], b"GIF89a");
+    assert_eq!(&gif[6..10], &[4, 0, 4, 0]);
+    assert_eq!(gif.last(), Some(&0x3b));
+}
+
+#[test]
+fn test_gif_rejects_dimensions_over_16_bits() {
+    let mut gif = Vec::new();
+
+    assert!(GifEncoder::new(&mut gif, 65536, 4, 10).is_err());
+    assert!(GifEncoder::new(&mut gif, 4, 70000, 10).is_err());
+    assert!(gif.is_empty());
+    assert!(GifEncoder::new(&mut gif, 65535, 4, 10).is_ok());
+}
+
+fn ui_input(x: f32, y: f32, pressed: bool, down: bool) -> UiInput {
+    UiInput {
+        mouse: Vector2::new(x, y),
+        pressed,
+        down,
+    }
+}
+
+/// Declares a panel at the origin with a button and a slider below it.
+fn declare_panel(ui: &mut Ui, input: UiInput, value: &mut f32) -> bool {
+    ui.begin(input);
+    ui.begin_panel(0.0, 0.0, 112.0);
+    let clicked = ui.button("Button");
+    ui.slider("Value", value, 0.0..=10.0);
+    ui.end_panel();
+    clicked
+}
+
+#[test]
+fn test_ui_button_clicks_once_per_press() {
+    let mut ui = Ui::new();
+    let mut value = 0.0;
+
+    assert!(!declare_panel(
+        &mut ui,
+        ui_input(50.0, 15.0, false, false),
+        &mut value
+    ));
+    assert!(declare_panel(
+        &mut ui,
+        ui_input(50.0, 15.0, true, true),
+        &mut value
+    ));
+    assert!(!declare_panel(
+        &mut ui,
+        ui_input(50.0, 15.0, false, true),
+        &mut value
+    ));
+    assert!(!declare_panel(
+        &mut ui,
+        ui_input(150.0, 15.0, true, true),
+        &mut value
+    ));
+}
+
+#[test]
+fn test_ui_slider_follows_drag_outside_the_track() {
+    let mut ui = Ui::new();
+    let mut value = 0.0;
+
+    // The slider track is the third row: 6 + 2 * (24 + 6) = 66 pixels down, 100 wide.
+    declare_panel(&mut ui, ui_input(56.0, 70.0, true, true), &mut value);
+    assert!((value - 5.0).abs() < 1e-4);
+
+    declare_panel(&mut ui, ui_input(500.0, 300.0, false, true), &mut value);
+    assert_eq!(value, 10.0);
+    assert!(ui.wants_mouse());
+
+    declare_panel(&mut ui, ui_input(500.0, 300.0, false, false), &mut value);
+    assert_eq!(value, 10.0);
+    assert!(!ui.wants_mouse());
+}
+
+#[test]
+fn test_ui_wants_mouse_over_panels() {
+    let mut ui = Ui::new();
+    let mut value = 0.0;
+
+    declare_panel(&mut ui, ui_input(100.0, 90.0, false, false), &mut value);
+    assert!(ui.wants_mouse());
+    declare_panel(&mut ui, ui_input(100.0, 130.0, false, false), &mut value);
+    assert!(!ui.wants_mouse());
+}
@@ -0,0 +1,265 @@
+use std::ops::RangeInclusive;
+
+use raylib::{
+    color::Color,
+    consts::MouseButton,
+    math::{Rectangle, Vector2},
+    prelude::RaylibDraw,
+    RaylibHandle,
+};
+
+const PADDING: f32 = 6.0;
+const ROW_HEIGHT: f32 = 24.0;
+const FONT_SIZE: i32 = 16;
+
+const PANEL_COLOR: Color = Color::new(0, 0, 0, 170);
+const WIDGET_COLOR: Color = Color::new(60, 60, 60, 255);
+const HOVER_COLOR: Color = Color::new(90, 90, 90, 255);
+const ACTIVE_COLOR: Color = Color::new(0, 121, 241, 255);
+
+/// State of the mouse for one frame.
+#[derive(Debug, Clone, Copy)]
+pub struct UiInput {
+    pub mouse: Vector2,
+    pub pressed: bool,
+    pub down: bool,
+}
+
+impl UiInput {
+    pub fn from_raylib(rl: &RaylibHandle) -> Self {
+        Self {
+            mouse: rl.get_mouse_position(),
+            pressed: rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT),
+            down: rl.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT),
+        }
+    }
+}
+
+/// Something the UI draws, queued while the widgets are declared in `handle_input`
+/// and drawn in `render`.
+#[derive(Debug, Clone, PartialEq)]
+enum DrawCommand {
+    Rectangle {
+        rect: Rectangle,
+        color: Color,
+    },
+    Text {
+        text: String,
+        position: Vector2,
+        color: Color,
+    },
+}
+
+/// Immediate-mode UI. Widgets are declared again every frame between `begin` and
+/// `render`; a button reports whether it was clicked and a slider changes its value
+/// in place, so the UI keeps no copy of the game state.
+/// Widgets are stacked top to bottom inside the current panel.
+pub struct Ui {
+    input: UiInput,
+    commands: Vec<DrawCommand>,
+    /// Top-left corner of the next widget.
+    cursor: Vector2,
+    width: f32,
+    /// Index of the panel background in `commands`, its height is known at `end_panel`.
+    panel: Option<usize>,
+    panels: Vec<Rectangle>,
+    /// Widgets are numbered in the order they are declared, which stays the same from
+    /// one frame to the next.
+    next_id: usize,
+    /// The slider being dragged, it keeps the mouse until the button is released.
+    active: Option<usize>,
+}
+
+impl Ui {
+    pub fn new() -> Self {
+        Self {
+            input: UiInput {
+                mouse: Vector2::new(0.0, 0.0),
+                pressed: false,
+                down: false,
+            },
+            commands: Vec::new(),
+            cursor: Vector2::new(0.0, 0.0),
+            width: 0.0,
+            panel: None,
+            panels: Vec::new(),
+            next_id: 0,
+            active: None,
+        }
+    }
+
+    /// Starts a new frame, forgetting the widgets of the last one.
+    pub fn begin(&mut self, input: UiInput) {
+        self.input = input;
+        self.commands.clear();
+        self.panels.clear();
+        self.panel = None;
+        self.next_id = 0;
+        if !input.down {
+            self.active = None;
+        }
+    }
+
+    /// Starts a panel with its top-left corner at `(x, y)`.
+    pub fn begin_panel(&mut self, x: f32, y: f32, width: f32) {
+        self.panel = Some(self.commands.len());
+        self.commands.push(DrawCommand::Rectangle {
+            rect: Rectangle::new(x, y, width, 0.0),
+            color: PANEL_COLOR,
+        });
+        self.cursor = Vector2::new(x + PADDING, y + PADDING);
+        self.width = width - 2.0 * PADDING;
+    }
+
+    pub fn end_panel(&mut self) {
+        let Some(index) = self.panel.take() else {
+            return;
+        };
+
+        if let DrawCommand::Rectangle { rect, .. } = &mut self.commands[index] {
+            rect.height = self.cursor.y - rect.y;
+            self.panels.push(*rect);
+        }
+    }
+
+    pub fn label(&mut self, text: &str) {
+        let rect = self.next_row();
+        self.text(text, rect, Color::WHITE);
+    }
+
+    /// Returns true in the frame the button is clicked.
+    pub fn button(&mut self, text: &str) -> bool {
+        let rect = self.next_row();
+        self.button_in(text, rect)
+    }
+
+    /// Buttons side by side in one row. Returns the index of the clicked one.
+    pub fn button_row(&mut self, texts: &[&str]) -> Option<usize> {
+        let row = self.next_row();
+        let width = (row.width - PADDING * (texts.len() as f32 - 1.0)) / texts.len() as f32;
+
+        let mut clicked = None;
+        for (i, text) in texts.iter().enumerate() {
+            let rect = Rectangle::new(
+                row.x + i as f32 * (width + PADDING),
+                row.y,
+                width,
+                row.height,
+            );
+            if self.button_in(text, rect) {
+                clicked = Some(i);
+            }
+        }
+
+        clicked
+    }
+
+    /// A slider below a label. Returns true if the value changed this frame.
+    pub fn slider(&mut self, text: &str, value: &mut f32, range: RangeInclusive<f32>) -> bool {
+        self.label(text);
+        let rect = self.next_row();
+        let id = self.next_id();
+
+        if self.input.pressed && rect.check_collision_point_rec(self.input.mouse) {
+            self.active = Some(id);
+        }
+
+        let before = *value;
+        if self.active == Some(id) {
+            let fraction = ((self.input.mouse.x - rect.x) / rect.width).clamp(0.0, 1.0);
+            *value = range.start() + fraction * (range.end() - range.start());
+        }
+
+        let fraction = ((*value - range.start()) / (range.end() - range.start())).clamp(0.0, 1.0);
+        let track = Rectangle::new(rect.x, rect.y + rect.height / 2.0 - 2.0, rect.width, 4.0);
+        let knob = Rectangle::new(
+            rect.x + fraction * (rect.width - 10.0),
+            rect.y + 2.0,
+            10.0,
+            rect.height - 4.0,
+        );
+        let knob_color = if self.active == Some(id) {
+            ACTIVE_COLOR
+        } else if rect.check_collision_point_rec(self.input.mouse) {
+            Color::LIGHTGRAY
+        } else {
+            Color::GRAY
+        };
+        self.rectangle(track, WIDGET_COLOR);
+        self.rectangle(knob, knob_color);
+
+        *value != before
+    }
+
+    /// True when the mouse is over a panel or dragging a slider, so the game should
+    /// not also treat the mouse as aimed at the world behind the UI.
+    pub fn wants_mouse(&self) -> bool {
+        self.active.is_some()
+            || self
+                .panels
+                .iter()
+                .any(|panel| panel.check_collision_point_rec(self.input.mouse))
+    }
+
+    pub fn render(&self, rl: &mut impl RaylibDraw) {
+        for command in self.commands.iter() {
+            match command {
+                DrawCommand::Rectangle { rect, color } => rl.draw_rectangle_rec(*rect, *color),
+                DrawCommand::Text {
+                    text,
+                    position,
+                    color,
+                } => rl.draw_text(
+                    text,
+                    position.x as i32,
+                    position.y as i32,
+                    FONT_SIZE,
+                    *color,
+                ),
+            }
+        }
+    }
+
+    fn button_in(&mut self, text: &str, rect: Rectangle) -> bool {
+        self.next_id();
+        let hovered = rect.check_collision_point_rec(self.input.mouse);
+        let clicked = hovered && self.input.pressed && self.active.is_none();
+
+        let color = match (hovered, self.input.down) {
+            (true, true) => ACTIVE_COLOR,
+            (true, false) => HOVER_COLOR,
+            _ => WIDGET_COLOR,
+        };
+        self.rectangle(rect, color);
+        self.text(text, rect, Color::WHITE);
+
+        clicked
+    }
+
+    /// Takes the next row of the current panel.
+    fn next_row(&mut self) -> Rectangle {
+        let rect = Rectangle::new(self.cursor.x, self.cursor.y, self.width, ROW_HEIGHT);
+        self.cursor.y += ROW_HEIGHT + PADDING;
+        rect
+    }
+
+    fn next_id(&mut self) -> usize {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    fn rectangle(&mut self, rect: Rectangle, color: Color) {
+        self.commands.push(DrawCommand::Rectangle { rect, color });
+    }
+
+    fn text(&mut self, text: &str, rect: Rectangle, color: Color) {
+        self.commands.push(DrawCommand::Text {
+            text: text.to_string(),
+            position: Vector2::new(
+                rect.x + PADDING,
+                rect.y + (rect.height - FONT_SIZE as f32) / 2.0,
+            ),
+            color,
+        });
+    }
+}
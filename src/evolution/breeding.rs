@@ -0,0 +1,106 @@
+use rand::Rng;
+
+use super::genes::{Gene, GeneTrait, GeneValue, Genome, Nucleotide};
+
+/// Food an organism needs before it can have offspring. The child gets half of it.
+pub const BREEDING_FOOD: u32 = 20;
+
+/// Chance for each nucleotide of a child's genome to be replaced by a random one.
+pub const MUTATION_RATE: f64 = 0.002;
+
+/// Chance for each nucleotide of a child's genome to be lost, and separately to be
+/// followed by an extra random one.
+pub const INDEL_RATE: f64 = 0.0005;
+
+/// Chance for each gene of a child's genome to be lost, and separately to be copied.
+/// Genomes grow and shrink this way, and copies of a gene are free to mutate since
+/// only the first gene for a trait counts.
+pub const DUPLICATION_RATE: f64 = 0.01;
+
+/// How likely an organism is to have offspring in a tick when it has enough food.
+pub struct Breeding {
+    pub chance: f64,
+}
+
+impl Breeding {
+    /// Organisms without a breeding rate gene breed at the medium rate.
+    pub fn from_traits(traits: &[GeneTrait]) -> Self {
+        let chance = traits
+            .iter()
+            .find_map(|t| match t {
+                GeneTrait::BreedingRate(GeneValue::HighBreedingRate) => Some(0.2),
+                GeneTrait::BreedingRate(GeneValue::MediumBreedingRate) => Some(0.1),
+                GeneTrait::BreedingRate(GeneValue::LowBreedingRate) => Some(0.05),
+                GeneTrait::BreedingRate(GeneValue::NoBreedingRate) => Some(0.0),
+                _ => None,
+            })
+            .unwrap_or(0.1);
+
+        Self { chance }
+    }
+}
+
+/// Copy of a genome where every nucleotide has `rate` chance of being replaced by a
+/// random one. A mutated gene may no longer code for anything.
+pub fn mutate(genome: &Genome, rate: f64, rng: &mut impl Rng) -> Genome {
+    let genes = genome
+        .genes
+        .iter()
+        .map(|gene| Gene {
+            sequence: gene
+                .sequence
+                .iter()
+                .map(|nucleotide| {
+                    if rng.gen_bool(rate) {
+                        Nucleotide::from(rng.gen_range(0..4u8))
+                    } else {
+                        *nucleotide
+                    }
+                })
+                .collect(),
+        })
+        .collect();
+
+    Genome { genes }
+}
+
+/// Copy of a genome where every nucleotide has `rate` chance of being lost and `rate`
+/// chance of getting a random nucleotide inserted after it. A gene that changes length
+/// is usually no longer found, the genes around it are not affected.
+pub fn insert_and_delete(genome: &Genome, rate: f64, rng: &mut impl Rng) -> Genome {
+    let genes = genome
+        .genes
+        .iter()
+        .map(|gene| {
+            let mut sequence = Vec::with_capacity(gene.sequence.len());
+            for nucleotide in gene.sequence.iter() {
+                if !rng.gen_bool(rate) {
+                    sequence.push(*nucleotide);
+                }
+                if rng.gen_bool(rate) {
+                    sequence.push(Nucleotide::from(rng.gen_range(0..4u8)));
+                }
+            }
+            Gene { sequence }
+        })
+        .collect();
+
+    Genome { genes }
+}
+
+/// Copy of a genome where every gene has `rate` chance of being lost and `rate` chance
+/// of being followed by a copy of itself.
+pub fn duplicate_and_lose(genome: &Genome, rate: f64, rng: &mut impl Rng) -> Genome {
+    let mut genes = Vec::with_capacity(genome.genes.len());
+    for gene in genome.genes.iter() {
+        if rng.gen_bool(rate) {
+            continue;
+        }
+        genes.push(gene.clone());
+        if rng.gen_bool(rate) {
+            genes.push(gene.clone());
+        }
+    }
+
+    Genome { genes }
+}
@@ -0,0 +1,124 @@
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+};
+
+use super::{
+    genes::{
+        combine_genes_into_one_sequence, get_genome_traits, split_into_genes, GeneTrait, Genome,
+    },
+    Organism,
+};
+
+/// Nucleotides per sequence line when writing, as most tools do.
+pub const FASTA_LINE_WIDTH: usize = 60;
+
+/// One genome with its header line.
+#[derive(Debug, Clone)]
+pub struct FastaRecord {
+    /// The first word of the header.
+    pub id: String,
+    /// The rest of the header, may be empty.
+    pub description: String,
+    pub genome: Genome,
+}
+
+impl FastaRecord {
+    /// Record named `organism_<id>` with the traits of the organism as description.
+    pub fn from_organism(organism: &Organism, gene_map: &HashMap<String, GeneTrait>) -> Self {
+        let traits: Vec<String> = get_genome_traits(&organism.genome, gene_map)
+            .iter()
+            .map(|gene_trait| format!("{:?}", gene_trait))
+            .collect();
+
+        Self {
+            id: format!("organism_{}", organism.id),
+            description: traits.join(" "),
+            genome: organism.genome.clone(),
+        }
+    }
+}
+
+pub fn write_fasta(mut writer: impl Write, records: &[FastaRecord]) -> io::Result<()> {
+    for record in records {
+        if record.description.is_empty() {
+            writeln!(writer, ">{}", record.id)?;
+        } else {
+            writeln!(writer, ">{} {}", record.id, record.description)?;
+        }
+
+        let sequence = combine_genes_into_one_sequence(&record.genome.genes);
+        for line in sequence.chunks(FASTA_LINE_WIDTH) {
+            writeln!(
+                writer,
+                "{}",
+                line.iter().map(|n| char::from(*n)).collect::<String>()
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads every record of a FASTA file. Blank lines are skipped and lower case
+/// nucleotides are accepted, anything else than A, T, C and G in a sequence is an
+/// error. Sequences may have any length, they are cut into genes after every stop
+/// codon.
+pub fn read_fasta(text: &str) -> Result<Vec<FastaRecord>, String> {
+    // Records with the line number of their header.
+    let mut records: Vec<(usize, FastaRecord, String)> = Vec::new();
+
+    for (i, line) in text.lines().enumerate() {
+        let number = i + 1;
+        let line = line.trim_end();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(header) = line.strip_prefix('>') {
+            let (id, description) = header.trim().split_once(' ').unwrap_or((header.trim(), ""));
+            if id.is_empty() {
+                return Err(format!("Line {}: header without a name", number));
+            }
+            records.push((
+                number,
+                FastaRecord {
+                    id: id.to_string(),
+                    description: description.trim().to_string(),
+                    genome: Genome { genes: Vec::new() },
+                },
+                String::new(),
+            ));
+            continue;
+        }
+
+        let Some((_, _, sequence)) = records.last_mut() else {
+            return Err(format!("Line {}: sequence before the first header", number));
+        };
+        for (column, c) in line.chars().enumerate() {
+            match c.to_ascii_uppercase() {
+                c @ ('A' | 'T' | 'C' | 'G') => sequence.push(c),
+                _ => {
+                    return Err(format!(
+                        "Line {}, column {}: invalid nucleotide '{}'",
+                        number,
+                        column + 1,
+                        c
+                    ))
+                }
+            }
+        }
+    }
+
+    records
+        .into_iter()
+        .map(|(number, mut record, sequence)| {
+            if sequence.is_empty() {
+                return Err(format!("Line {}: {} has no sequence", number, record.id));
+            }
+
+            record.genome.genes = split_into_genes(&sequence);
+            Ok(record)
+        })
+        .collect()
+}
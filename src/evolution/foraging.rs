@@ -0,0 +1,291 @@
+use std::f32::consts::PI;
+
+use rand::Rng;
+use raylib::math::Vector2;
+
+use crate::engine::Entity;
+
+use super::{
+    genes::{GeneTrait, GeneValue},
+    Appearance, Body, Organism,
+};
+
+/// How far away an organism notices food.
+pub const SENSING_RADIUS: f32 = 120.0;
+
+/// Most a wandering organism turns per second, in radians.
+const WANDER_TURN_RATE: f32 = 3.0;
+
+/// Food points an organism gets from one piece of food.
+pub const FOOD_ENERGY: u32 = 5;
+
+/// Energy a medium organism moving at medium speed uses per tick. Bigger organisms
+/// use proportionally more, faster ones quadratically more.
+const MOVEMENT_COST: f32 = 0.1;
+const MEDIUM_RADIUS: f32 = 10.0;
+const MEDIUM_SPEED: f32 = 50.0;
+
+/// A piece of food, lying at the position of its `Body`.
+pub struct Food;
+
+/// What an organism eats.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Diet {
+    Herbivore,
+    /// Hunts organisms smaller than itself and gains all the food they stored.
+    Carnivore,
+    Omnivore,
+}
+
+impl Diet {
+    /// Organisms without a diet gene eat plants.
+    pub fn from_traits(traits: &[GeneTrait]) -> Self {
+        traits
+            .iter()
+            .find_map(|t| match t {
+                GeneTrait::Diet(GeneValue::Herbivore) => Some(Diet::Herbivore),
+                GeneTrait::Diet(GeneValue::Carnivore) => Some(Diet::Carnivore),
+                GeneTrait::Diet(GeneValue::Omnivore) => Some(Diet::Omnivore),
+                _ => None,
+            })
+            .unwrap_or(Diet::Herbivore)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Diet::Herbivore => "Herbivore",
+            Diet::Carnivore => "Carnivore",
+            Diet::Omnivore => "Omnivore",
+        }
+    }
+
+    pub fn eats_plants(self) -> bool {
+        matches!(self, Diet::Herbivore | Diet::Omnivore)
+    }
+
+    pub fn eats_meat(self) -> bool {
+        matches!(self, Diet::Carnivore | Diet::Omnivore)
+    }
+}
+
+/// How an organism moves.
+pub struct Movement {
+    /// World units per second.
+    pub speed: f32,
+    /// Direction of travel in radians.
+    pub heading: f32,
+    /// Energy used but not yet taken from the food of the organism, which only
+    /// counts whole points.
+    pub fatigue: f32,
+}
+
+impl Movement {
+    pub fn new(speed: f32, heading: f32) -> Self {
+        Self {
+            speed,
+            heading,
+            fatigue: 0.0,
+        }
+    }
+
+    pub fn velocity(&self) -> Vector2 {
+        Vector2::new(self.heading.cos(), self.heading.sin()) * self.speed
+    }
+}
+
+/// Speed in world units per second. Organisms without a speed gene cannot move.
+pub fn speed_from_traits(traits: &[GeneTrait]) -> f32 {
+    traits
+        .iter()
+        .find_map(|t| match t {
+            GeneTrait::Speed(GeneValue::HighSpeed) => Some(80.0),
+            GeneTrait::Speed(GeneValue::MediumSpeed) => Some(MEDIUM_SPEED),
+            GeneTrait::Speed(GeneValue::LowSpeed) => Some(25.0),
+            GeneTrait::Speed(GeneValue::NoSpeed) => Some(0.0),
+            _ => None,
+        })
+        .unwrap_or(0.0)
+}
+
+/// Energy an organism of `radius` moving at `speed` uses per tick.
+pub fn movement_cost(radius: f32, speed: f32) -> f32 {
+    MOVEMENT_COST * (radius / MEDIUM_RADIUS) * (speed / MEDIUM_SPEED).powi(2)
+}
+
+/// Position and size of an organism, taken before any of them moves.
+struct Prey {
+    id: u32,
+    position: Vector2,
+    radius: f32,
+}
+
+/// Position of the closest thing within `SENSING_RADIUS` of `position`.
+fn nearest(targets: impl Iterator<Item = Vector2>, position: Vector2) -> Option<Vector2> {
+    targets
+        .filter(|target| target.distance_to(position) <= SENSING_RADIUS)
+        .min_by(|a, b| a.distance_to(position).total_cmp(&b.distance_to(position)))
+}
+
+/// Moves organisms and lets them eat food and each other.
+pub struct ForagingSystem;
+
+impl ForagingSystem {
+    /// Turns every organism towards the nearest thing it can eat that it senses, or
+    /// a little to a random side if it senses none, moves it and lets it eat what it
+    /// touches. Plant eaters eat food, meat eaters eat organisms smaller than
+    /// themselves; eaten organisms are removed and their ids returned. Organisms
+    /// bounce off the edges of the `world`.
+    pub fn update(
+        &self,
+        organisms: &mut Vec<Entity>,
+        food: &mut Vec<Entity>,
+        world: Vector2,
+        delta_time: f32,
+        rng: &mut impl Rng,
+    ) -> Vec<u32> {
+        let mut prey: Vec<Prey> = organisms
+            .iter()
+            .filter_map(|entity| {
+                let body = entity.get_component::<Body>()?;
+                let appearance = entity.get_component::<Appearance>()?;
+                Some(Prey {
+                    id: entity.id(),
+                    position: body.position,
+                    radius: appearance.radius,
+                })
+            })
+            .collect();
+        // Predator and prey ids in the order the prey was caught.
+        let mut meals: Vec<(u32, u32)> = Vec::new();
+
+        for entity in organisms.iter_mut() {
+            if meals.iter().any(|&(_, eaten)| eaten == entity.id()) {
+                continue;
+            }
+            let (Some(body), Some(appearance)) = (
+                entity.get_component::<Body>(),
+                entity.get_component::<Appearance>(),
+            ) else {
+                continue;
+            };
+            let (mut position, radius) = (body.position, appearance.radius);
+            let diet = entity
+                .get_component::<Diet>()
+                .copied()
+                .unwrap_or(Diet::Herbivore);
+            let can_eat = |other: &Prey| diet.eats_meat() && other.radius < radius;
+
+            let plants = food
+                .iter()
+                .filter(|_| diet.eats_plants())
+                .filter_map(|entity| entity.get_component::<Body>())
+                .map(|body| body.position);
+            let animals = prey
+                .iter()
+                .filter(|other| can_eat(other))
+                .map(|other| other.position);
+            let target = nearest(plants.chain(animals), position);
+
+            let Some(movement) = entity.get_component_mut::<Movement>() else {
+                continue;
+            };
+            match target {
+                Some(target) => {
+                    movement.heading = (target.y - position.y).atan2(target.x - position.x)
+                }
+                None => {
+                    movement.heading += rng.gen_range(-1.0..=1.0) * WANDER_TURN_RATE * delta_time
+                }
+            }
+
+            position += movement.velocity() * delta_time;
+            if position.x < 0.0 || position.x > world.x {
+                movement.heading = PI - movement.heading;
+                position.x = position.x.clamp(0.0, world.x);
+            }
+            if position.y < 0.0 || position.y > world.y {
+                movement.heading = -movement.heading;
+                position.y = position.y.clamp(0.0, world.y);
+            }
+
+            if let Some(body) = entity.get_component_mut::<Body>() {
+                body.position = position;
+            }
+            if let Some(me) = prey.iter_mut().find(|other| other.id == entity.id()) {
+                me.position = position;
+            }
+
+            let eaten_food = food.iter().position(|food| {
+                diet.eats_plants()
+                    && food
+                        .get_component::<Body>()
+                        .is_some_and(|body| body.position.distance_to(position) <= radius)
+            });
+            if let Some(index) = eaten_food {
+                food.swap_remove(index);
+                if let Some(organism) = entity.get_component_mut::<Organism>() {
+                    organism.food += FOOD_ENERGY;
+                }
+            }
+
+            let caught = prey.iter().position(|other| {
+                can_eat(other) && other.position.distance_to(position) <= radius + other.radius
+            });
+            if let Some(index) = caught {
+                meals.push((entity.id(), prey.swap_remove(index).id));
+            }
+        }
+
+        // In the order the prey was caught, so a predator that is eaten later in the
+        // same frame passes its meal on.
+        for &(predator, eaten) in meals.iter() {
+            let Some(index) = organisms.iter().position(|entity| entity.id() == eaten) else {
+                continue;
+            };
+            let stored = organisms
+                .remove(index)
+                .get_component::<Organism>()
+                .map_or(0, |organism| organism.food);
+
+            if let Some(organism) = organisms
+                .iter_mut()
+                .find(|entity| entity.id() == predator)
+                .and_then(|entity| entity.get_component_mut::<Organism>())
+            {
+                organism.food += stored;
+            }
+        }
+
+        meals.into_iter().map(|(_, eaten)| eaten).collect()
+    }
+
+    /// Takes the energy used for moving during one tick from the food of every
+    /// organism. Organisms without food left lose health instead.
+    pub fn pay(&self, organisms: &mut [Entity]) {
+        for entity in organisms.iter_mut() {
+            let (Some(movement), Some(appearance)) = (
+                entity.get_component::<Movement>(),
+                entity.get_component::<Appearance>(),
+            ) else {
+                continue;
+            };
+            let mut fatigue = movement.fatigue + movement_cost(appearance.radius, movement.speed);
+
+            let Some(organism) = entity.get_component_mut::<Organism>() else {
+                continue;
+            };
+            while fatigue >= 1.0 {
+                fatigue -= 1.0;
+                if organism.food > 0 {
+                    organism.food -= 1;
+                } else {
+                    organism.health = organism.health.saturating_sub(1);
+                }
+            }
+
+            if let Some(movement) = entity.get_component_mut::<Movement>() {
+                movement.fatigue = fatigue;
+            }
+        }
+    }
+}
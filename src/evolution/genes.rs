@@ -1,4 +1,6 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, ops::Range};
+
+use rand::Rng;
 
 use super::Organism;
 
@@ -122,48 +124,64 @@ pub struct Genome {
     pub genes: Vec<Gene>,
 }
 
-pub const GENE_LENGTH: usize = 5; // length of a gene sequence
-
-/// Gene map and their traits
-/// gene segment | trait | gene value | trait value/effect
-/// 0-4          | color | AATTC      | red
-/// 0-4          | color | ATTTC      | green
-/// 0-4          | color | ATCTC      | blue
-/// 0-4          | color | ATGTC      | yellow
-/// 0-4          | color | ATATC      | purple
+pub const GENE_LENGTH: usize = 5; // length of the coding part of a gene
+
+/// Marks the beginning of a gene, the `GENE_LENGTH` nucleotides after it code for a trait.
+pub const START_CODON: &str = "ATG";
+/// One of these right after the coding nucleotides ends a gene.
+pub const STOP_CODONS: [&str; 3] = ["TAA", "TAG", "TGA"];
+const CODON_LENGTH: usize = 3;
+
+/// Most non-coding nucleotides in front of each gene of a random genome.
+const MAX_SPACER_LENGTH: usize = 6;
+
+/// Gene map and their traits. A genome is read by scanning it for genes, which are a
+/// start codon, one of the codes below and a stop codon. Everything between genes
+/// does not code for anything, so genomes can have any length and a nucleotide that
+/// is inserted or lost only breaks the gene it is in.
+/// trait | gene value | trait value/effect
+/// color | AATTC      | red
+/// color | ATTTC      | green
+/// color | ATCTC      | blue
+/// color | ATGTC      | yellow
+/// color | ATATC      | purple
 ///
-/// 5-9          | size  | CCCGT      | small
-/// 5-9          | size  | CCTGT      | medium
-/// 5-9          | size  | CCGGT      | large
+/// size  | CCCGT      | small
+/// size  | CCTGT      | medium
+/// size  | CCGGT      | large
 ///
-/// 10-14        | shape | GGGGA      | round
-/// 10-14        | shape | GGCGA      | square
+/// shape | GGGGA      | round
+/// shape | GGCGA      | square
 ///
-/// 15-19        | temperature resistance | TTTTT | high (can survive in extreme heat and cold)
-/// 15-19        | temperature resistance | TTGTT | medium (can survive in moderate heat and cold)
-/// 15-19        | temperature resistance | TTATT | low (can only survive in moderate heat)
-/// 15-19        | temperature resistance | TTAAT | none (cannot survive at all)
+/// temperature resistance | TTTTT | high (can survive in extreme heat and cold)
+/// temperature resistance | TTGTT | medium (can survive in moderate heat and cold)
+/// temperature resistance | TTATT | low (can only survive in moderate heat)
+/// temperature resistance | TTAAT | none (cannot survive at all)
 ///
-/// 20-24        | water resistance | AAAAA | high (can survive in water)
-/// 20-24        | water resistance | AAATA | none (cannot survive in water)
+/// water resistance | AAAAA | high (can survive in water)
+/// water resistance | AAATA | none (cannot survive in water)
 ///
-/// 25-29        | metabolism | GCGCG | high (can survive on very little food)
-/// 25-29        | metabolism | GCGTG | medium (can survive on moderate food)
-/// 25-29        | metabolism | GCGAG | low (needs a lot of food)
+/// metabolism | GCGCG | high (can survive on very little food)
+/// metabolism | GCGTG | medium (can survive on moderate food)
+/// metabolism | GCGAG | low (needs a lot of food)
 ///
-/// 30-34        | speed | TTTTT | high (can move very fast)
-/// 30-34        | speed | TTGTT | medium (can move at a moderate pace)
-/// 30-34        | speed | TTATT | low (can only move slowly)
-/// 30-34        | speed | TTAAT | none (cannot move at all)
+/// speed | TCTCT | high (can move very fast)
+/// speed | TCGCT | medium (can move at a moderate pace)
+/// speed | TCACT | low (can only move slowly)
+/// speed | TCAAT | none (cannot move at all)
 ///
-/// 35-39        | breeding rate | CCCCC | high (can reproduce quickly)
-/// 35-39        | breeding rate | CCCTC | medium (can reproduce at a moderate pace)
-/// 35-39        | breeding rate | CCCAC | low (reproduces slowly)
-/// 35-39        | breeding rate | CCCAA | none (cannot reproduce)
+/// breeding rate | CCCCC | high (can reproduce quickly)
+/// breeding rate | CCCTC | medium (can reproduce at a moderate pace)
+/// breeding rate | CCCAC | low (reproduces slowly)
+/// breeding rate | CCCAA | none (cannot reproduce)
 ///
-/// 40-44        | lifespan | GGGGG | long (can live for a long time)
-/// 40-44        | lifespan | GGCGG | medium (can live for a moderate amount of time)
-/// 40-44        | lifespan | GGAGG | short (lives for a short amount of time)
+/// lifespan | GGGGG | long (can live for a long time)
+/// lifespan | GGCGG | medium (can live for a moderate amount of time)
+/// lifespan | GGAGG | short (lives for a short amount of time)
+///
+/// diet | AGAGA | herbivore (eats plants)
+/// diet | AGCGA | carnivore (hunts smaller organisms)
+/// diet | AGTGA | omnivore (eats plants and hunts smaller organisms)
 #[derive(Debug, Clone)]
 pub enum GeneTrait {
     Color(GeneValue),
@@ -175,6 +193,40 @@ pub enum GeneTrait {
     Speed(GeneValue),
     BreedingRate(GeneValue),
     Lifespan(GeneValue),
+    Diet(GeneValue),
+}
+
+impl GeneTrait {
+    /// Name of the kind of trait, without the value.
+    pub fn name(&self) -> &'static str {
+        match self {
+            GeneTrait::Color(_) => "Color",
+            GeneTrait::Size(_) => "Size",
+            GeneTrait::Shape(_) => "Shape",
+            GeneTrait::TemperatureResistance(_) => "TemperatureResistance",
+            GeneTrait::WaterResistance(_) => "WaterResistance",
+            GeneTrait::Metabolism(_) => "Metabolism",
+            GeneTrait::Speed(_) => "Speed",
+            GeneTrait::BreedingRate(_) => "BreedingRate",
+            GeneTrait::Lifespan(_) => "Lifespan",
+            GeneTrait::Diet(_) => "Diet",
+        }
+    }
+
+    pub fn value(&self) -> &GeneValue {
+        match self {
+            GeneTrait::Color(value)
+            | GeneTrait::Size(value)
+            | GeneTrait::Shape(value)
+            | GeneTrait::TemperatureResistance(value)
+            | GeneTrait::WaterResistance(value)
+            | GeneTrait::Metabolism(value)
+            | GeneTrait::Speed(value)
+            | GeneTrait::BreedingRate(value)
+            | GeneTrait::Lifespan(value)
+            | GeneTrait::Diet(value) => value,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -209,6 +261,9 @@ pub enum GeneValue {
     LongLifespan,
     MediumLifespan,
     ShortLifespan,
+    Herbivore,
+    Carnivore,
+    Omnivore,
 }
 
 pub fn create_gene_map() -> HashMap<String, GeneTrait> {
@@ -266,13 +321,13 @@ pub fn create_gene_map() -> HashMap<String, GeneTrait> {
         GeneTrait::Metabolism(GeneValue::LowMetabolism),
     );
 
-    gene_map.insert("TTTTT".to_string(), GeneTrait::Speed(GeneValue::HighSpeed));
+    gene_map.insert("TCTCT".to_string(), GeneTrait::Speed(GeneValue::HighSpeed));
     gene_map.insert(
-        "TTGTT".to_string(),
+        "TCGCT".to_string(),
         GeneTrait::Speed(GeneValue::MediumSpeed),
     );
-    gene_map.insert("TTATT".to_string(), GeneTrait::Speed(GeneValue::LowSpeed));
-    gene_map.insert("TTAAT".to_string(), GeneTrait::Speed(GeneValue::NoSpeed));
+    gene_map.insert("TCACT".to_string(), GeneTrait::Speed(GeneValue::LowSpeed));
+    gene_map.insert("TCAAT".to_string(), GeneTrait::Speed(GeneValue::NoSpeed));
 
     gene_map.insert(
         "CCCCC".to_string(),
@@ -304,29 +359,136 @@ pub fn create_gene_map() -> HashMap<String, GeneTrait> {
         GeneTrait::Lifespan(GeneValue::ShortLifespan),
     );
 
+    gene_map.insert("AGAGA".to_string(), GeneTrait::Diet(GeneValue::Herbivore));
+    gene_map.insert("AGCGA".to_string(), GeneTrait::Diet(GeneValue::Carnivore));
+    gene_map.insert("AGTGA".to_string(), GeneTrait::Diet(GeneValue::Omnivore));
+
     gene_map
 }
 
+/// Positions of the coding nucleotides of every gene in a genome sequence, in order.
+/// Scanning goes on after the stop codon of a gene, so genes do not overlap.
+pub fn find_coding_regions(genome_sequence: &str) -> Vec<Range<usize>> {
+    let mut regions = Vec::new();
+    let gene_length = CODON_LENGTH + GENE_LENGTH + CODON_LENGTH;
+
+    let mut i = 0;
+    while i + gene_length <= genome_sequence.len() {
+        let code = i + CODON_LENGTH..i + CODON_LENGTH + GENE_LENGTH;
+        let stop = &genome_sequence[code.end..code.end + CODON_LENGTH];
+        if genome_sequence[i..].starts_with(START_CODON) && STOP_CODONS.contains(&stop) {
+            i = code.end + CODON_LENGTH;
+            regions.push(code);
+        } else {
+            i += 1;
+        }
+    }
+
+    regions
+}
+
+/// Trait of every gene found in the genome sequence, `None` for genes whose code is
+/// not in the gene map.
 pub fn get_gene_traits_from_genome_sequence(
     genome_sequence: &str,
     gene_map: &HashMap<String, GeneTrait>,
 ) -> Vec<Option<GeneTrait>> {
-    let mut traits = Vec::new();
-
-    for i in (0..genome_sequence.len()).step_by(GENE_LENGTH) {
-        let segment = &genome_sequence[i..i + GENE_LENGTH];
-        let gene_trait = gene_map.get(segment);
+    find_coding_regions(genome_sequence)
+        .into_iter()
+        .map(|code| gene_map.get(&genome_sequence[code]).cloned())
+        .collect()
+}
 
-        traits.push(gene_trait.cloned());
-    }
+/// Gene with the start codon in front of the coding nucleotides and a stop codon
+/// after them.
+pub fn mark_gene(code: &[Nucleotide]) -> Gene {
+    let mut sequence = create_sequence_from_str(START_CODON);
+    sequence.extend_from_slice(code);
+    sequence.extend(create_sequence_from_str(STOP_CODONS[0]));
+    Gene { sequence }
+}
 
-    traits
+/// Cuts a genome sequence into genes, each with the non-coding nucleotides in front of
+/// it. Nucleotides after the last gene become a gene of their own.
+pub fn split_into_genes(genome_sequence: &str) -> Vec<Gene> {
+    let mut genes = Vec::new();
+    let mut start = 0;
+    for code in find_coding_regions(genome_sequence) {
+        let end = code.end + CODON_LENGTH;
+        genes.push(Gene {
+            sequence: create_sequence_from_str(&genome_sequence[start..end]),
+        });
+        start = end;
+    }
+
+    if start < genome_sequence.len() {
+        genes.push(Gene {
+            sequence: create_sequence_from_str(&genome_sequence[start..]),
+        });
+    }
+    genes
 }
 
 pub fn get_only_existing_traits(traits: Vec<Option<GeneTrait>>) -> Vec<GeneTrait> {
     traits.into_iter().filter_map(|t| t).collect()
 }
 
+pub fn get_genome_traits(genome: &Genome, gene_map: &HashMap<String, GeneTrait>) -> Vec<GeneTrait> {
+    let genome_sequence = combine_genes_into_one_sequence(&genome.genes);
+    let genome_sequence_str = genome_sequence
+        .iter()
+        .map(|n| char::from(*n))
+        .collect::<String>();
+    let traits = get_gene_traits_from_genome_sequence(&genome_sequence_str, gene_map);
+
+    get_only_existing_traits(traits)
+}
+
+/// Sequences of the gene map grouped by the trait they code for.
+/// Groups and the sequences in them are sorted, so the order does not depend on the map.
+pub fn get_gene_sequences_by_trait(gene_map: &HashMap<String, GeneTrait>) -> Vec<Vec<String>> {
+    let mut groups: Vec<(std::mem::Discriminant<GeneTrait>, Vec<String>)> = Vec::new();
+
+    for (sequence, gene_trait) in gene_map.iter() {
+        let kind = std::mem::discriminant(gene_trait);
+        match groups
+            .iter_mut()
+            .find(|(group_kind, _)| *group_kind == kind)
+        {
+            Some((_, sequences)) => sequences.push(sequence.clone()),
+            None => groups.push((kind, vec![sequence.clone()])),
+        }
+    }
+
+    let mut groups: Vec<Vec<String>> = groups
+        .into_iter()
+        .map(|(_, mut sequences)| {
+            sequences.sort();
+            sequences
+        })
+        .collect();
+    groups.sort();
+    groups
+}
+
+/// Genome with one randomly picked gene for every trait of the gene map, each after a
+/// random number of non-coding nucleotides.
+pub fn create_random_genome(rng: &mut impl Rng, gene_map: &HashMap<String, GeneTrait>) -> Genome {
+    let genes = get_gene_sequences_by_trait(gene_map)
+        .iter()
+        .map(|sequences| {
+            let mut sequence: Sequence = (0..rng.gen_range(0..=MAX_SPACER_LENGTH))
+                .map(|_| Nucleotide::from(rng.gen_range(0..4u8)))
+                .collect();
+            let code = create_sequence_from_str(&sequences[rng.gen_range(0..sequences.len())]);
+            sequence.extend(mark_gene(&code).sequence);
+            Gene { sequence }
+        })
+        .collect();
+
+    Genome { genes }
+}
+
 pub fn create_organism_based_on_genome(
     genome: Genome,
     gene_map: &HashMap<String, GeneTrait>,
@@ -365,6 +527,8 @@ pub fn create_organism_based_on_genome(
 
     Organism {
         genome,
+        id: 0,
+        parents: Vec::new(),
         food,
         age: 0,
         health,
@@ -372,11 +536,14 @@ pub fn create_organism_based_on_genome(
     }
 }
 
+/// Builds genes by name instead of by sequence, used to write genomes by hand in tests.
+#[cfg(test)]
 pub struct GeneBuilder {
     gene_map: HashMap<String, GeneTrait>,
     gene: Gene,
 }
 
+#[cfg(test)]
 impl GeneBuilder {
     pub fn new() -> Self {
         GeneBuilder {
@@ -493,22 +660,22 @@ impl GeneBuilder {
     }
 
     pub fn high_speed(&mut self) -> &mut Self {
-        self.gene.sequence = create_sequence_from_str("TTTTT");
+        self.gene.sequence = create_sequence_from_str("TCTCT");
         self
     }
 
     pub fn medium_speed(&mut self) -> &mut Self {
-        self.gene.sequence = create_sequence_from_str("TTGTT");
+        self.gene.sequence = create_sequence_from_str("TCGCT");
         self
     }
 
     pub fn low_speed(&mut self) -> &mut Self {
-        self.gene.sequence = create_sequence_from_str("TTATT");
+        self.gene.sequence = create_sequence_from_str("TCACT");
         self
     }
 
     pub fn no_speed(&mut self) -> &mut Self {
-        self.gene.sequence = create_sequence_from_str("TTAAT");
+        self.gene.sequence = create_sequence_from_str("TCAAT");
         self
     }
 
@@ -547,6 +714,21 @@ impl GeneBuilder {
         self
     }
 
+    pub fn herbivore(&mut self) -> &mut Self {
+        self.gene.sequence = create_sequence_from_str("AGAGA");
+        self
+    }
+
+    pub fn carnivore(&mut self) -> &mut Self {
+        self.gene.sequence = create_sequence_from_str("AGCGA");
+        self
+    }
+
+    pub fn omnivore(&mut self) -> &mut Self {
+        self.gene.sequence = create_sequence_from_str("AGTGA");
+        self
+    }
+
     pub fn build_genes_from_sequences(&self, sequences: Vec<&str>) -> Vec<Gene> {
         sequences
             .iter()
@@ -554,8 +736,11 @@ impl GeneBuilder {
             .collect()
     }
 
+    /// Genome of the given codes, each marked with the start and stop codons.
     pub fn build_genome(&self, genes: Vec<Gene>) -> Genome {
-        Genome { genes }
+        Genome {
+            genes: genes.iter().map(|gene| mark_gene(&gene.sequence)).collect(),
+        }
     }
 
     pub fn build_organism(&self, genome: Genome) -> Organism {
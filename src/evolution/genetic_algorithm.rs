@@ -0,0 +1,435 @@
+use std::collections::HashMap;
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::{
+    breeding::mutate,
+    foraging::{movement_cost, speed_from_traits},
+    genes::{
+        combine_genes_into_one_sequence, create_gene_map, create_organism_based_on_genome,
+        create_random_genome, get_genome_traits, GeneTrait, Genome,
+    },
+    Appearance, Organism, TICK_DURATION,
+};
+
+/// Scores an organism. Higher is better, and roulette selection also needs the score
+/// to never be negative. The organism can be read through its accessors, like
+/// `Organism::genome` and `Organism::food`.
+pub trait Fitness {
+    fn fitness(&self, organism: &Organism, traits: &[GeneTrait]) -> f64;
+}
+
+/// How parents are picked from a generation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Selection {
+    /// The fittest of `size` organisms picked at random.
+    Tournament { size: usize },
+    /// Chance proportional to fitness.
+    Roulette,
+    /// Chance proportional to the position in the population sorted by fitness, so a
+    /// few much fitter organisms do not take over the population at once.
+    Rank,
+}
+
+/// How the genes of two parents are combined. Cuts are only made between genes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Crossover {
+    /// Genes before a random point come from one parent, the rest from the other.
+    SinglePoint,
+    /// Genes between two random points come from the second parent.
+    TwoPoint,
+    /// Every gene comes from a random parent.
+    Uniform,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GaConfig {
+    pub population_size: usize,
+    /// The fittest organisms copied unchanged into the next generation.
+    pub elitism: usize,
+    pub selection: Selection,
+    pub crossover: Crossover,
+    /// Chance of two parents being crossed over, otherwise the child is a copy of the
+    /// first one.
+    pub crossover_rate: f64,
+    /// Chance for each nucleotide of a child to be replaced by a random one.
+    pub mutation_rate: f64,
+    pub max_generations: u32,
+    /// The run has converged when the best fitness improved by no more than
+    /// `tolerance` over this many generations.
+    pub stall_generations: u32,
+    pub tolerance: f64,
+}
+
+impl Default for GaConfig {
+    fn default() -> Self {
+        Self {
+            population_size: 100,
+            elitism: 2,
+            selection: Selection::Tournament { size: 3 },
+            crossover: Crossover::SinglePoint,
+            crossover_rate: 0.9,
+            mutation_rate: 0.01,
+            max_generations: 500,
+            stall_generations: 50,
+            tolerance: 1e-6,
+        }
+    }
+}
+
+/// Fitness of a generation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GenerationStats {
+    pub generation: u32,
+    pub best: f64,
+    pub mean: f64,
+    pub worst: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StopReason {
+    Converged,
+    MaxGenerations,
+}
+
+pub struct Individual {
+    pub genome: Genome,
+    pub fitness: f64,
+}
+
+/// Classic generational genetic algorithm over genomes, as opposed to the open-ended
+/// ecology of the `Evolution` world.
+pub struct GeneticAlgorithm<F: Fitness> {
+    config: GaConfig,
+    fitness: F,
+    gene_map: HashMap<String, GeneTrait>,
+    /// Sorted by fitness, fittest first.
+    population: Vec<Individual>,
+    history: Vec<GenerationStats>,
+}
+
+impl<F: Fitness> GeneticAlgorithm<F> {
+    /// Starts with a population of random genomes.
+    pub fn new(config: GaConfig, fitness: F, rng: &mut impl Rng) -> Self {
+        let gene_map = create_gene_map();
+        let genomes = (0..config.population_size)
+            .map(|_| create_random_genome(rng, &gene_map))
+            .collect();
+
+        let mut algorithm = Self {
+            config,
+            fitness,
+            gene_map,
+            population: Vec::new(),
+            history: Vec::new(),
+        };
+        algorithm.replace_population(genomes);
+        algorithm
+    }
+
+    /// The fittest organism so far. Elitism keeps it in the population.
+    pub fn best(&self) -> Option<&Individual> {
+        self.population.first()
+    }
+
+    pub fn history(&self) -> &[GenerationStats] {
+        &self.history
+    }
+
+    /// Breeds the next generation.
+    pub fn step(&mut self, rng: &mut impl Rng) -> GenerationStats {
+        let elites = self.config.elitism.min(self.population.len());
+        let mut genomes: Vec<Genome> = self.population[..elites]
+            .iter()
+            .map(|individual| individual.genome.clone())
+            .collect();
+
+        while genomes.len() < self.config.population_size {
+            let first = &select(&self.population, self.config.selection, rng).genome;
+            let second = &select(&self.population, self.config.selection, rng).genome;
+            let child = if rng.gen_bool(self.config.crossover_rate) {
+                crossover(first, second, self.config.crossover, rng)
+            } else {
+                first.clone()
+            };
+            genomes.push(mutate(&child, self.config.mutation_rate, rng));
+        }
+
+        self.replace_population(genomes)
+    }
+
+    /// Whether the best fitness stopped improving, see `GaConfig::stall_generations`.
+    pub fn converged(&self) -> bool {
+        let stall = self.config.stall_generations as usize;
+        if self.history.len() <= stall {
+            return false;
+        }
+
+        let latest = self.history[self.history.len() - 1].best;
+        let before = self.history[self.history.len() - 1 - stall].best;
+        latest - before <= self.config.tolerance
+    }
+
+    /// Runs generations until the population converges or `max_generations` is reached.
+    pub fn run(&mut self, rng: &mut impl Rng) -> StopReason {
+        while !self.converged() {
+            if self.history.len() > self.config.max_generations as usize {
+                return StopReason::MaxGenerations;
+            }
+            self.step(rng);
+        }
+
+        StopReason::Converged
+    }
+
+    fn replace_population(&mut self, genomes: Vec<Genome>) -> GenerationStats {
+        let mut population: Vec<Individual> = genomes
+            .into_iter()
+            .map(|genome| Individual {
+                fitness: self.evaluate(&genome),
+                genome,
+            })
+            .collect();
+        population.sort_by(|a, b| b.fitness.total_cmp(&a.fitness));
+        self.population = population;
+
+        let fitness = self.population.iter().map(|individual| individual.fitness);
+        let stats = GenerationStats {
+            generation: self.history.len() as u32,
+            best: fitness.clone().fold(f64::MIN, f64::max),
+            mean: fitness.clone().sum::<f64>() / self.population.len().max(1) as f64,
+            worst: fitness.fold(f64::MAX, f64::min),
+        };
+        self.history.push(stats);
+        stats
+    }
+
+    fn evaluate(&self, genome: &Genome) -> f64 {
+        let organism = create_organism_based_on_genome(genome.clone(), &self.gene_map);
+        let traits = get_genome_traits(genome, &self.gene_map);
+        self.fitness.fitness(&organism, &traits)
+    }
+}
+
+/// Picks a parent from a population sorted fittest first.
+pub fn select<'a>(
+    population: &'a [Individual],
+    selection: Selection,
+    rng: &mut impl Rng,
+) -> &'a Individual {
+    let index = match selection {
+        Selection::Tournament { size } => (0..size.max(1))
+            .map(|_| rng.gen_range(0..population.len()))
+            .min()
+            .unwrap_or(0),
+        Selection::Roulette => {
+            let weights: Vec<f64> = population
+                .iter()
+                .map(|individual| individual.fitness.max(0.0))
+                .collect();
+            pick_weighted(&weights, rng)
+        }
+        Selection::Rank => {
+            let weights: Vec<f64> = (0..population.len())
+                .map(|i| (population.len() - i) as f64)
+                .collect();
+            pick_weighted(&weights, rng)
+        }
+    };
+
+    &population[index]
+}
+
+/// Index picked with a chance proportional to its weight, or uniformly if all
+/// weights are zero.
+fn pick_weighted(weights: &[f64], rng: &mut impl Rng) -> usize {
+    let total: f64 = weights.iter().sum();
+    if total <= 0.0 {
+        return rng.gen_range(0..weights.len());
+    }
+
+    let mut target = rng.gen_range(0.0..total);
+    for (i, weight) in weights.iter().enumerate() {
+        if target < *weight {
+            return i;
+        }
+        target -= weight;
+    }
+
+    weights.len() - 1
+}
+
+/// Child of two genomes. When the parents have a different number of genes, the
+/// genes past the end of the shorter one come from the first parent.
+pub fn crossover(
+    first: &Genome,
+    second: &Genome,
+    crossover: Crossover,
+    rng: &mut impl Rng,
+) -> Genome {
+    let shared = first.genes.len().min(second.genes.len());
+    let from_second: Vec<bool> = match crossover {
+        Crossover::SinglePoint => {
+            let point = rng.gen_range(0..=shared);
+            (0..shared).map(|i| i >= point).collect()
+        }
+        Crossover::TwoPoint => {
+            let a = rng.gen_range(0..=shared);
+            let b = rng.gen_range(0..=shared);
+            let (start, end) = (a.min(b), a.max(b));
+            (0..shared).map(|i| i >= start && i < end).collect()
+        }
+        Crossover::Uniform => (0..shared).map(|_| rng.gen_bool(0.5)).collect(),
+    };
+
+    let genes = first
+        .genes
+        .iter()
+        .enumerate()
+        .map(|(i, gene)| match from_second.get(i) {
+            Some(true) => second.genes[i].clone(),
+            _ => gene.clone(),
+        })
+        .collect();
+
+    Genome { genes }
+}
+
+/// Distance in world units an organism can travel in the `Evolution` world before it
+/// starves or dies of old age, if it never finds food. Rewards the same speed, size
+/// and metabolism trade-off as foraging does.
+pub struct EnduranceFitness;
+
+impl Fitness for EnduranceFitness {
+    fn fitness(&self, organism: &Organism, traits: &[GeneTrait]) -> f64 {
+        let speed = speed_from_traits(traits);
+        let cost = movement_cost(Appearance::from_traits(traits).radius, speed);
+        let lifespan = organism.lifespan() as f32;
+        let ticks = if cost > 0.0 {
+            ((organism.food() + organism.health()) as f32 / cost).min(lifespan)
+        } else {
+            lifespan
+        };
+
+        (speed * ticks * TICK_DURATION) as f64
+    }
+}
+
+/// Command line options of the genetic algorithm.
+pub struct GaOptions {
+    pub config: GaConfig,
+    pub seed: u64,
+}
+
+impl GaOptions {
+    /// Parses `--population`, `--generations`, `--elitism`, `--selection`
+    /// (`tournament`, `roulette` or `rank`), `--tournament-size`, `--crossover`
+    /// (`single`, `two` or `uniform`), `--crossover-rate`, `--mutation`, `--stall` and
+    /// `--seed`, each followed by a value.
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = Self {
+            config: GaConfig::default(),
+            seed: 0,
+        };
+        let mut tournament_size = 3;
+
+        let mut args = args.iter();
+        while let Some(flag) = args.next() {
+            let value = args
+                .next()
+                .ok_or_else(|| format!("Missing value for {}", flag))?;
+            let invalid = || format!("Invalid value \"{}\" for {}", value, flag);
+            let count = || value.parse::<usize>().map_err(|_| invalid());
+            let rate = || {
+                value
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|rate| (0.0..=1.0).contains(rate))
+                    .ok_or_else(invalid)
+            };
+
+            match flag.as_str() {
+                "--population" => options.config.population_size = count()?.max(1),
+                "--generations" => options.config.max_generations = count()? as u32,
+                "--elitism" => options.config.elitism = count()?,
+                "--selection" => {
+                    options.config.selection = match value.as_str() {
+                        "tournament" => Selection::Tournament { size: 0 },
+                        "roulette" => Selection::Roulette,
+                        "rank" => Selection::Rank,
+                        _ => return Err(invalid()),
+                    }
+                }
+                "--tournament-size" => tournament_size = count()?.max(1),
+                "--crossover" => {
+                    options.config.crossover = match value.as_str() {
+                        "single" => Crossover::SinglePoint,
+                        "two" => Crossover::TwoPoint,
+                        "uniform" => Crossover::Uniform,
+                        _ => return Err(invalid()),
+                    }
+                }
+                "--crossover-rate" => options.config.crossover_rate = rate()?,
+                "--mutation" => options.config.mutation_rate = rate()?,
+                "--stall" => options.config.stall_generations = count()? as u32,
+                "--seed" => options.seed = value.parse::<u64>().map_err(|_| invalid())?,
+                _ => return Err(format!("Unknown option {}", flag)),
+            }
+        }
+
+        if let Selection::Tournament { size } = &mut options.config.selection {
+            *size = tournament_size;
+        }
+
+        Ok(options)
+    }
+}
+
+/// Runs the genetic algorithm with `EnduranceFitness` without opening a window and
+/// prints the fitness of every generation and the best genome found.
+pub fn run(args: Vec<String>) {
+    let options = match GaOptions::parse(&args) {
+        Ok(options) => options,
+        Err(error) => {
+            println!("{}", error);
+            println!(
+                "Usage: evolution-ga [--population 100] [--generations 500] [--elitism 2] \
+                 [--selection tournament|roulette|rank] [--tournament-size 3] \
+                 [--crossover single|two|uniform] [--crossover-rate 0.9] [--mutation 0.01] \
+                 [--stall 50] [--seed 0]"
+            );
+            return;
+        }
+    };
+
+    let mut rng = StdRng::seed_from_u64(options.seed);
+    let mut algorithm = GeneticAlgorithm::new(options.config, EnduranceFitness, &mut rng);
+    let reason = algorithm.run(&mut rng);
+
+    println!("generation,best,mean,worst");
+    for stats in algorithm.history() {
+        println!(
+            "{},{:.1},{:.1},{:.1}",
+            stats.generation, stats.best, stats.mean, stats.worst
+        );
+    }
+
+    let generations = algorithm.history().len() - 1;
+    match reason {
+        StopReason::Converged => println!("Converged after {} generations", generations),
+        StopReason::MaxGenerations => println!("Stopped after {} generations", generations),
+    }
+
+    if let Some(best) = algorithm.best() {
+        let sequence: String = combine_genes_into_one_sequence(&best.genome.genes)
+            .iter()
+            .map(|n| char::from(*n))
+            .collect();
+        println!("Best fitness: {:.1}", best.fitness);
+        println!("Best genome: {}", sequence);
+        println!(
+            "Traits: {:?}",
+            get_genome_traits(&best.genome, &algorithm.gene_map)
+        );
+    }
+}
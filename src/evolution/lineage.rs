@@ -0,0 +1,193 @@
+use std::collections::{HashMap, HashSet};
+
+use super::genes::{combine_genes_into_one_sequence, Genome};
+
+/// Birth, death and parentage of one organism.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineageRecord {
+    pub id: u32,
+    /// Empty for organisms that were not born from others.
+    pub parents: Vec<u32>,
+    pub birth: u64,
+    /// `None` while the organism is alive.
+    pub death: Option<u64>,
+    pub genome_hash: u64,
+}
+
+/// FNV-1a hash of the nucleotides of a genome. Equal genomes have equal hashes, also
+/// between runs, so hashes in exported trees can be compared.
+pub fn genome_hash(genome: &Genome) -> u64 {
+    combine_genes_into_one_sequence(&genome.genes)
+        .iter()
+        .fold(0xcbf29ce484222325, |hash, nucleotide| {
+            (hash ^ u8::from(*nucleotide) as u64).wrapping_mul(0x100000001b3)
+        })
+}
+
+/// Every organism that ever lived and who descended from whom. Times are in ticks.
+#[derive(Debug, Default)]
+pub struct Lineage {
+    records: HashMap<u32, LineageRecord>,
+    /// Ids of the children of every organism, in the order they were born.
+    children: HashMap<u32, Vec<u32>>,
+}
+
+impl Lineage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_birth(&mut self, id: u32, parents: &[u32], genome: &Genome, tick: u64) {
+        self.insert(LineageRecord {
+            id,
+            parents: parents.to_vec(),
+            birth: tick,
+            death: None,
+            genome_hash: genome_hash(genome),
+        });
+    }
+
+    /// Adds a record as it is, for example one read back from a snapshot. Records have
+    /// to be inserted in the order the organisms were born.
+    pub fn insert(&mut self, record: LineageRecord) {
+        for parent in record.parents.iter() {
+            self.children.entry(*parent).or_default().push(record.id);
+        }
+        self.records.insert(record.id, record);
+    }
+
+    /// Every record, in no particular order.
+    pub fn records(&self) -> impl Iterator<Item = &LineageRecord> {
+        self.records.values()
+    }
+
+    pub fn record_death(&mut self, id: u32, tick: u64) {
+        if let Some(record) = self.records.get_mut(&id) {
+            record.death.get_or_insert(tick);
+        }
+    }
+
+    pub fn get(&self, id: u32) -> Option<&LineageRecord> {
+        self.records.get(&id)
+    }
+
+    /// Ids of an organism and all its recorded ancestors.
+    pub fn ancestors(&self, id: u32) -> HashSet<u32> {
+        let mut ancestors = HashSet::new();
+        let mut next = vec![id];
+        while let Some(id) = next.pop() {
+            if self.records.contains_key(&id) && ancestors.insert(id) {
+                next.extend(self.records[&id].parents.iter().copied());
+            }
+        }
+
+        ancestors
+    }
+
+    /// The latest born organism that both are descended from, counting an organism as
+    /// descended from itself.
+    pub fn most_recent_common_ancestor(&self, a: u32, b: u32) -> Option<u32> {
+        let ancestors_of_b = self.ancestors(b);
+        self.ancestors(a)
+            .into_iter()
+            .filter(|id| ancestors_of_b.contains(id))
+            .max_by_key(|id| (self.records[id].birth, *id))
+    }
+
+    /// Organisms that are alive or have living descendants.
+    fn with_living_descendants(&self) -> HashSet<u32> {
+        let mut living = HashSet::new();
+        let mut next: Vec<u32> = self
+            .records
+            .values()
+            .filter(|record| record.death.is_none())
+            .map(|record| record.id)
+            .collect();
+        // Ancestors that are already in the set had theirs added before.
+        while let Some(id) = next.pop() {
+            if self.records.contains_key(&id) && living.insert(id) {
+                next.extend(self.records[&id].parents.iter().copied());
+            }
+        }
+
+        living
+    }
+
+    /// The most recent common ancestor of all living organisms.
+    pub fn living_common_ancestor(&self) -> Option<u32> {
+        let mut living = self
+            .records
+            .values()
+            .filter(|record| record.death.is_none())
+            .map(|record| record.id);
+        let first = living.next()?;
+        living.try_fold(first, |ancestor, id| {
+            self.most_recent_common_ancestor(ancestor, id)
+        })
+    }
+
+    /// Ids of the organisms without parents that still have living descendants,
+    /// themselves included, oldest first.
+    pub fn surviving_lineages(&self) -> Vec<u32> {
+        let living = self.with_living_descendants();
+        let mut founders: Vec<u32> = self
+            .records
+            .values()
+            .filter(|record| record.parents.is_empty() && living.contains(&record.id))
+            .map(|record| record.id)
+            .collect();
+        founders.sort_by_key(|id| (self.records[id].birth, *id));
+        founders
+    }
+
+    /// The phylogeny in Newick format. Nodes are named by id, and a branch is as long
+    /// as the ticks between the birth of the parent and the child. Organisms with two
+    /// parents are placed under the first one, since a tree cannot show both.
+    /// With `surviving_only`, dead branches without living descendants are left out.
+    pub fn to_newick(&self, surviving_only: bool) -> String {
+        let living = self.with_living_descendants();
+        let include = |id: &u32| !surviving_only || living.contains(id);
+
+        let mut roots: Vec<u32> = self
+            .records
+            .values()
+            .filter(|record| {
+                record
+                    .parents
+                    .first()
+                    .is_none_or(|parent| !self.records.contains_key(parent))
+            })
+            .map(|record| record.id)
+            .filter(include)
+            .collect();
+        roots.sort_by_key(|id| (self.records[id].birth, *id));
+
+        let trees: Vec<String> = roots
+            .iter()
+            .map(|root| self.newick_node(*root, 0, &include))
+            .collect();
+        match trees.len() {
+            1 => format!("{};", trees[0]),
+            _ => format!("({});", trees.join(",")),
+        }
+    }
+
+    fn newick_node(&self, id: u32, parent_birth: u64, include: &impl Fn(&u32) -> bool) -> String {
+        let record = &self.records[&id];
+        let children: Vec<String> = self
+            .children
+            .get(&id)
+            .into_iter()
+            .flatten()
+            .filter(|child| self.records[child].parents.first() == Some(&id) && include(child))
+            .map(|child| self.newick_node(*child, record.birth, include))
+            .collect();
+
+        let length = record.birth - parent_birth;
+        if children.is_empty() {
+            format!("{}:{}", id, length)
+        } else {
+            format!("({}){}:{}", children.join(","), id, length)
+        }
+    }
+}
@@ -1,15 +1,698 @@
+use std::{collections::HashMap, f32::consts::PI};
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use raylib::{
+    color::Color,
+    consts::{KeyboardKey, MouseButton},
+    math::{Rectangle, Vector2},
+    prelude::{RaylibDraw, RaylibDrawHandle},
+    RaylibHandle, RaylibThread,
+};
+
+use crate::engine::{
+    camera::Camera,
+    ui::{Ui, UiInput},
+    Entity, EntityBuilder, Game, GameState,
+};
+use breeding::{
+    duplicate_and_lose, insert_and_delete, mutate, Breeding, BREEDING_FOOD, DUPLICATION_RATE,
+    INDEL_RATE, MUTATION_RATE,
+};
+use fasta::{read_fasta, write_fasta, FastaRecord};
+use foraging::{speed_from_traits, Diet, Food, ForagingSystem, Movement};
 use genes::{
-    combine_genes_into_one_sequence, create_gene_map, get_gene_traits_from_genome_sequence,
-    get_only_existing_traits, GeneBuilder, Genome,
+    combine_genes_into_one_sequence, create_gene_map, create_organism_based_on_genome,
+    create_random_genome, find_coding_regions, get_gene_traits_from_genome_sequence,
+    get_genome_traits, get_only_existing_traits, GeneTrait, GeneValue, Genome,
 };
+use genetic_algorithm::{crossover, Crossover};
+use lineage::Lineage;
+use species::{CensusEntry, SpeciationConfig, SpeciesEvent, SpeciesTracker};
+use stats::{ChartMetric, StatsFormat, StatsRenderSystem, StatsSystem};
 
+mod breeding;
+mod fasta;
+mod foraging;
 mod genes;
+pub mod genetic_algorithm;
+mod lineage;
+mod snapshot;
+mod species;
+mod stats;
+#[cfg(test)]
 mod tests;
 
-pub struct Evolution {}
+const WORLD_WIDTH: f32 = 1200.0;
+const WORLD_HEIGHT: f32 = 900.0;
+
+const INITIAL_POPULATION: u32 = 60;
+
+/// No more organisms are born while there are this many.
+const MAX_POPULATION: usize = 400;
+
+/// How far from its parent a child is born.
+const BIRTH_DISTANCE: f32 = 20.0;
+
+/// How close another organism has to be to become the mate of one that breeds.
+const MATE_DISTANCE: f32 = 60.0;
+
+const INITIAL_FOOD: u32 = 40;
+/// Food stops growing when there are this many pieces in the world.
+const MAX_FOOD: usize = 150;
+const FOOD_PER_TICK: u32 = 4;
+const FOOD_COLOR: Color = Color::new(170, 220, 90, 255);
+const FOOD_RADIUS: f32 = 3.0;
+
+/// Seconds between two ticks of the simulation. Ages are counted in ticks.
+const TICK_DURATION: f32 = 1.0;
+
+const ZOOM_STEP: f32 = 1.1;
+
+const INSPECTOR_WIDTH: f32 = 280.0;
+
+const PHYLOGENY_EXPORT_PATH: &str = "phylogeny.nwk";
+const SNAPSHOT_PATH: &str = "evolution.snapshot";
+const FASTA_EXPORT_PATH: &str = "population.fasta";
+
+const CHART_PANEL_WIDTH: f32 = 300.0;
+
+/// Genes shown per line in the inspector, with the non-coding nucleotides in front of
+/// them.
+const GENES_PER_LINE: usize = 2;
+
+/// A 2D world of organisms that look the way their genes say.
+pub struct Evolution {
+    organism_entities: Vec<Entity>,
+    food_entities: Vec<Entity>,
+    next_id: u32,
+    gene_map: HashMap<String, GeneTrait>,
+    /// ChaCha, because its position in the stream can be saved and restored.
+    rng: ChaCha8Rng,
+    lineage: Lineage,
+    species: SpeciesTracker,
+    /// Ticks since the world was created.
+    ticks: u64,
+    foraging_system: ForagingSystem,
+    organism_render_system: OrganismRenderSystem,
+    inspector_system: InspectorSystem,
+    stats_system: StatsSystem,
+    stats_render_system: StatsRenderSystem,
+    /// What the chart shows, `None` while it is hidden.
+    chart: Option<ChartMetric>,
+    camera: Camera,
+    ui: Ui,
+    /// Entity id of the organism shown in the inspector.
+    selected: Option<u32>,
+    game_state: GameState,
+    tick_timer: f32,
+}
+
+impl Evolution {
+    /// World with organisms with random genomes and food at random positions.
+    pub fn new() -> Self {
+        let mut evolution = Self::empty(ChaCha8Rng::from_entropy());
+        let genomes = (0..INITIAL_POPULATION)
+            .map(|_| create_random_genome(&mut evolution.rng, &evolution.gene_map))
+            .collect();
+        evolution.populate(genomes);
+        evolution
+    }
+
+    /// World whose first organisms have the given genomes, for example read from a
+    /// FASTA file, at random positions.
+    pub fn from_genomes(genomes: Vec<Genome>) -> Self {
+        let mut evolution = Self::empty(ChaCha8Rng::from_entropy());
+        evolution.populate(genomes);
+        evolution
+    }
+
+    fn populate(&mut self, genomes: Vec<Genome>) {
+        for genome in genomes {
+            let position = self.random_position();
+            self.spawn(genome, position, &[]);
+        }
+        for _ in 0..INITIAL_FOOD {
+            self.spawn_food();
+        }
+        self.census();
+        self.record_stats();
+    }
+
+    /// World without organisms or food at tick 0.
+    fn empty(rng: ChaCha8Rng) -> Self {
+        let gene_map = create_gene_map();
+        Self {
+            organism_entities: Vec::new(),
+            food_entities: Vec::new(),
+            next_id: 0,
+            stats_system: StatsSystem::new(&gene_map),
+            gene_map,
+            rng,
+            lineage: Lineage::new(),
+            species: SpeciesTracker::new(SpeciationConfig::default()),
+            ticks: 0,
+            foraging_system: ForagingSystem,
+            organism_render_system: OrganismRenderSystem,
+            inspector_system: InspectorSystem,
+            stats_render_system: StatsRenderSystem,
+            chart: None,
+            camera: Camera::new(),
+            ui: Ui::new(),
+            selected: None,
+            game_state: GameState::Playing,
+            tick_timer: 0.0,
+        }
+    }
+
+    /// Adds an organism to the world and returns its id, which is also the id of its
+    /// entity.
+    fn spawn(&mut self, genome: Genome, position: Vector2, parents: &[u32]) -> u32 {
+        let id = self.next_id;
+        self.next_id += 1;
+
+        let mut organism = create_organism_based_on_genome(genome, &self.gene_map);
+        organism.id = id;
+        organism.parents = parents.to_vec();
+        self.lineage
+            .record_birth(id, parents, &organism.genome, self.ticks);
+        if !parents.is_empty() {
+            self.stats_system.count_birth();
+        }
+
+        let traits = get_genome_traits(&organism.genome, &self.gene_map);
+        let movement = Movement::new(speed_from_traits(&traits), self.rng.gen_range(-PI..PI));
+        let body = Body { position };
+        let entity = self.organism_entity(organism, body, movement);
+        self.organism_entities.push(entity);
+
+        id
+    }
+
+    /// Entity of an organism, with the components that follow from its genome.
+    fn organism_entity(&self, organism: Organism, body: Body, movement: Movement) -> Entity {
+        let traits = get_genome_traits(&organism.genome, &self.gene_map);
+
+        EntityBuilder::new(organism.id)
+            .with_component(Appearance::from_traits(&traits))
+            .with_component(Diet::from_traits(&traits))
+            .with_component(Breeding::from_traits(&traits))
+            .with_component(organism)
+            .with_component(body)
+            .with_component(movement)
+            .build()
+    }
+
+    /// Organisms with enough food may have a child, which gets half of their food, as
+    /// long as the population stays within `MAX_POPULATION`. The
+    /// child's genome is a mix of the parent's and that of the nearest organism it can
+    /// breed with, or a copy when there is none close enough. Then nucleotides mutate,
+    /// are inserted or lost, and genes may be copied or lost.
+    fn breed(&mut self) {
+        let rng = &mut self.rng;
+        let breeders: Vec<usize> = self
+            .organism_entities
+            .iter()
+            .enumerate()
+            .filter(|(_, entity)| {
+                let chance = entity
+                    .get_component::<Breeding>()
+                    .map_or(0.0, |breeding| breeding.chance);
+                entity
+                    .get_component::<Organism>()
+                    .is_some_and(|organism| organism.food >= BREEDING_FOOD)
+                    && rng.gen_bool(chance)
+            })
+            .map(|(index, _)| index)
+            .collect();
+
+        // Only as many breeders as there is room for pay food for a child.
+        let free = MAX_POPULATION.saturating_sub(self.organism_entities.len());
+        let mut births = Vec::new();
+        for index in breeders {
+            if births.len() >= free {
+                break;
+            }
+
+            let mate = self
+                .mate_of(index)
+                .and_then(|mate| self.organism_entities[mate].get_component::<Organism>());
+            let entity = &self.organism_entities[index];
+            let (Some(body), Some(organism)) = (
+                entity.get_component::<Body>(),
+                entity.get_component::<Organism>(),
+            ) else {
+                continue;
+            };
+
+            let (genome, parents) = match mate {
+                Some(mate) => (
+                    crossover(
+                        &organism.genome,
+                        &mate.genome,
+                        Crossover::Uniform,
+                        &mut self.rng,
+                    ),
+                    vec![organism.id, mate.id],
+                ),
+                None => (organism.genome.clone(), vec![organism.id]),
+            };
+            let genome = mutate(&genome, MUTATION_RATE, &mut self.rng);
+            let genome = insert_and_delete(&genome, INDEL_RATE, &mut self.rng);
+            let genome = duplicate_and_lose(&genome, DUPLICATION_RATE, &mut self.rng);
+            let position = body.position;
+
+            if let Some(organism) = self.organism_entities[index].get_component_mut::<Organism>() {
+                let food = organism.food / 2;
+                organism.food -= food;
+                births.push((genome, position, food, parents));
+            }
+        }
+
+        for (genome, position, food, parents) in births {
+            let angle = self.rng.gen_range(-PI..PI);
+            let position = Vector2::new(
+                (position.x + angle.cos() * BIRTH_DISTANCE).clamp(0.0, WORLD_WIDTH),
+                (position.y + angle.sin() * BIRTH_DISTANCE).clamp(0.0, WORLD_HEIGHT),
+            );
+            self.spawn(genome, position, &parents);
+            if let Some(child) = self
+                .organism_entities
+                .last_mut()
+                .and_then(|entity| entity.get_component_mut::<Organism>())
+            {
+                child.food = food;
+            }
+        }
+    }
+
+    /// Index of the nearest other organism within `MATE_DISTANCE` that the organism at
+    /// `index` can breed with.
+    fn mate_of(&self, index: usize) -> Option<usize> {
+        let entity = &self.organism_entities[index];
+        let position = entity.get_component::<Body>()?.position;
+        let genome = &entity.get_component::<Organism>()?.genome;
+
+        self.organism_entities
+            .iter()
+            .enumerate()
+            .filter(|(other, _)| *other != index)
+            .filter_map(|(other, entity)| {
+                let distance = entity
+                    .get_component::<Body>()?
+                    .position
+                    .distance_to(position);
+                let organism = entity.get_component::<Organism>()?;
+                (distance <= MATE_DISTANCE && self.species.can_breed(genome, &organism.genome))
+                    .then_some((other, distance))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(other, _)| other)
+    }
+
+    /// Sorts the organisms into species.
+    fn census(&mut self) {
+        let organisms: Vec<CensusEntry> = self
+            .organism_entities
+            .iter()
+            .filter_map(|entity| entity.get_component::<Organism>())
+            .map(|organism| CensusEntry {
+                id: organism.id,
+                genome: &organism.genome,
+                parents: &organism.parents,
+            })
+            .collect();
+        self.species.census(&organisms, self.ticks);
+    }
+
+    /// Number of organisms with each diet.
+    fn diet_counts(&self) -> [(Diet, usize); 3] {
+        [Diet::Herbivore, Diet::Carnivore, Diet::Omnivore].map(|diet| {
+            let count = self
+                .organism_entities
+                .iter()
+                .filter(|entity| entity.get_component::<Diet>() == Some(&diet))
+                .count();
+            (diet, count)
+        })
+    }
+
+    fn spawn_food(&mut self) {
+        let position = self.random_position();
+        let id = self.next_id;
+        self.next_id += 1;
+        self.food_entities.push(
+            EntityBuilder::new(id)
+                .with_component(Food)
+                .with_component(Body { position })
+                .build(),
+        );
+    }
+
+    fn random_position(&mut self) -> Vector2 {
+        Vector2::new(
+            self.rng.gen_range(0.0..WORLD_WIDTH),
+            self.rng.gen_range(0.0..WORLD_HEIGHT),
+        )
+    }
+
+    /// Centres the world on the screen and zooms so that all of it is visible.
+    pub fn fit_camera_to_world(&mut self, screen_width: i32, screen_height: i32) {
+        self.camera.fit(
+            Rectangle::new(0.0, 0.0, WORLD_WIDTH, WORLD_HEIGHT),
+            screen_width as f32,
+            screen_height as f32,
+        );
+    }
+
+    /// Advances the simulation by one tick: organisms age and pay for moving, those
+    /// that ran out of health or grew too old die, well fed ones may have a child
+    /// and new food grows. Then the organisms are sorted into species again.
+    fn tick(&mut self) {
+        self.ticks += 1;
+        for entity in self.organism_entities.iter_mut() {
+            if let Some(organism) = entity.get_component_mut::<Organism>() {
+                organism.age += 1;
+            }
+        }
+
+        self.foraging_system.pay(&mut self.organism_entities);
+        let (ticks, lineage, stats_system) =
+            (self.ticks, &mut self.lineage, &mut self.stats_system);
+        self.organism_entities.retain(|entity| {
+            let alive = entity
+                .get_component::<Organism>()
+                .is_some_and(|organism| organism.health > 0 && organism.age <= organism.lifespan);
+            if !alive {
+                lineage.record_death(entity.id(), ticks);
+                stats_system.count_death();
+            }
+            alive
+        });
+
+        self.breed();
+
+        for _ in 0..FOOD_PER_TICK {
+            if self.food_entities.len() < MAX_FOOD {
+                self.spawn_food();
+            }
+        }
+
+        self.census();
+        self.record_stats();
+    }
+
+    /// Records the statistics of this tick. Writing stops when an output fails, the
+    /// simulation goes on.
+    fn record_stats(&mut self) {
+        if let Err(error) =
+            self.stats_system
+                .record(self.ticks, &self.organism_entities, &self.gene_map)
+        {
+            println!("Could not write statistics: {}", error);
+            self.stats_system.close_outputs();
+        }
+    }
+
+    /// Saves the whole simulation, see `snapshot` for the format.
+    pub fn save(&self, path: &str) -> std::io::Result<()> {
+        std::fs::write(path, snapshot::write(self))
+    }
+
+    /// Loads a simulation saved with `save`. It continues exactly as the saved one
+    /// would have.
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|error| error.to_string())?;
+        snapshot::read(&text)
+    }
+
+    /// Writes the genomes of the living organisms to a FASTA file.
+    pub fn export_fasta(&self, path: &str) -> std::io::Result<()> {
+        let records: Vec<FastaRecord> = self
+            .organism_entities
+            .iter()
+            .filter_map(|entity| entity.get_component::<Organism>())
+            .map(|organism| FastaRecord::from_organism(organism, &self.gene_map))
+            .collect();
+        let file = std::fs::File::create(path)?;
+        write_fasta(std::io::BufWriter::new(file), &records)
+    }
+
+    /// Writes the statistics of every tick to a file, starting with the latest ticks that
+    /// are still kept, see `StatsSystem::add_output`.
+    pub fn write_stats(&mut self, path: &str, format: StatsFormat) -> std::io::Result<()> {
+        let file = std::fs::File::create(path)?;
+        self.stats_system
+            .add_output(format, std::io::BufWriter::new(file))
+    }
+
+    /// Wheel zooms at the mouse, the right or middle mouse button pans.
+    fn handle_camera_input(&mut self, rl: &RaylibHandle) {
+        let mouse_position = rl.get_mouse_position();
+
+        let wheel = rl.get_mouse_wheel_move();
+        if wheel != 0.0 {
+            self.camera.zoom_at(mouse_position, ZOOM_STEP.powf(wheel));
+        }
+
+        if rl.is_mouse_button_down(MouseButton::MOUSE_BUTTON_RIGHT)
+            || rl.is_mouse_button_down(MouseButton::MOUSE_BUTTON_MIDDLE)
+        {
+            self.camera.pan(rl.get_mouse_delta());
+        }
+    }
+}
+
+impl Game for Evolution {
+    fn handle_input(&mut self, rl: &RaylibHandle) {
+        self.ui.begin(UiInput::from_raylib(rl));
+        let selected = self.selected.and_then(|id| {
+            self.organism_entities
+                .iter()
+                .find(|entity| entity.id() == id)
+        });
+        if let Some(entity) = selected {
+            let x = rl.get_screen_width() as f32 - INSPECTOR_WIDTH - 5.0;
+            if self.inspector_system.declare(
+                &mut self.ui,
+                entity,
+                &self.gene_map,
+                &self.lineage,
+                &self.species,
+                x,
+            ) {
+                self.selected = None;
+            }
+        }
+
+        if let Some(metric) = self.chart {
+            let names = ChartMetric::ALL.map(|metric| metric.name());
+            self.ui.begin_panel(5.0, 35.0, CHART_PANEL_WIDTH);
+            self.ui.label(&format!("Chart: {}", metric.name()));
+            if let Some(i) = self.ui.button_row(&names[..3]) {
+                self.chart = Some(ChartMetric::ALL[i]);
+            }
+            if let Some(i) = self.ui.button_row(&names[3..]) {
+                self.chart = Some(ChartMetric::ALL[i + 3]);
+            }
+            self.ui.end_panel();
+        }
+
+        if rl.is_key_pressed(KeyboardKey::KEY_SPACE) {
+            match self.game_state {
+                GameState::Playing => self.change_state(GameState::Paused),
+                GameState::Paused => self.change_state(GameState::Playing),
+            }
+        }
 
-struct Organism {
+        if rl.is_key_pressed(KeyboardKey::KEY_T) {
+            match std::fs::write(PHYLOGENY_EXPORT_PATH, self.lineage.to_newick(true)) {
+                Ok(()) => {
+                    println!("Phylogeny exported to {}", PHYLOGENY_EXPORT_PATH);
+                    println!(
+                        "{} surviving lineages, common ancestor of the living: {}",
+                        self.lineage.surviving_lineages().len(),
+                        self.lineage
+                            .living_common_ancestor()
+                            .map_or("none".to_string(), |id| format!("#{}", id))
+                    );
+                }
+                Err(error) => println!("Could not export the phylogeny: {}", error),
+            }
+
+            let (mut splits, mut extinctions) = (0, 0);
+            for event in self.species.events() {
+                match event {
+                    SpeciesEvent::Split { .. } => splits += 1,
+                    SpeciesEvent::Extinct { .. } => extinctions += 1,
+                    SpeciesEvent::Appeared { .. } => {}
+                }
+            }
+            println!(
+                "{} living species, {} splits and {} extinctions so far",
+                self.species.living().count(),
+                splits,
+                extinctions
+            );
+        }
+
+        if rl.is_key_pressed(KeyboardKey::KEY_G) {
+            self.chart = match self.chart {
+                Some(_) => None,
+                None => Some(ChartMetric::Population),
+            };
+        }
+
+        if rl.is_key_pressed(KeyboardKey::KEY_B) {
+            let config = &mut self.species.config;
+            config.breeding_barrier = !config.breeding_barrier;
+            println!(
+                "Breeding between species is {}",
+                if config.breeding_barrier {
+                    "blocked"
+                } else {
+                    "allowed"
+                }
+            );
+        }
+
+        if rl.is_key_pressed(KeyboardKey::KEY_E) {
+            match self.export_fasta(FASTA_EXPORT_PATH) {
+                Ok(()) => println!("Genomes exported to {}", FASTA_EXPORT_PATH),
+                Err(error) => println!("Could not export the genomes: {}", error),
+            }
+        }
+
+        if rl.is_key_pressed(KeyboardKey::KEY_F5) {
+            match self.save(SNAPSHOT_PATH) {
+                Ok(()) => println!("Simulation saved to {}", SNAPSHOT_PATH),
+                Err(error) => println!("Could not save the simulation: {}", error),
+            }
+        }
+
+        if rl.is_key_pressed(KeyboardKey::KEY_F9) {
+            match Evolution::load(SNAPSHOT_PATH) {
+                Ok(mut evolution) => {
+                    // The loaded simulation starts new statistics, because the ticks of
+                    // this run would go back in time. Its statistics files are closed.
+                    std::mem::swap(&mut evolution.ui, &mut self.ui);
+                    evolution.chart = self.chart;
+                    std::mem::swap(&mut evolution.game_state, &mut self.game_state);
+                    *self = evolution;
+                    self.fit_camera_to_world(rl.get_screen_width(), rl.get_screen_height());
+                    println!(
+                        "Simulation loaded from {} at tick {}, statistics start over",
+                        SNAPSHOT_PATH, self.ticks
+                    );
+                }
+                Err(error) => println!("Could not load the simulation: {}", error),
+            }
+        }
+
+        if rl.is_key_pressed(KeyboardKey::KEY_HOME) {
+            self.fit_camera_to_world(rl.get_screen_width(), rl.get_screen_height());
+        }
+
+        self.handle_camera_input(rl);
+
+        if rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT) && !self.ui.wants_mouse() {
+            let world = self.camera.screen_to_world(rl.get_mouse_position());
+            self.selected = organism_at(&self.organism_entities, world);
+        }
+    }
+
+    fn update(&mut self, delta_time: f32) {
+        if let GameState::Playing = self.game_state {
+            let eaten = self.foraging_system.update(
+                &mut self.organism_entities,
+                &mut self.food_entities,
+                Vector2::new(WORLD_WIDTH, WORLD_HEIGHT),
+                delta_time,
+                &mut self.rng,
+            );
+            for id in eaten {
+                self.lineage.record_death(id, self.ticks);
+                self.stats_system.count_death();
+            }
+
+            self.tick_timer += delta_time;
+            while self.tick_timer >= TICK_DURATION {
+                self.tick_timer -= TICK_DURATION;
+                self.tick();
+            }
+        }
+    }
+
+    fn render(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread) {
+        let mut d = rl.begin_drawing(thread);
+
+        d.clear_background(Color::BLACK);
+
+        let top_left = self.camera.world_to_screen(Vector2::new(0.0, 0.0));
+        d.draw_rectangle_v(
+            top_left,
+            Vector2::new(WORLD_WIDTH, WORLD_HEIGHT) * self.camera.zoom,
+            Color::new(20, 40, 25, 255),
+        );
+
+        for entity in self.food_entities.iter() {
+            if let Some(body) = entity.get_component::<Body>() {
+                d.draw_circle_v(
+                    self.camera.world_to_screen(body.position),
+                    FOOD_RADIUS * self.camera.zoom,
+                    FOOD_COLOR,
+                );
+            }
+        }
+
+        self.organism_render_system.render(
+            &mut d,
+            &self.organism_entities,
+            &self.camera,
+            self.selected,
+        );
+
+        let hint = match self.game_state {
+            GameState::Playing => {
+                "Space pauses, click to inspect, T exports the tree, B toggles the breeding barrier, G shows the chart, E exports genomes, F5 saves, F9 loads"
+            }
+            GameState::Paused => "Paused - Space resumes",
+        };
+        let y = d.get_screen_height() - 30;
+        d.draw_text(hint, 10, y, 20, Color::WHITE);
+
+        let counts: Vec<String> = self
+            .diet_counts()
+            .iter()
+            .map(|(diet, count)| format!("{}s: {}", diet.name(), count))
+            .collect();
+        let population = format!(
+            "{}  Species: {}  Food: {}",
+            counts.join("  "),
+            self.species.living().count(),
+            self.food_entities.len()
+        );
+        d.draw_text(&population, 10, 10, 20, Color::WHITE);
+
+        if let Some(metric) = self.chart {
+            self.stats_render_system
+                .render(&mut d, &self.stats_system, metric);
+        }
+
+        self.ui.render(&mut d);
+    }
+
+    fn change_state(&mut self, new_state: GameState) {
+        self.game_state = new_state;
+    }
+}
+
+pub struct Organism {
     genome: Genome,
+    /// Set when the organism is added to the world.
+    id: u32,
+    /// Empty for organisms that were not born from others.
+    parents: Vec<u32>,
 
     food: u32,
     age: u32,
@@ -17,6 +700,32 @@ struct Organism {
     lifespan: u32,
 }
 
+/// Read-only view of an organism, for code outside the simulation such as a `Fitness`.
+impl Organism {
+    pub fn genome(&self) -> &Genome {
+        &self.genome
+    }
+
+    /// Food points the organism has stored.
+    pub fn food(&self) -> u32 {
+        self.food
+    }
+
+    /// Ticks since the organism was born.
+    pub fn age(&self) -> u32 {
+        self.age
+    }
+
+    pub fn health(&self) -> u32 {
+        self.health
+    }
+
+    /// Oldest age in ticks the organism lives to.
+    pub fn lifespan(&self) -> u32 {
+        self.lifespan
+    }
+}
+
 impl std::fmt::Debug for Organism {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let genome_sequence = combine_genes_into_one_sequence(&self.genome.genes);
@@ -28,6 +737,8 @@ impl std::fmt::Debug for Organism {
         let traits = get_only_existing_traits(traits);
 
         f.debug_struct("Organism")
+            .field("id", &self.id)
+            .field("parents", &self.parents)
             .field("genome", &self.genome)
             .field("Genes", &self.genome.genes.len())
             .field("food", &self.food)
@@ -40,29 +751,315 @@ impl std::fmt::Debug for Organism {
     }
 }
 
-pub fn run() {
-    let mut gene_builder = GeneBuilder::new();
+/// Where something is in the world, in world units.
+struct Body {
+    position: Vector2,
+}
 
-    let water_gene = gene_builder.high_water_resistance().build_gene();
-    let temperature_gene = gene_builder.high_temperature_resistance().build_gene();
-    let metabolism_gene = gene_builder.high_metabolism().build_gene();
-    let speed_gene = gene_builder.high_speed().build_gene();
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Shape {
+    Circle,
+    Square,
+}
 
-    let red_gene = gene_builder.red().build_gene();
-    let green_gene = gene_builder.green().build_gene();
-    let blue_gene = gene_builder.blue().build_gene();
+/// How an organism is drawn, decided by its genes. Organisms without a gene for
+/// a trait get the default: gray, medium and round.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Appearance {
+    color: Color,
+    radius: f32,
+    shape: Shape,
+}
+
+impl Appearance {
+    fn from_traits(traits: &[GeneTrait]) -> Self {
+        let mut appearance = Appearance {
+            color: Color::GRAY,
+            radius: 10.0,
+            shape: Shape::Circle,
+        };
+
+        // The first gene for a trait wins, like in `create_organism_based_on_genome`.
+        let color = traits.iter().find_map(|t| match t {
+            GeneTrait::Color(GeneValue::Red) => Some(Color::RED),
+            GeneTrait::Color(GeneValue::Green) => Some(Color::GREEN),
+            GeneTrait::Color(GeneValue::Blue) => Some(Color::BLUE),
+            GeneTrait::Color(GeneValue::Yellow) => Some(Color::YELLOW),
+            GeneTrait::Color(GeneValue::Purple) => Some(Color::PURPLE),
+            _ => None,
+        });
+        let radius = traits.iter().find_map(|t| match t {
+            GeneTrait::Size(GeneValue::Small) => Some(6.0),
+            GeneTrait::Size(GeneValue::Medium) => Some(10.0),
+            GeneTrait::Size(GeneValue::Large) => Some(16.0),
+            _ => None,
+        });
+        let shape = traits.iter().find_map(|t| match t {
+            GeneTrait::Shape(GeneValue::Round) => Some(Shape::Circle),
+            GeneTrait::Shape(GeneValue::Square) => Some(Shape::Square),
+            _ => None,
+        });
+
+        appearance.color = color.unwrap_or(appearance.color);
+        appearance.radius = radius.unwrap_or(appearance.radius);
+        appearance.shape = shape.unwrap_or(appearance.shape);
+        appearance
+    }
+
+    /// Whether a world point is inside the organism when it is drawn at `position`.
+    fn contains(&self, position: Vector2, point: Vector2) -> bool {
+        let offset = point - position;
+        match self.shape {
+            Shape::Circle => offset.length() <= self.radius,
+            Shape::Square => offset.x.abs() <= self.radius && offset.y.abs() <= self.radius,
+        }
+    }
+}
+
+/// Returns the id of the organism at a world point. Organisms drawn later are on top,
+/// so they are checked first.
+fn organism_at(entities: &[Entity], point: Vector2) -> Option<u32> {
+    entities
+        .iter()
+        .rev()
+        .find(|entity| {
+            match (
+                entity.get_component::<Body>(),
+                entity.get_component::<Appearance>(),
+            ) {
+                (Some(body), Some(appearance)) => appearance.contains(body.position, point),
+                _ => false,
+            }
+        })
+        .map(|entity| entity.id())
+}
+
+struct OrganismRenderSystem;
+
+impl OrganismRenderSystem {
+    pub fn render(
+        &self,
+        rl: &mut RaylibDrawHandle,
+        entities: &[Entity],
+        camera: &Camera,
+        selected: Option<u32>,
+    ) {
+        for entity in entities.iter() {
+            let (Some(body), Some(appearance)) = (
+                entity.get_component::<Body>(),
+                entity.get_component::<Appearance>(),
+            ) else {
+                continue;
+            };
+
+            let center = camera.world_to_screen(body.position);
+            let radius = appearance.radius * camera.zoom;
+            match appearance.shape {
+                Shape::Circle => rl.draw_circle_v(center, radius, appearance.color),
+                Shape::Square => rl.draw_rectangle_v(
+                    center - Vector2::new(radius, radius),
+                    Vector2::new(radius * 2.0, radius * 2.0),
+                    appearance.color,
+                ),
+            }
+
+            if selected == Some(entity.id()) {
+                rl.draw_circle_lines(
+                    center.x as i32,
+                    center.y as i32,
+                    radius * 1.5 + 3.0,
+                    Color::WHITE,
+                );
+            }
+        }
+    }
+}
+
+/// Panel with everything about one organism.
+struct InspectorSystem;
+
+impl InspectorSystem {
+    /// Declares the panel at the right edge of the screen, `x` pixels from the left.
+    /// Returns true when the panel was closed.
+    pub fn declare(
+        &self,
+        ui: &mut Ui,
+        entity: &Entity,
+        gene_map: &HashMap<String, GeneTrait>,
+        lineage: &Lineage,
+        species: &SpeciesTracker,
+        x: f32,
+    ) -> bool {
+        let Some(organism) = entity.get_component::<Organism>() else {
+            return true;
+        };
+
+        ui.begin_panel(x, 5.0, INSPECTOR_WIDTH);
+        ui.label(&format!("Organism #{}", entity.id()));
+        match organism.parents.as_slice() {
+            [] => ui.label("Founder"),
+            parents => {
+                let parents: Vec<String> = parents.iter().map(|id| format!("#{}", id)).collect();
+                ui.label(&format!("Parent: {}", parents.join(", ")));
+            }
+        }
+        ui.label(&format!("Age: {} / {}", organism.age, organism.lifespan));
+        ui.label(&format!("Health: {}", organism.health));
+        ui.label(&format!("Food: {}", organism.food));
+        if let Some(movement) = entity.get_component::<Movement>() {
+            ui.label(&format!("Speed: {}", movement.speed));
+        }
+        if let Some(diet) = entity.get_component::<Diet>() {
+            ui.label(&format!("Diet: {}", diet.name()));
+        }
+        if let Some(species) = species
+            .species_of(entity.id())
+            .and_then(|id| species.get(id))
+        {
+            ui.label(&match species.split_from {
+                Some(from) => format!(
+                    "Species: #{}, split from #{} at tick {}",
+                    species.id, from, species.appeared
+                ),
+                None => format!("Species: #{}, since tick {}", species.id, species.appeared),
+            });
+        }
+
+        let sequence = combine_genes_into_one_sequence(&organism.genome.genes);
+        let sequence: String = sequence.iter().map(|n| char::from(*n)).collect();
+        ui.label(&format!(
+            "Genome ({} nucleotides, {} genes):",
+            sequence.len(),
+            find_coding_regions(&sequence).len()
+        ));
+        if let Some(record) = lineage.get(entity.id()) {
+            ui.label(&format!("Hash {:016x}", record.genome_hash));
+        }
+        for genes in organism.genome.genes.chunks(GENES_PER_LINE) {
+            let line = genes
+                .iter()
+                .map(|gene| {
+                    gene.sequence
+                        .iter()
+                        .map(|n| char::from(*n))
+                        .collect::<String>()
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            ui.label(&line);
+        }
 
-    let genome = gene_builder.build_genome(vec![
-        red_gene,
-        green_gene,
-        blue_gene,
-        water_gene,
-        temperature_gene,
-        metabolism_gene,
-        speed_gene,
-    ]);
+        ui.label("Traits:");
+        for gene_trait in get_genome_traits(&organism.genome, gene_map) {
+            ui.label(&format!("{:?}", gene_trait));
+        }
 
-    let organism = gene_builder.build_organism(genome);
+        let closed = ui.button("Close");
+        ui.end_panel();
+        closed
+    }
+}
+
+/// Command line options of the simulation.
+#[derive(Debug, Default, PartialEq)]
+pub struct EvolutionOptions {
+    pub csv: Option<String>,
+    pub jsonl: Option<String>,
+    pub load: Option<String>,
+    pub genomes: Option<String>,
+}
+
+impl EvolutionOptions {
+    /// Parses `--csv` and `--jsonl`, each followed by the file the statistics are
+    /// written to, `--load` followed by a saved simulation to continue and `--genomes`
+    /// followed by a FASTA file with the genomes of the first organisms.
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = Self::default();
+
+        let mut args = args.iter();
+        while let Some(flag) = args.next() {
+            let value = args
+                .next()
+                .ok_or_else(|| format!("Missing value for {}", flag))?;
+
+            match flag.as_str() {
+                "--csv" => options.csv = Some(value.clone()),
+                "--jsonl" => options.jsonl = Some(value.clone()),
+                "--load" => options.load = Some(value.clone()),
+                "--genomes" => options.genomes = Some(value.clone()),
+                _ => return Err(format!("Unknown option {}", flag)),
+            }
+        }
+
+        if options.load.is_some() && options.genomes.is_some() {
+            return Err("--load and --genomes cannot be used together".to_string());
+        }
+
+        Ok(options)
+    }
+}
+
+/// Opens the simulation, a new one or the one saved in the `--load` file. With `--csv`
+/// or `--jsonl` the statistics of every tick are written to a file while it runs.
+pub fn run(args: Vec<String>) {
+    let options = match EvolutionOptions::parse(&args) {
+        Ok(options) => options,
+        Err(error) => {
+            println!("{}", error);
+            println!(
+                "Usage: [--csv stats.csv] [--jsonl stats.jsonl] \
+                 [--load evolution.snapshot | --genomes population.fasta]"
+            );
+            return;
+        }
+    };
+
+    let loaded = match (&options.load, &options.genomes) {
+        (Some(path), _) => Evolution::load(path).map_err(|error| (path, error)),
+        (_, Some(path)) => std::fs::read_to_string(path)
+            .map_err(|error| error.to_string())
+            .and_then(|text| read_fasta(&text))
+            .map(|records| {
+                Evolution::from_genomes(records.into_iter().map(|record| record.genome).collect())
+            })
+            .map_err(|error| (path, error)),
+        (None, None) => Ok(Evolution::new()),
+    };
+    let mut evolution = match loaded {
+        Ok(evolution) => evolution,
+        Err((path, error)) => {
+            println!("Could not load {}: {}", path, error);
+            return;
+        }
+    };
+    let outputs = [
+        (options.csv, StatsFormat::Csv),
+        (options.jsonl, StatsFormat::JsonLines),
+    ];
+    for (path, format) in outputs {
+        if let Some(path) = path {
+            if let Err(error) = evolution.write_stats(&path, format) {
+                println!("Could not write statistics to {}: {}", path, error);
+                return;
+            }
+        }
+    }
+
+    let (mut rl, thread) = raylib::init()
+        .size(800, 600)
+        .title("Evolution")
+        .vsync()
+        .build();
 
-    println!("{:?}", organism);
+    evolution.fit_camera_to_world(rl.get_screen_width(), rl.get_screen_height());
+
+    while !rl.window_should_close() {
+        let delta_time = rl.get_frame_time();
+
+        evolution.handle_input(&rl);
+
+        evolution.update(delta_time);
+
+        evolution.render(&mut rl, &thread);
+    }
 }
@@ -0,0 +1,489 @@
+use std::{fmt::Write, str::FromStr};
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use raylib::math::Vector2;
+
+use super::{
+    foraging::{Food, Movement},
+    genes::{create_sequence_from_str, find_coding_regions, mark_gene, Gene, Genome, Nucleotide},
+    lineage::LineageRecord,
+    species::{DistanceMetric, SpeciationConfig, Species, SpeciesEvent, SpeciesTracker},
+    Body, Evolution, Organism,
+};
+use crate::engine::EntityBuilder;
+
+const HEADER: &str = "evolution-snapshot";
+
+/// Version written into new snapshots. Increase it when the format changes, and keep
+/// reading the older versions.
+pub const SNAPSHOT_VERSION: u32 = 3;
+
+/// Saves everything the simulation needs to continue as a text with one line per item.
+/// The first line is `evolution-snapshot <version>`, every other line starts with
+/// what it describes:
+///
+/// ```text
+/// tick <ticks> <seconds since the last tick>
+/// next-id <id>
+/// rng <seed in hex> <stream> <word position>
+/// speciation <hamming|edit> <threshold> <breeding barrier>
+/// organism <id> <parents> <food> <age> <health> <lifespan> <x> <y> <speed> <heading> <fatigue> <genome>
+/// food <id> <x> <y>
+/// lineage <id> <parents> <birth> <death> <genome hash>
+/// species <id> <appeared> <split from> <extinct> <members> <representative genome>
+/// event <appeared|split|extinct> <species> [<split from>] <tick>
+/// ```
+///
+/// Lists are separated by commas and `-` stands for an empty list or no value.
+/// Genomes are written as gene sequences, and organisms keep the food, health and
+/// lifespan they have, so a snapshot still loads after the gene map changed. Only the
+/// traits that are read from the genome while the simulation runs, like diet and
+/// appearance, follow the new gene map then.
+///
+/// Version 1 genes were only the coding nucleotides. They are read as genes with the
+/// start and stop codons around them, without non-coding nucleotides. Organisms of
+/// versions 1 and 2 have their velocity after the position, which is skipped because
+/// it follows from the speed and heading. Their speed genes used codes that now mean
+/// temperature resistance, and are rewritten to the current speed codes.
+///
+/// Statistics, the camera and the selection are not saved.
+pub fn write(evolution: &Evolution) -> String {
+    let mut text = String::new();
+    // Writing to a String cannot fail.
+    let _ = write_lines(evolution, &mut text);
+    text
+}
+
+fn write_lines(evolution: &Evolution, text: &mut String) -> std::fmt::Result {
+    writeln!(text, "{} {}", HEADER, SNAPSHOT_VERSION)?;
+    writeln!(text, "tick {} {}", evolution.ticks, evolution.tick_timer)?;
+    writeln!(text, "next-id {}", evolution.next_id)?;
+
+    let seed: String = evolution
+        .rng
+        .get_seed()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    writeln!(
+        text,
+        "rng {} {} {}",
+        seed,
+        evolution.rng.get_stream(),
+        evolution.rng.get_word_pos()
+    )?;
+
+    let config = &evolution.species.config;
+    let metric = match config.metric {
+        DistanceMetric::Hamming => "hamming",
+        DistanceMetric::Edit => "edit",
+    };
+    writeln!(
+        text,
+        "speciation {} {} {}",
+        metric, config.threshold, config.breeding_barrier
+    )?;
+
+    for entity in evolution.organism_entities.iter() {
+        let (Some(organism), Some(body), Some(movement)) = (
+            entity.get_component::<Organism>(),
+            entity.get_component::<Body>(),
+            entity.get_component::<Movement>(),
+        ) else {
+            continue;
+        };
+
+        writeln!(
+            text,
+            "organism {} {} {} {} {} {} {} {} {} {} {} {}",
+            organism.id,
+            list(&organism.parents),
+            organism.food,
+            organism.age,
+            organism.health,
+            organism.lifespan,
+            body.position.x,
+            body.position.y,
+            movement.speed,
+            movement.heading,
+            movement.fatigue,
+            genome(&organism.genome)
+        )?;
+    }
+
+    for entity in evolution.food_entities.iter() {
+        if let Some(body) = entity.get_component::<Body>() {
+            writeln!(
+                text,
+                "food {} {} {}",
+                entity.id(),
+                body.position.x,
+                body.position.y
+            )?;
+        }
+    }
+
+    // Children are found again in the order their records are read, which has to
+    // be the order they were born in.
+    let mut records: Vec<&LineageRecord> = evolution.lineage.records().collect();
+    records.sort_by_key(|record| record.id);
+    for record in records {
+        writeln!(
+            text,
+            "lineage {} {} {} {} {:016x}",
+            record.id,
+            list(&record.parents),
+            record.birth,
+            optional(record.death),
+            record.genome_hash
+        )?;
+    }
+
+    for species in evolution.species.all() {
+        writeln!(
+            text,
+            "species {} {} {} {} {} {}",
+            species.id,
+            species.appeared,
+            optional(species.split_from),
+            optional(species.extinct),
+            list(&species.members),
+            genome(&species.representative)
+        )?;
+    }
+
+    for event in evolution.species.events() {
+        match event {
+            SpeciesEvent::Appeared { species, tick } => {
+                writeln!(text, "event appeared {} {}", species, tick)?
+            }
+            SpeciesEvent::Split {
+                species,
+                from,
+                tick,
+            } => writeln!(text, "event split {} {} {}", species, from, tick)?,
+            SpeciesEvent::Extinct { species, tick } => {
+                writeln!(text, "event extinct {} {}", species, tick)?
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn list(values: &[u32]) -> String {
+    match values {
+        [] => "-".to_string(),
+        values => values
+            .iter()
+            .map(|value| value.to_string())
+            .collect::<Vec<_>>()
+            .join(","),
+    }
+}
+
+fn optional(value: Option<impl ToString>) -> String {
+    value.map_or("-".to_string(), |value| value.to_string())
+}
+
+fn genome(genome: &Genome) -> String {
+    if genome.genes.is_empty() {
+        return "-".to_string();
+    }
+
+    genome
+        .genes
+        .iter()
+        .map(|gene| {
+            gene.sequence
+                .iter()
+                .map(|n| char::from(*n))
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Reads a simulation written by `write`, of this or an older version.
+pub fn read(text: &str) -> Result<Evolution, String> {
+    let mut lines = text.lines().enumerate().map(|(i, line)| (i + 1, line));
+
+    let header = lines.next().map_or("", |(_, line)| line);
+    let version = match header.split_whitespace().collect::<Vec<_>>().as_slice() {
+        [HEADER, version] => version
+            .parse::<u32>()
+            .map_err(|_| format!("Line 1: invalid version \"{}\"", version))?,
+        _ => return Err("Line 1: not an evolution snapshot".to_string()),
+    };
+    if version == 0 || version > SNAPSHOT_VERSION {
+        return Err(format!(
+            "Snapshot version {} is not supported, the latest is {}",
+            version, SNAPSHOT_VERSION
+        ));
+    }
+
+    // Only genomes changed since version 1, see `upgrade_genome`, and organisms lost
+    // their velocity in version 3.
+    let mut evolution = Evolution::empty(ChaCha8Rng::from_seed([0; 32]));
+    let mut has_rng = false;
+    let mut config = SpeciationConfig::default();
+    let mut species = Vec::new();
+    let mut events = Vec::new();
+
+    for (number, line) in lines {
+        let mut fields = Fields {
+            line: number,
+            fields: line.split_whitespace(),
+        };
+        let Some(kind) = fields.fields.next() else {
+            continue;
+        };
+
+        match kind {
+            "tick" => {
+                evolution.ticks = fields.next()?;
+                evolution.tick_timer = fields.next()?;
+            }
+            "next-id" => evolution.next_id = fields.next()?,
+            "rng" => {
+                let seed = fields.next_str()?;
+                let seed = parse_seed(seed)
+                    .ok_or_else(|| fields.error(&format!("invalid seed \"{}\"", seed)))?;
+                evolution.rng = ChaCha8Rng::from_seed(seed);
+                evolution.rng.set_stream(fields.next()?);
+                evolution.rng.set_word_pos(fields.next()?);
+                has_rng = true;
+            }
+            "speciation" => {
+                config.metric = match fields.next_str()? {
+                    "hamming" => DistanceMetric::Hamming,
+                    "edit" => DistanceMetric::Edit,
+                    metric => return Err(fields.error(&format!("unknown metric \"{}\"", metric))),
+                };
+                config.threshold = fields.next()?;
+                config.breeding_barrier = fields.next()?;
+            }
+            "organism" => {
+                let id = fields.next()?;
+                let parents = fields.list()?;
+                let food = fields.next()?;
+                let age = fields.next()?;
+                let health = fields.next()?;
+                let lifespan = fields.next()?;
+                let position = Vector2::new(fields.next()?, fields.next()?);
+                if version < 3 {
+                    fields.next::<f32>()?;
+                    fields.next::<f32>()?;
+                }
+                let mut movement = Movement::new(fields.next()?, fields.next()?);
+                movement.fatigue = fields.next()?;
+                let organism = Organism {
+                    genome: upgrade_genome(fields.genome()?, version),
+                    id,
+                    parents,
+                    food,
+                    age,
+                    health,
+                    lifespan,
+                };
+
+                let entity = evolution.organism_entity(organism, Body { position }, movement);
+                evolution.organism_entities.push(entity);
+            }
+            "food" => {
+                let id = fields.next()?;
+                let position = Vector2::new(fields.next()?, fields.next()?);
+                evolution.food_entities.push(
+                    EntityBuilder::new(id)
+                        .with_component(Food)
+                        .with_component(Body { position })
+                        .build(),
+                );
+            }
+            "lineage" => {
+                let record = LineageRecord {
+                    id: fields.next()?,
+                    parents: fields.list()?,
+                    birth: fields.next()?,
+                    death: fields.optional()?,
+                    genome_hash: {
+                        let hash = fields.next_str()?;
+                        u64::from_str_radix(hash, 16)
+                            .map_err(|_| fields.error(&format!("invalid hash \"{}\"", hash)))?
+                    },
+                };
+                evolution.lineage.insert(record);
+            }
+            "species" => species.push(Species {
+                id: fields.next()?,
+                appeared: fields.next()?,
+                split_from: fields.optional()?,
+                extinct: fields.optional()?,
+                members: fields.list()?,
+                representative: upgrade_genome(fields.genome()?, version),
+            }),
+            "event" => events.push(match fields.next_str()? {
+                "appeared" => SpeciesEvent::Appeared {
+                    species: fields.next()?,
+                    tick: fields.next()?,
+                },
+                "split" => SpeciesEvent::Split {
+                    species: fields.next()?,
+                    from: fields.next()?,
+                    tick: fields.next()?,
+                },
+                "extinct" => SpeciesEvent::Extinct {
+                    species: fields.next()?,
+                    tick: fields.next()?,
+                },
+                event => return Err(fields.error(&format!("unknown event \"{}\"", event))),
+            }),
+            kind => return Err(fields.error(&format!("unknown line \"{}\"", kind))),
+        }
+
+        fields.end()?;
+    }
+
+    if !has_rng {
+        return Err("The snapshot has no rng line".to_string());
+    }
+    evolution.species = SpeciesTracker::restore(config, species, events);
+
+    Ok(evolution)
+}
+
+/// Speed codes before version 3 and the codes they have now. The old ones were shared
+/// with temperature resistance, and speed won.
+const OLD_SPEED_CODES: [(&str, &str); 4] = [
+    ("TTTTT", "TCTCT"),
+    ("TTGTT", "TCGCT"),
+    ("TTATT", "TCACT"),
+    ("TTAAT", "TCAAT"),
+];
+
+/// Genome of the current version for a genome read from a snapshot of `version`.
+fn upgrade_genome(genome: Genome, version: u32) -> Genome {
+    let mut genome = if version >= 2 {
+        genome
+    } else {
+        Genome {
+            genes: genome
+                .genes
+                .iter()
+                .map(|gene| mark_gene(&gene.sequence))
+                .collect(),
+        }
+    };
+
+    if version < 3 {
+        for gene in genome.genes.iter_mut() {
+            upgrade_speed_codes(gene);
+        }
+    }
+    genome
+}
+
+fn upgrade_speed_codes(gene: &mut Gene) {
+    let sequence: String = gene.sequence.iter().map(|n| char::from(*n)).collect();
+
+    for code in find_coding_regions(&sequence) {
+        let old = &sequence[code.clone()];
+        if let Some((_, new)) = OLD_SPEED_CODES.iter().find(|(speed, _)| *speed == old) {
+            gene.sequence[code].copy_from_slice(&create_sequence_from_str(new));
+        }
+    }
+}
+
+fn parse_seed(seed: &str) -> Option<[u8; 32]> {
+    if seed.len() != 64 || !seed.is_ascii() {
+        return None;
+    }
+
+    let mut bytes = [0; 32];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&seed[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(bytes)
+}
+
+/// The values of one line after the first word, which says what the line describes.
+struct Fields<'a> {
+    line: usize,
+    fields: std::str::SplitWhitespace<'a>,
+}
+
+impl<'a> Fields<'a> {
+    fn error(&self, message: &str) -> String {
+        format!("Line {}: {}", self.line, message)
+    }
+
+    fn next_str(&mut self) -> Result<&'a str, String> {
+        self.fields
+            .next()
+            .ok_or_else(|| self.error("missing value"))
+    }
+
+    fn next<T: FromStr>(&mut self) -> Result<T, String> {
+        let value = self.next_str()?;
+        value
+            .parse()
+            .map_err(|_| self.error(&format!("invalid value \"{}\"", value)))
+    }
+
+    /// A value or `-` for none.
+    fn optional<T: FromStr>(&mut self) -> Result<Option<T>, String> {
+        match self.next_str()? {
+            "-" => Ok(None),
+            value => value
+                .parse()
+                .map(Some)
+                .map_err(|_| self.error(&format!("invalid value \"{}\"", value))),
+        }
+    }
+
+    /// Ids separated by commas, or `-` for none.
+    fn list(&mut self) -> Result<Vec<u32>, String> {
+        match self.next_str()? {
+            "-" => Ok(Vec::new()),
+            values => values
+                .split(',')
+                .map(|value| {
+                    value
+                        .parse()
+                        .map_err(|_| self.error(&format!("invalid id \"{}\"", value)))
+                })
+                .collect(),
+        }
+    }
+
+    /// Gene sequences separated by commas, or `-` for a genome without genes.
+    fn genome(&mut self) -> Result<Genome, String> {
+        let genes = match self.next_str()? {
+            "-" => "",
+            genes => genes,
+        };
+
+        let genes = genes
+            .split(',')
+            .filter(|gene| !gene.is_empty())
+            .map(|gene| {
+                if !gene.chars().all(|c| matches!(c, 'A' | 'T' | 'C' | 'G')) {
+                    return Err(self.error(&format!("invalid gene \"{}\"", gene)));
+                }
+                Ok(Gene {
+                    sequence: gene.chars().map(Nucleotide::from).collect(),
+                })
+            })
+            .collect::<Result<Vec<Gene>, String>>()?;
+
+        Ok(Genome { genes })
+    }
+
+    fn end(&mut self) -> Result<(), String> {
+        match self.fields.next() {
+            Some(value) => Err(self.error(&format!("unexpected value \"{}\"", value))),
+            None => Ok(()),
+        }
+    }
+}
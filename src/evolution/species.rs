@@ -0,0 +1,333 @@
+use std::collections::HashMap;
+
+use super::genes::{combine_genes_into_one_sequence, Genome, Nucleotide, Sequence};
+
+/// How the difference between two genomes is measured.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DistanceMetric {
+    /// Nucleotides that differ at the same position, plus the difference in length.
+    Hamming,
+    /// Fewest insertions, deletions and substitutions of nucleotides that turn one
+    /// genome into the other. Slower, but not thrown off by shifted genes.
+    Edit,
+}
+
+impl DistanceMetric {
+    /// The full distance between two genomes. The simulation only asks whether genomes
+    /// are `within` a threshold, this is what tests check that against.
+    #[cfg(test)]
+    pub fn distance(self, a: &Genome, b: &Genome) -> usize {
+        let a = combine_genes_into_one_sequence(&a.genes);
+        let b = combine_genes_into_one_sequence(&b.genes);
+        match self {
+            DistanceMetric::Hamming => {
+                let differences = a.iter().zip(b.iter()).filter(|(a, b)| a != b).count();
+                differences + a.len().abs_diff(b.len())
+            }
+            DistanceMetric::Edit => {
+                let mut previous: Vec<usize> = (0..=b.len()).collect();
+                for (i, a) in a.iter().enumerate() {
+                    let mut current = vec![i + 1; b.len() + 1];
+                    for (j, b) in b.iter().enumerate() {
+                        let substitution = previous[j] + usize::from(a != b);
+                        current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+                    }
+                    previous = current;
+                }
+                previous[b.len()]
+            }
+        }
+    }
+
+    /// Whether two genomes are at most `threshold` apart.
+    pub fn within(self, a: &Genome, b: &Genome, threshold: usize) -> bool {
+        self.sequences_within(
+            &combine_genes_into_one_sequence(&a.genes),
+            &combine_genes_into_one_sequence(&b.genes),
+            threshold,
+            &mut DistanceRows::default(),
+        )
+    }
+
+    /// Whether two genomes already combined into one sequence each are at most
+    /// `threshold` apart. The edit distance only fills the cells of its table at most
+    /// `threshold` from the diagonal and gives up as soon as a row is all further than
+    /// that, reusing `rows` between calls.
+    fn sequences_within(
+        self,
+        a: &[Nucleotide],
+        b: &[Nucleotide],
+        threshold: usize,
+        rows: &mut DistanceRows,
+    ) -> bool {
+        if a.len().abs_diff(b.len()) > threshold {
+            return false;
+        }
+
+        match self {
+            DistanceMetric::Hamming => {
+                let differences = a.iter().zip(b.iter()).filter(|(a, b)| a != b).count();
+                differences + a.len().abs_diff(b.len()) <= threshold
+            }
+            DistanceMetric::Edit => {
+                let DistanceRows { previous, current } = rows;
+                let outside = threshold + 1;
+                previous.clear();
+                previous.extend(0..=b.len());
+                current.clear();
+                current.resize(b.len() + 1, outside);
+
+                for (i, a) in a.iter().enumerate() {
+                    let first = (i + 1).saturating_sub(threshold).max(1);
+                    let last = (i + 1 + threshold).min(b.len());
+                    current[first - 1] = if first == 1 { i + 1 } else { outside };
+                    let mut nearest = current[first - 1];
+                    for j in first..=last {
+                        let substitution = previous[j - 1] + usize::from(*a != b[j - 1]);
+                        current[j] = substitution.min(previous[j] + 1).min(current[j - 1] + 1);
+                        nearest = nearest.min(current[j]);
+                    }
+                    if last < b.len() {
+                        current[last + 1] = outside;
+                    }
+
+                    if nearest > threshold {
+                        return false;
+                    }
+                    std::mem::swap(previous, current);
+                }
+                previous[b.len()] <= threshold
+            }
+        }
+    }
+}
+
+/// The two rows of the edit distance table that are needed at a time.
+#[derive(Default)]
+struct DistanceRows {
+    previous: Vec<usize>,
+    current: Vec<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpeciationConfig {
+    pub metric: DistanceMetric,
+    /// Organisms at most this far from the representative of a species belong to it.
+    pub threshold: usize,
+    /// Whether organisms further apart than `threshold` may not breed with each other.
+    pub breeding_barrier: bool,
+}
+
+impl Default for SpeciationConfig {
+    fn default() -> Self {
+        Self {
+            metric: DistanceMetric::Edit,
+            threshold: 6,
+            breeding_barrier: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Species {
+    pub id: u32,
+    /// New organisms are compared to this genome, which is the genome of the oldest
+    /// member at the last census.
+    pub representative: Genome,
+    /// Organism ids at the last census.
+    pub members: Vec<u32>,
+    pub appeared: u64,
+    /// The species the parent of the first member belonged to.
+    pub split_from: Option<u32>,
+    pub extinct: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpeciesEvent {
+    Appeared {
+        species: u32,
+        tick: u64,
+    },
+    /// A species appeared among the offspring of another.
+    Split {
+        species: u32,
+        from: u32,
+        tick: u64,
+    },
+    Extinct {
+        species: u32,
+        tick: u64,
+    },
+}
+
+/// An organism as the census sees it.
+pub struct CensusEntry<'a> {
+    pub id: u32,
+    pub genome: &'a Genome,
+    pub parents: &'a [u32],
+}
+
+/// Groups organisms into species by threshold clustering: each organism joins the
+/// first species whose representative is close enough, or starts a new one. Species
+/// keep their id from one census to the next as long as they have members.
+#[derive(Debug)]
+pub struct SpeciesTracker {
+    pub config: SpeciationConfig,
+    /// Every species that ever existed, in the order they appeared.
+    species: Vec<Species>,
+    next_id: u32,
+    /// Species of every organism at the last census.
+    species_of: HashMap<u32, u32>,
+    events: Vec<SpeciesEvent>,
+}
+
+impl SpeciesTracker {
+    pub fn new(config: SpeciationConfig) -> Self {
+        Self {
+            config,
+            species: Vec::new(),
+            next_id: 0,
+            species_of: HashMap::new(),
+            events: Vec::new(),
+        }
+    }
+
+    /// Assigns every organism to a species, oldest organisms first, and records which
+    /// species appeared, split off or went extinct since the last census.
+    pub fn census(&mut self, organisms: &[CensusEntry], tick: u64) {
+        let SpeciationConfig {
+            metric, threshold, ..
+        } = self.config;
+        let mut rows = DistanceRows::default();
+        let mut species_of = HashMap::new();
+        // Sequences of the representatives of living species, by index in `species`.
+        let mut representatives: Vec<Option<Sequence>> = self
+            .species
+            .iter()
+            .map(|species| {
+                species
+                    .extinct
+                    .is_none()
+                    .then(|| combine_genes_into_one_sequence(&species.representative.genes))
+            })
+            .collect();
+        for species in self.species.iter_mut() {
+            species.members.clear();
+        }
+
+        for organism in organisms {
+            let sequence = combine_genes_into_one_sequence(&organism.genome.genes);
+            let existing = representatives.iter().position(|representative| {
+                representative.as_ref().is_some_and(|representative| {
+                    metric.sequences_within(representative, &sequence, threshold, &mut rows)
+                })
+            });
+
+            let index = match existing {
+                Some(index) => index,
+                None => {
+                    let split_from = organism
+                        .parents
+                        .iter()
+                        .find_map(|parent| self.species_of.get(parent).copied());
+                    let id = self.next_id;
+                    self.next_id += 1;
+                    self.species.push(Species {
+                        id,
+                        representative: organism.genome.clone(),
+                        members: Vec::new(),
+                        appeared: tick,
+                        split_from,
+                        extinct: None,
+                    });
+                    self.events.push(match split_from {
+                        Some(from) => SpeciesEvent::Split {
+                            species: id,
+                            from,
+                            tick,
+                        },
+                        None => SpeciesEvent::Appeared { species: id, tick },
+                    });
+                    representatives.push(None);
+                    self.species.len() - 1
+                }
+            };
+
+            let species = &mut self.species[index];
+            if species.members.is_empty() {
+                species.representative = organism.genome.clone();
+                representatives[index] = Some(sequence);
+            }
+            species.members.push(organism.id);
+            species_of.insert(organism.id, species.id);
+        }
+
+        for species in self.species.iter_mut() {
+            if species.members.is_empty() && species.extinct.is_none() {
+                species.extinct = Some(tick);
+                self.events.push(SpeciesEvent::Extinct {
+                    species: species.id,
+                    tick,
+                });
+            }
+        }
+
+        self.species_of = species_of;
+    }
+
+    /// Tracker that continues from earlier species and events, for example read back
+    /// from a snapshot. `species` has to be in the order they appeared.
+    pub fn restore(
+        config: SpeciationConfig,
+        species: Vec<Species>,
+        events: Vec<SpeciesEvent>,
+    ) -> Self {
+        let species_of = species
+            .iter()
+            .filter(|species| species.extinct.is_none())
+            .flat_map(|species| species.members.iter().map(|member| (*member, species.id)))
+            .collect();
+
+        Self {
+            config,
+            next_id: species
+                .iter()
+                .map(|species| species.id + 1)
+                .max()
+                .unwrap_or(0),
+            species,
+            species_of,
+            events,
+        }
+    }
+
+    /// Every species that ever existed, in the order they appeared.
+    pub fn all(&self) -> &[Species] {
+        &self.species
+    }
+
+    pub fn get(&self, id: u32) -> Option<&Species> {
+        self.species.iter().find(|species| species.id == id)
+    }
+
+    pub fn species_of(&self, organism: u32) -> Option<u32> {
+        self.species_of.get(&organism).copied()
+    }
+
+    /// Species with members at the last census.
+    pub fn living(&self) -> impl Iterator<Item = &Species> {
+        self.species
+            .iter()
+            .filter(|species| species.extinct.is_none())
+    }
+
+    /// Everything that happened to species, in order.
+    pub fn events(&self) -> &[SpeciesEvent] {
+        &self.events
+    }
+
+    /// Whether two organisms may breed, see `SpeciationConfig::breeding_barrier`.
+    pub fn can_breed(&self, a: &Genome, b: &Genome) -> bool {
+        !self.config.breeding_barrier || self.config.metric.within(a, b, self.config.threshold)
+    }
+}
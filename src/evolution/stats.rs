@@ -0,0 +1,453 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    io::{self, Write},
+};
+
+use raylib::{
+    color::Color,
+    math::Vector2,
+    prelude::{RaylibDraw, RaylibDrawHandle},
+};
+
+use super::{
+    genes::{get_genome_traits, GeneTrait},
+    Organism,
+};
+use crate::engine::Entity;
+
+/// Ticks shown in the chart, the latest ones. Only the records of these ticks are kept.
+pub const CHART_TICKS: usize = 300;
+const CHART_WIDTH: f32 = 400.0;
+const CHART_HEIGHT: f32 = 160.0;
+const SERIES_COLORS: [Color; 3] = [Color::SKYBLUE, Color::WHITE, Color::ORANGE];
+
+/// Smallest, mean and largest value of a number over the population.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Summary {
+    pub min: u32,
+    pub mean: f64,
+    pub max: u32,
+}
+
+impl Summary {
+    /// All zero for an empty population.
+    pub fn of(values: impl Iterator<Item = u32> + Clone) -> Self {
+        let count = values.clone().count();
+        if count == 0 {
+            return Self::default();
+        }
+
+        Self {
+            min: values.clone().min().unwrap_or(0),
+            mean: values.clone().map(f64::from).sum::<f64>() / count as f64,
+            max: values.max().unwrap_or(0),
+        }
+    }
+}
+
+/// Numbers for a single tick of a run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TickStats {
+    pub tick: u64,
+    pub population: u32,
+    pub age: Summary,
+    pub health: Summary,
+    pub food: Summary,
+    /// Share of the organisms with each trait value, in the order of the trait
+    /// columns.
+    pub trait_frequencies: Vec<f64>,
+    /// Shannon entropy in bits of the alleles at every locus, in the order of the
+    /// diversity columns. A locus is a kind of trait and the allele of an organism is
+    /// the value of its first gene for that kind, or none. 0 when all organisms have
+    /// the same allele.
+    pub diversity: Vec<f64>,
+    /// Organisms born and died since the previous tick.
+    pub births: u32,
+    pub deaths: u32,
+}
+
+impl TickStats {
+    /// Mean entropy over all loci.
+    pub fn mean_diversity(&self) -> f64 {
+        self.diversity.iter().sum::<f64>() / self.diversity.len().max(1) as f64
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StatsFormat {
+    Csv,
+    JsonLines,
+}
+
+/// Shannon entropy in bits of how often each value occurs.
+pub fn shannon_entropy<T: std::hash::Hash + Eq>(values: impl Iterator<Item = T>) -> f64 {
+    let mut counts: HashMap<T, usize> = HashMap::new();
+    for value in values {
+        *counts.entry(value).or_default() += 1;
+    }
+
+    let total = counts.values().sum::<usize>() as f64;
+    counts
+        .values()
+        .map(|count| {
+            let p = *count as f64 / total;
+            p * (1.0 / p).log2()
+        })
+        .sum()
+}
+
+/// Collects statistics every tick and writes each record to its outputs right away,
+/// so they can be followed while the run goes on. Only the latest `CHART_TICKS` records
+/// are kept in memory.
+pub struct StatsSystem {
+    /// Names of the trait values, one column each.
+    values: Vec<String>,
+    /// Names of the kinds of traits, whose diversity is recorded.
+    loci: Vec<String>,
+    births: u32,
+    deaths: u32,
+    records: VecDeque<TickStats>,
+    outputs: Vec<(StatsFormat, Box<dyn Write>)>,
+}
+
+impl StatsSystem {
+    pub fn new(gene_map: &HashMap<String, GeneTrait>) -> Self {
+        let mut values: Vec<String> = gene_map
+            .values()
+            .map(|gene_trait| format!("{:?}", gene_trait.value()))
+            .collect();
+        values.sort();
+        values.dedup();
+        let mut loci: Vec<String> = gene_map
+            .values()
+            .map(|gene_trait| gene_trait.name().to_string())
+            .collect();
+        loci.sort();
+        loci.dedup();
+
+        Self {
+            values,
+            loci,
+            births: 0,
+            deaths: 0,
+            records: VecDeque::new(),
+            outputs: Vec::new(),
+        }
+    }
+
+    /// The latest records, oldest first.
+    pub fn records(&self) -> &VecDeque<TickStats> {
+        &self.records
+    }
+
+    pub fn count_birth(&mut self) {
+        self.births += 1;
+    }
+
+    pub fn count_death(&mut self) {
+        self.deaths += 1;
+    }
+
+    /// Writes the records that are still kept and every later one to `writer`.
+    pub fn add_output(
+        &mut self,
+        format: StatsFormat,
+        mut writer: impl Write + 'static,
+    ) -> io::Result<()> {
+        if format == StatsFormat::Csv {
+            writeln!(writer, "{}", self.csv_header())?;
+        }
+        for record in self.records.iter() {
+            self.write_record(&mut writer, format, record)?;
+        }
+        writer.flush()?;
+
+        self.outputs.push((format, Box::new(writer)));
+        Ok(())
+    }
+
+    /// Stops writing, for example after an output failed.
+    pub fn close_outputs(&mut self) {
+        self.outputs.clear();
+    }
+
+    /// Records the population at the end of a tick, with the births and deaths counted
+    /// since the last record.
+    pub fn record(
+        &mut self,
+        tick: u64,
+        entities: &[Entity],
+        gene_map: &HashMap<String, GeneTrait>,
+    ) -> io::Result<()> {
+        let organisms: Vec<&Organism> = entities
+            .iter()
+            .filter_map(|entity| entity.get_component::<Organism>())
+            .collect();
+
+        let traits: Vec<Vec<GeneTrait>> = organisms
+            .iter()
+            .map(|organism| get_genome_traits(organism.genome(), gene_map))
+            .collect();
+
+        let mut value_counts = vec![0; self.values.len()];
+        for traits in traits.iter() {
+            let mut names: Vec<String> = traits
+                .iter()
+                .map(|gene_trait| format!("{:?}", gene_trait.value()))
+                .collect();
+            // An organism with two genes for the same value still counts once.
+            names.sort();
+            names.dedup();
+            for name in names {
+                if let Ok(index) = self.values.binary_search(&name) {
+                    value_counts[index] += 1;
+                }
+            }
+        }
+
+        let population = organisms.len().max(1) as f64;
+        let record = TickStats {
+            tick,
+            population: organisms.len() as u32,
+            age: Summary::of(organisms.iter().map(|organism| organism.age())),
+            health: Summary::of(organisms.iter().map(|organism| organism.health())),
+            food: Summary::of(organisms.iter().map(|organism| organism.food())),
+            trait_frequencies: value_counts
+                .iter()
+                .map(|count| *count as f64 / population)
+                .collect(),
+            diversity: self
+                .loci
+                .iter()
+                .map(|locus| {
+                    shannon_entropy(traits.iter().map(|traits| {
+                        traits
+                            .iter()
+                            .find(|gene_trait| gene_trait.name() == locus)
+                            .map(|gene_trait| format!("{:?}", gene_trait.value()))
+                    }))
+                })
+                .collect(),
+            births: self.births,
+            deaths: self.deaths,
+        };
+        self.births = 0;
+        self.deaths = 0;
+
+        let mut outputs = std::mem::take(&mut self.outputs);
+        let written = outputs.iter_mut().try_for_each(|(format, writer)| {
+            self.write_record(writer, *format, &record)?;
+            writer.flush()
+        });
+        self.outputs = outputs;
+        if self.records.len() == CHART_TICKS {
+            self.records.pop_front();
+        }
+        self.records.push_back(record);
+        written
+    }
+
+    fn csv_header(&self) -> String {
+        let mut columns: Vec<String> = [
+            "tick",
+            "population",
+            "age_min",
+            "age_mean",
+            "age_max",
+            "health_min",
+            "health_mean",
+            "health_max",
+            "food_min",
+            "food_mean",
+            "food_max",
+            "births",
+            "deaths",
+        ]
+        .iter()
+        .map(|column| column.to_string())
+        .collect();
+        columns.extend(self.values.iter().cloned());
+        columns.extend(self.loci.iter().map(|locus| format!("diversity_{}", locus)));
+        columns.join(",")
+    }
+
+    fn write_record(
+        &self,
+        writer: &mut impl Write,
+        format: StatsFormat,
+        record: &TickStats,
+    ) -> io::Result<()> {
+        match format {
+            StatsFormat::Csv => {
+                let mut columns = vec![record.tick.to_string(), record.population.to_string()];
+                for summary in [record.age, record.health, record.food] {
+                    columns.push(summary.min.to_string());
+                    columns.push(format!("{:.3}", summary.mean));
+                    columns.push(summary.max.to_string());
+                }
+                columns.push(record.births.to_string());
+                columns.push(record.deaths.to_string());
+                columns.extend(record.trait_frequencies.iter().map(|f| format!("{:.4}", f)));
+                columns.extend(record.diversity.iter().map(|d| format!("{:.4}", d)));
+                writeln!(writer, "{}", columns.join(","))
+            }
+            StatsFormat::JsonLines => {
+                let summary = |summary: Summary| {
+                    format!(
+                        "{{\"min\":{},\"mean\":{:.3},\"max\":{}}}",
+                        summary.min, summary.mean, summary.max
+                    )
+                };
+                let traits: Vec<String> = self
+                    .values
+                    .iter()
+                    .zip(record.trait_frequencies.iter())
+                    .map(|(name, frequency)| format!("\"{}\":{:.4}", name, frequency))
+                    .collect();
+                let diversity: Vec<String> = self
+                    .loci
+                    .iter()
+                    .zip(record.diversity.iter())
+                    .map(|(locus, diversity)| format!("\"{}\":{:.4}", locus, diversity))
+                    .collect();
+                writeln!(
+                    writer,
+                    "{{\"tick\":{},\"population\":{},\"age\":{},\"health\":{},\"food\":{},\
+                     \"births\":{},\"deaths\":{},\"traits\":{{{}}},\"diversity\":{{{}}}}}",
+                    record.tick,
+                    record.population,
+                    summary(record.age),
+                    summary(record.health),
+                    summary(record.food),
+                    record.births,
+                    record.deaths,
+                    traits.join(","),
+                    diversity.join(",")
+                )
+            }
+        }
+    }
+}
+
+/// What the chart shows.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChartMetric {
+    Population,
+    Age,
+    Health,
+    Food,
+    Diversity,
+    BirthsAndDeaths,
+}
+
+impl ChartMetric {
+    pub const ALL: [ChartMetric; 6] = [
+        ChartMetric::Population,
+        ChartMetric::Age,
+        ChartMetric::Health,
+        ChartMetric::Food,
+        ChartMetric::Diversity,
+        ChartMetric::BirthsAndDeaths,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ChartMetric::Population => "Population",
+            ChartMetric::Age => "Age",
+            ChartMetric::Health => "Health",
+            ChartMetric::Food => "Food",
+            ChartMetric::Diversity => "Diversity",
+            ChartMetric::BirthsAndDeaths => "Births",
+        }
+    }
+
+    /// Names of the lines and their value in a record.
+    fn series(&self, record: &TickStats) -> Vec<(&'static str, f64)> {
+        let summary = |summary: Summary| {
+            vec![
+                ("min", summary.min as f64),
+                ("mean", summary.mean),
+                ("max", summary.max as f64),
+            ]
+        };
+        match self {
+            ChartMetric::Population => vec![("organisms", record.population as f64)],
+            ChartMetric::Age => summary(record.age),
+            ChartMetric::Health => summary(record.health),
+            ChartMetric::Food => summary(record.food),
+            ChartMetric::Diversity => vec![("mean bits per locus", record.mean_diversity())],
+            ChartMetric::BirthsAndDeaths => vec![
+                ("births", record.births as f64),
+                ("deaths", record.deaths as f64),
+            ],
+        }
+    }
+}
+
+/// Draws a line chart of the latest records in the bottom-left corner of the screen.
+pub struct StatsRenderSystem;
+
+impl StatsRenderSystem {
+    pub fn render(&self, rl: &mut RaylibDrawHandle, stats: &StatsSystem, metric: ChartMetric) {
+        let records = stats.records();
+        let Some(latest) = records.back() else {
+            return;
+        };
+
+        let series: Vec<Vec<(&str, f64)>> =
+            records.iter().map(|record| metric.series(record)).collect();
+        let max = series
+            .iter()
+            .flatten()
+            .map(|(_, value)| *value)
+            .fold(1.0, f64::max);
+
+        let left = 10.0;
+        let top = rl.get_screen_height() as f32 - 40.0 - CHART_HEIGHT;
+        rl.draw_rectangle(
+            left as i32 - 5,
+            top as i32 - 25,
+            CHART_WIDTH as i32 + 10,
+            CHART_HEIGHT as i32 + 30,
+            Color::BLACK.fade(0.6),
+        );
+        rl.draw_text(
+            &format!(
+                "{} over {} ticks, max {:.1}",
+                metric.name(),
+                records.len(),
+                max
+            ),
+            left as i32,
+            top as i32 - 20,
+            16,
+            Color::WHITE,
+        );
+
+        let point = |i: usize, value: f64| {
+            Vector2::new(
+                left + i as f32 * CHART_WIDTH / (CHART_TICKS - 1) as f32,
+                top + CHART_HEIGHT - (value / max) as f32 * CHART_HEIGHT,
+            )
+        };
+        for (line, color) in SERIES_COLORS.iter().enumerate() {
+            for i in 1..series.len() {
+                if let (Some((_, from)), Some((_, to))) =
+                    (series[i - 1].get(line), series[i].get(line))
+                {
+                    rl.draw_line_v(point(i - 1, *from), point(i, *to), *color);
+                }
+            }
+        }
+
+        for (line, (name, value)) in metric.series(latest).iter().enumerate() {
+            rl.draw_text(
+                &format!("{} {:.1}", name, value),
+                left as i32 + line as i32 * 130,
+                (top + CHART_HEIGHT) as i32 - 18,
+                16,
+                SERIES_COLORS[line % SERIES_COLORS.len()],
+            );
+        }
+    }
+}
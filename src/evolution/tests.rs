@@ -1,4 +1,21 @@
+use std::{cell::RefCell, io::Write, rc::Rc};
+
+use rand::SeedableRng;
+use raylib::{color::Color, math::Vector2};
+
+use crate::engine::{Entity, EntityBuilder};
+use crate::evolution::breeding::*;
+use crate::evolution::fasta::*;
+use crate::evolution::foraging::*;
 use crate::evolution::genes::*;
+use crate::evolution::genetic_algorithm::*;
+use crate::evolution::lineage::*;
+use crate::evolution::snapshot;
+use crate::evolution::species::*;
+use crate::evolution::stats::*;
+use crate::evolution::{
+    organism_at, Appearance, Body, Evolution, EvolutionOptions, Organism, Shape, MAX_POPULATION,
+};
 
 #[test]
 fn test_red_gene() {
@@ -332,12 +349,12 @@ fn test_high_speed_gene() {
     let mut gene_builder = GeneBuilder::new();
     let high_speed_gene = gene_builder.high_speed().build_gene();
 
-    //TTTTT
+    //TCTCT
     let expected_sequence = vec![
         Nucleotide::T,
+        Nucleotide::C,
         Nucleotide::T,
-        Nucleotide::T,
-        Nucleotide::T,
+        Nucleotide::C,
         Nucleotide::T,
     ];
 
@@ -349,12 +366,12 @@ fn test_medium_speed_gene() {
     let mut gene_builder = GeneBuilder::new();
     let medium_speed_gene = gene_builder.medium_speed().build_gene();
 
-    //TTGTT
+    //TCGCT
     let expected_sequence = vec![
         Nucleotide::T,
-        Nucleotide::T,
+        Nucleotide::C,
         Nucleotide::G,
-        Nucleotide::T,
+        Nucleotide::C,
         Nucleotide::T,
     ];
 
@@ -366,12 +383,12 @@ fn test_low_speed_gene() {
     let mut gene_builder = GeneBuilder::new();
     let low_speed_gene = gene_builder.low_speed().build_gene();
 
-    //TTATT
+    //TCACT
     let expected_sequence = vec![
         Nucleotide::T,
-        Nucleotide::T,
+        Nucleotide::C,
         Nucleotide::A,
-        Nucleotide::T,
+        Nucleotide::C,
         Nucleotide::T,
     ];
 
@@ -383,10 +400,10 @@ fn test_no_speed_gene() {
     let mut gene_builder = GeneBuilder::new();
     let no_speed_gene = gene_builder.no_speed().build_gene();
 
-    //TTAAT
+    //TCAAT
     let expected_sequence = vec![
         Nucleotide::T,
-        Nucleotide::T,
+        Nucleotide::C,
         Nucleotide::A,
         Nucleotide::A,
         Nucleotide::T,
@@ -561,3 +578,1376 @@ fn test_create_sequence_from_str() {
 
     assert_eq!(sequence, expected_sequence);
 }
+
+#[test]
+fn test_every_trait_value_has_its_own_code() {
+    let gene_map = create_gene_map();
+    let mut values: Vec<String> = gene_map
+        .values()
+        .map(|gene_trait| format!("{:?}", gene_trait))
+        .collect();
+    values.sort();
+    values.dedup();
+
+    // A code used twice would leave one of its traits out of the map.
+    assert_eq!(values.len(), 33);
+    assert_eq!(gene_map.len(), 33);
+    assert_eq!(get_gene_sequences_by_trait(&gene_map).len(), 10);
+}
+
+#[test]
+fn test_random_genome_has_one_gene_per_trait() {
+    let gene_map = create_gene_map();
+    let groups = get_gene_sequences_by_trait(&gene_map);
+    let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+
+    let genome = create_random_genome(&mut rng, &gene_map);
+    let sequence = sequence_str(&genome);
+    let codes = find_coding_regions(&sequence);
+
+    assert_eq!(genome.genes.len(), groups.len());
+    assert_eq!(codes.len(), groups.len());
+    for (code, group) in codes.into_iter().zip(groups.iter()) {
+        assert!(group.contains(&sequence[code].to_string()));
+    }
+}
+
+#[test]
+fn test_mark_gene() {
+    let gene = mark_gene(&create_sequence_from_str("AATTC"));
+    let sequence: String = gene.sequence.iter().map(|n| char::from(*n)).collect();
+
+    assert_eq!(sequence, "ATGAATTCTAA");
+}
+
+#[test]
+fn test_find_coding_regions() {
+    // A start codon without a stop codon in the right place, a gene after a spacer and
+    // a gene ending with another stop codon.
+    let sequence = "ATGCCGGCATGAATTCTAAGGATGCCCGTTGA";
+
+    let codes: Vec<&str> = find_coding_regions(sequence)
+        .into_iter()
+        .map(|code| &sequence[code])
+        .collect();
+
+    assert_eq!(codes, vec!["AATTC", "CCCGT"]);
+    assert!(find_coding_regions("AATTCCCCGT").is_empty());
+    assert!(find_coding_regions("ATGAATT").is_empty());
+}
+
+#[test]
+fn test_traits_do_not_shift_after_an_indel_in_a_spacer() {
+    let gene_map = create_gene_map();
+    let traits = |sequence: &str| -> Vec<String> {
+        get_gene_traits_from_genome_sequence(sequence, &gene_map)
+            .iter()
+            .map(|gene_trait| format!("{:?}", gene_trait))
+            .collect()
+    };
+    let original = traits("CCATGAATTCTAAGGATGCCCGTTAA");
+
+    assert_eq!(original.len(), 2);
+    assert_eq!(traits("CATGAATTCTAAGGATGCCCGTTAA"), original);
+    assert_eq!(traits("CCATGAATTCTAAGGTATGCCCGTTAA"), original);
+}
+
+#[test]
+fn test_split_into_genes() {
+    let genes = split_into_genes("CCATGAATTCTAAGGATGCCCGTTAAGA");
+    let genes: Vec<String> = genes
+        .iter()
+        .map(|gene| gene.sequence.iter().map(|n| char::from(*n)).collect())
+        .collect();
+
+    assert_eq!(genes, vec!["CCATGAATTCTAA", "GGATGCCCGTTAA", "GA"]);
+    assert!(split_into_genes("").is_empty());
+}
+
+#[test]
+fn test_appearance_from_genes() {
+    let gene_map = create_gene_map();
+    let mut gene_builder = GeneBuilder::new();
+    let genes = vec![
+        gene_builder.blue().build_gene(),
+        gene_builder.large().build_gene(),
+        gene_builder.square().build_gene(),
+    ];
+    let genome = gene_builder.build_genome(genes);
+
+    let appearance = Appearance::from_traits(&get_genome_traits(&genome, &gene_map));
+
+    assert_eq!(appearance.color, Color::BLUE);
+    assert_eq!(appearance.radius, 16.0);
+    assert_eq!(appearance.shape, Shape::Square);
+}
+
+#[test]
+fn test_appearance_defaults_without_genes() {
+    let appearance = Appearance::from_traits(&[]);
+
+    assert_eq!(appearance.color, Color::GRAY);
+    assert_eq!(appearance.radius, 10.0);
+    assert_eq!(appearance.shape, Shape::Circle);
+}
+
+#[test]
+fn test_organism_at_picks_topmost() {
+    let organism = |id: u32, x: f32, shape: Shape| {
+        EntityBuilder::new(id)
+            .with_component(Body {
+                position: Vector2::new(x, 0.0),
+            })
+            .with_component(Appearance {
+                color: Color::GRAY,
+                radius: 10.0,
+                shape,
+            })
+            .build()
+    };
+    let entities = vec![
+        organism(0, 0.0, Shape::Circle),
+        organism(1, 5.0, Shape::Square),
+    ];
+
+    assert_eq!(organism_at(&entities, Vector2::new(-8.0, 0.0)), Some(0));
+    assert_eq!(organism_at(&entities, Vector2::new(2.0, 0.0)), Some(1));
+    // Inside the corner of the square, outside the circle.
+    assert_eq!(organism_at(&entities, Vector2::new(14.0, 9.0)), Some(1));
+    assert_eq!(organism_at(&entities, Vector2::new(-8.0, 8.0)), None);
+}
+
+fn moving_organism(id: u32, position: Vector2, speed: f32, radius: f32) -> Entity {
+    hunting_organism(id, position, speed, radius, Diet::Herbivore)
+}
+
+fn hunting_organism(id: u32, position: Vector2, speed: f32, radius: f32, diet: Diet) -> Entity {
+    let mut gene_builder = GeneBuilder::new();
+    let gene = gene_builder.medium().build_gene();
+    let genome = gene_builder.build_genome(vec![gene]);
+    let organism = gene_builder.build_organism(genome);
+
+    EntityBuilder::new(id)
+        .with_component(organism)
+        .with_component(Body { position })
+        .with_component(Appearance {
+            color: Color::GRAY,
+            radius,
+            shape: Shape::Circle,
+        })
+        .with_component(Movement::new(speed, 0.0))
+        .with_component(diet)
+        .build()
+}
+
+fn food_at(id: u32, position: Vector2) -> Entity {
+    EntityBuilder::new(id)
+        .with_component(Food)
+        .with_component(Body { position })
+        .build()
+}
+
+#[test]
+fn test_speed_from_genes() {
+    let gene_map = create_gene_map();
+    let mut gene_builder = GeneBuilder::new();
+    let fast_gene = gene_builder.high_speed().build_gene();
+    let fast = gene_builder.build_genome(vec![fast_gene]);
+    let still_gene = gene_builder.no_speed().build_gene();
+    let still = gene_builder.build_genome(vec![still_gene]);
+
+    assert_eq!(
+        speed_from_traits(&get_genome_traits(&fast, &gene_map)),
+        80.0
+    );
+    assert_eq!(
+        speed_from_traits(&get_genome_traits(&still, &gene_map)),
+        0.0
+    );
+    assert_eq!(speed_from_traits(&[]), 0.0);
+}
+
+#[test]
+fn test_movement_cost_grows_with_size_and_speed() {
+    assert_eq!(movement_cost(10.0, 0.0), 0.0);
+    assert!(movement_cost(16.0, 50.0) > movement_cost(10.0, 50.0));
+    assert!(movement_cost(10.0, 80.0) > movement_cost(10.0, 50.0));
+}
+
+#[test]
+fn test_organism_seeks_nearest_food() {
+    let mut organisms = vec![moving_organism(0, Vector2::new(100.0, 100.0), 50.0, 10.0)];
+    let mut food = vec![
+        food_at(1, Vector2::new(100.0, 160.0)),
+        food_at(2, Vector2::new(100.0, 20.0)),
+    ];
+    let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+
+    ForagingSystem.update(
+        &mut organisms,
+        &mut food,
+        Vector2::new(1000.0, 1000.0),
+        1.0,
+        &mut rng,
+    );
+
+    let body = organisms[0].get_component::<Body>().unwrap();
+    let velocity = organisms[0].get_component::<Movement>().unwrap().velocity();
+    assert!((body.position.x - 100.0).abs() < 0.001);
+    assert!((body.position.y - 150.0).abs() < 0.001);
+    assert!(velocity.y > 0.0);
+}
+
+#[test]
+fn test_organism_eats_food_it_touches() {
+    let mut organisms = vec![moving_organism(0, Vector2::new(100.0, 100.0), 0.0, 10.0)];
+    let mut food = vec![
+        food_at(1, Vector2::new(105.0, 100.0)),
+        food_at(2, Vector2::new(300.0, 100.0)),
+    ];
+    let food_before = organisms[0].get_component::<Organism>().unwrap().food;
+    let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+
+    ForagingSystem.update(
+        &mut organisms,
+        &mut food,
+        Vector2::new(1000.0, 1000.0),
+        0.1,
+        &mut rng,
+    );
+
+    assert_eq!(food.len(), 1);
+    assert_eq!(food[0].id(), 2);
+    assert_eq!(
+        organisms[0].get_component::<Organism>().unwrap().food,
+        food_before + FOOD_ENERGY
+    );
+}
+
+#[test]
+fn test_organism_bounces_off_world_edge() {
+    let mut organisms = vec![moving_organism(0, Vector2::new(95.0, 50.0), 50.0, 10.0)];
+    let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+
+    ForagingSystem.update(
+        &mut organisms,
+        &mut Vec::new(),
+        Vector2::new(100.0, 100.0),
+        0.2,
+        &mut rng,
+    );
+
+    let body = organisms[0].get_component::<Body>().unwrap();
+    let velocity = organisms[0].get_component::<Movement>().unwrap().velocity();
+    assert_eq!(body.position.x, 100.0);
+    assert!(velocity.x < 0.0);
+}
+
+#[test]
+fn test_moving_costs_food_then_health() {
+    // A large organism at high speed uses more than one point per tick.
+    let mut organisms = vec![moving_organism(0, Vector2::new(0.0, 0.0), 80.0, 16.0)];
+    let cost = movement_cost(16.0, 80.0);
+    let (food, health) = {
+        let organism = organisms[0].get_component::<Organism>().unwrap();
+        (organism.food, organism.health)
+    };
+
+    let ticks = (food as f32 / cost).ceil() as u32 + 4;
+    for _ in 0..ticks {
+        ForagingSystem.pay(&mut organisms);
+    }
+
+    let organism = organisms[0].get_component::<Organism>().unwrap();
+    assert_eq!(organism.food, 0);
+    assert!(organism.health < health);
+}
+
+#[test]
+fn test_standing_still_is_free() {
+    let mut organisms = vec![moving_organism(0, Vector2::new(0.0, 0.0), 0.0, 16.0)];
+    let food = organisms[0].get_component::<Organism>().unwrap().food;
+
+    for _ in 0..100 {
+        ForagingSystem.pay(&mut organisms);
+    }
+
+    assert_eq!(organisms[0].get_component::<Organism>().unwrap().food, food);
+}
+
+#[test]
+fn test_diet_from_genes() {
+    let gene_map = create_gene_map();
+    let mut gene_builder = GeneBuilder::new();
+    let carnivore_gene = gene_builder.carnivore().build_gene();
+    let carnivore = gene_builder.build_genome(vec![carnivore_gene]);
+    let omnivore_gene = gene_builder.omnivore().build_gene();
+    let omnivore = gene_builder.build_genome(vec![omnivore_gene]);
+    let herbivore_gene = gene_builder.herbivore().build_gene();
+    let herbivore = gene_builder.build_genome(vec![herbivore_gene]);
+
+    assert_eq!(
+        Diet::from_traits(&get_genome_traits(&herbivore, &gene_map)),
+        Diet::Herbivore
+    );
+    assert_eq!(
+        Diet::from_traits(&get_genome_traits(&carnivore, &gene_map)),
+        Diet::Carnivore
+    );
+    assert_eq!(
+        Diet::from_traits(&get_genome_traits(&omnivore, &gene_map)),
+        Diet::Omnivore
+    );
+    assert_eq!(Diet::from_traits(&[]), Diet::Herbivore);
+}
+
+#[test]
+fn test_carnivore_eats_smaller_organism_and_gains_its_food() {
+    let mut organisms = vec![
+        hunting_organism(0, Vector2::new(100.0, 100.0), 0.0, 16.0, Diet::Carnivore),
+        moving_organism(1, Vector2::new(120.0, 100.0), 0.0, 6.0),
+    ];
+    organisms[1].get_component_mut::<Organism>().unwrap().food = 17;
+    let food_before = organisms[0].get_component::<Organism>().unwrap().food;
+    let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+
+    let eaten = ForagingSystem.update(
+        &mut organisms,
+        &mut Vec::new(),
+        Vector2::new(1000.0, 1000.0),
+        0.1,
+        &mut rng,
+    );
+
+    assert_eq!(eaten, vec![1]);
+    assert_eq!(organisms.len(), 1);
+    assert_eq!(organisms[0].id(), 0);
+    assert_eq!(
+        organisms[0].get_component::<Organism>().unwrap().food,
+        food_before + 17
+    );
+}
+
+#[test]
+fn test_size_decides_who_is_eaten() {
+    // The carnivore is smaller than its neighbour and the same size as the other.
+    let mut organisms = vec![
+        moving_organism(0, Vector2::new(100.0, 100.0), 0.0, 16.0),
+        hunting_organism(1, Vector2::new(110.0, 100.0), 0.0, 10.0, Diet::Carnivore),
+        moving_organism(2, Vector2::new(120.0, 100.0), 0.0, 10.0),
+    ];
+    let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+
+    ForagingSystem.update(
+        &mut organisms,
+        &mut Vec::new(),
+        Vector2::new(1000.0, 1000.0),
+        0.1,
+        &mut rng,
+    );
+
+    assert_eq!(organisms.len(), 3);
+}
+
+#[test]
+fn test_diet_decides_what_is_eaten() {
+    let mut organisms = vec![
+        hunting_organism(0, Vector2::new(100.0, 100.0), 0.0, 16.0, Diet::Carnivore),
+        moving_organism(1, Vector2::new(500.0, 100.0), 0.0, 16.0),
+        moving_organism(2, Vector2::new(510.0, 100.0), 0.0, 6.0),
+        hunting_organism(3, Vector2::new(800.0, 100.0), 0.0, 16.0, Diet::Omnivore),
+    ];
+    let mut food = vec![
+        food_at(4, Vector2::new(105.0, 100.0)),
+        food_at(5, Vector2::new(805.0, 100.0)),
+    ];
+    let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+
+    ForagingSystem.update(
+        &mut organisms,
+        &mut food,
+        Vector2::new(1000.0, 1000.0),
+        0.1,
+        &mut rng,
+    );
+
+    // The carnivore leaves the plant, the herbivore leaves the smaller organism and
+    // the omnivore eats plants too.
+    assert_eq!(food.len(), 1);
+    assert_eq!(food[0].id(), 4);
+    assert_eq!(organisms.len(), 4);
+}
+
+#[test]
+fn test_predator_chases_smaller_prey() {
+    let mut organisms = vec![
+        hunting_organism(0, Vector2::new(100.0, 100.0), 50.0, 16.0, Diet::Carnivore),
+        moving_organism(1, Vector2::new(100.0, 200.0), 0.0, 6.0),
+        moving_organism(2, Vector2::new(150.0, 100.0), 0.0, 16.0),
+    ];
+    let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+
+    ForagingSystem.update(
+        &mut organisms,
+        &mut Vec::new(),
+        Vector2::new(1000.0, 1000.0),
+        0.1,
+        &mut rng,
+    );
+
+    let velocity = organisms[0].get_component::<Movement>().unwrap().velocity();
+    assert!(velocity.y > 0.0);
+    assert!(velocity.x.abs() < 0.001);
+}
+
+#[test]
+fn test_mutation_rate() {
+    let gene_map = create_gene_map();
+    let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+    let genome = create_random_genome(&mut rng, &gene_map);
+    let sequence = combine_genes_into_one_sequence(&genome.genes);
+
+    let copy = mutate(&genome, 0.0, &mut rng);
+    assert_eq!(combine_genes_into_one_sequence(&copy.genes), sequence);
+
+    let mutated = mutate(&genome, 1.0, &mut rng);
+    let mutated = combine_genes_into_one_sequence(&mutated.genes);
+    assert_eq!(mutated.len(), sequence.len());
+    assert_ne!(mutated, sequence);
+}
+
+#[test]
+fn test_insert_and_delete() {
+    let gene_map = create_gene_map();
+    let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+    let genome = create_random_genome(&mut rng, &gene_map);
+    let sequence = combine_genes_into_one_sequence(&genome.genes);
+
+    let copy = insert_and_delete(&genome, 0.0, &mut rng);
+    assert_eq!(combine_genes_into_one_sequence(&copy.genes), sequence);
+
+    let lengths: Vec<usize> = (0..20)
+        .map(|_| {
+            combine_genes_into_one_sequence(&insert_and_delete(&genome, 0.2, &mut rng).genes).len()
+        })
+        .collect();
+    assert!(lengths.iter().any(|length| *length != sequence.len()));
+    assert!(lengths.iter().all(|length| *length > 0));
+}
+
+#[test]
+fn test_duplicate_and_lose() {
+    let genome = genome_of(vec!["AATTC", "CCCGT", "GGCGA"]);
+    let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+
+    let copy = duplicate_and_lose(&genome, 0.0, &mut rng);
+    assert_eq!(sequence_str(&copy), sequence_str(&genome));
+    assert!(duplicate_and_lose(&genome, 1.0, &mut rng).genes.is_empty());
+
+    let mut grown = false;
+    for _ in 0..50 {
+        let genes = duplicate_and_lose(&genome, 0.3, &mut rng).genes;
+        grown |= genes.len() > genome.genes.len();
+        // Genes are copied or lost whole.
+        assert!(genes
+            .iter()
+            .all(|gene| genome.genes.iter().any(|g| g.sequence == gene.sequence)));
+    }
+    assert!(grown);
+}
+
+#[test]
+fn test_breeding_chance_from_genes() {
+    let gene_map = create_gene_map();
+    let mut gene_builder = GeneBuilder::new();
+    let gene = gene_builder.no_breeding_rate().build_gene();
+    let sterile = gene_builder.build_genome(vec![gene]);
+
+    assert_eq!(
+        Breeding::from_traits(&get_genome_traits(&sterile, &gene_map)).chance,
+        0.0
+    );
+    assert!(Breeding::from_traits(&[]).chance > 0.0);
+}
+
+/// One point for every gene that codes for high speed.
+struct SpeedFitness;
+
+impl Fitness for SpeedFitness {
+    fn fitness(&self, _organism: &Organism, traits: &[GeneTrait]) -> f64 {
+        traits
+            .iter()
+            .filter(|t| matches!(t, GeneTrait::Speed(GeneValue::HighSpeed)))
+            .count() as f64
+    }
+}
+
+fn individuals(fitness: &[f64]) -> Vec<Individual> {
+    let mut gene_builder = GeneBuilder::new();
+    fitness
+        .iter()
+        .map(|&fitness| {
+            let gene = gene_builder.red().build_gene();
+            Individual {
+                genome: gene_builder.build_genome(vec![gene]),
+                fitness,
+            }
+        })
+        .collect()
+}
+
+fn sequence_str(genome: &Genome) -> String {
+    combine_genes_into_one_sequence(&genome.genes)
+        .iter()
+        .map(|n| char::from(*n))
+        .collect()
+}
+
+#[test]
+fn test_tournament_with_whole_population_picks_fittest() {
+    let population = individuals(&[5.0, 3.0, 1.0]);
+    let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+
+    for _ in 0..20 {
+        let picked = select(&population, Selection::Tournament { size: 50 }, &mut rng);
+        assert_eq!(picked.fitness, 5.0);
+    }
+}
+
+#[test]
+fn test_roulette_never_picks_zero_fitness() {
+    let population = individuals(&[2.0, 1.0, 0.0]);
+    let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+
+    for _ in 0..200 {
+        assert!(select(&population, Selection::Roulette, &mut rng).fitness > 0.0);
+    }
+}
+
+#[test]
+fn test_rank_prefers_fitter() {
+    // Rank weights are 3, 2 and 1, however far apart the fitness is.
+    let population = individuals(&[1000.0, 1.0, 0.0]);
+    let mut rng = rand::rngs::StdRng::seed_from_u64(1);
+
+    let mut picks = [0; 3];
+    for _ in 0..6000 {
+        let picked = select(&population, Selection::Rank, &mut rng);
+        let index = population
+            .iter()
+            .position(|individual| std::ptr::eq(individual, picked))
+            .unwrap();
+        picks[index] += 1;
+    }
+
+    assert!(picks[0] > picks[1] && picks[1] > picks[2] && picks[2] > 0);
+    assert!(picks[0] < 4000);
+}
+
+#[test]
+fn test_crossover_takes_genes_from_both_parents() {
+    let gene_builder = GeneBuilder::new();
+    let first = Genome {
+        genes: gene_builder
+            .build_genes_from_sequences(vec!["AAAAA", "AAAAA", "AAAAA", "AAAAA", "AAAAA", "AAAAA"]),
+    };
+    let second = Genome {
+        genes: gene_builder.build_genes_from_sequences(vec!["CCCCC", "CCCCC", "CCCCC", "CCCCC"]),
+    };
+    let mut rng = rand::rngs::StdRng::seed_from_u64(3);
+
+    for kind in [
+        Crossover::SinglePoint,
+        Crossover::TwoPoint,
+        Crossover::Uniform,
+    ] {
+        for _ in 0..20 {
+            let child = crossover(&first, &second, kind, &mut rng);
+            let child = sequence_str(&child);
+
+            assert_eq!(child.len(), 30);
+            // Genes past the end of the shorter parent come from the first one.
+            assert_eq!(&child[20..], "AAAAAAAAAA");
+            assert!(child
+                .as_bytes()
+                .chunks(5)
+                .all(|gene| gene == b"AAAAA" || gene == b"CCCCC"));
+        }
+    }
+
+    // A single cut: some A genes, then only C genes.
+    let child = sequence_str(&crossover(
+        &first,
+        &second,
+        Crossover::SinglePoint,
+        &mut rng,
+    ));
+    let first_c = child[..20].find('C').unwrap_or(20);
+    assert!(child[first_c..20].chars().all(|n| n == 'C'));
+}
+
+#[test]
+fn test_genetic_algorithm_improves_and_keeps_elites() {
+    let config = GaConfig {
+        population_size: 30,
+        elitism: 1,
+        max_generations: 40,
+        stall_generations: 100,
+        ..GaConfig::default()
+    };
+    let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+    let mut algorithm = GeneticAlgorithm::new(config, SpeedFitness, &mut rng);
+
+    let reason = algorithm.run(&mut rng);
+
+    assert_eq!(reason, StopReason::MaxGenerations);
+    let history = algorithm.history();
+    assert_eq!(history.len(), 41);
+    assert!(history
+        .windows(2)
+        .all(|generations| generations[1].best >= generations[0].best));
+    assert_eq!(algorithm.best().unwrap().fitness, 1.0);
+}
+
+#[test]
+fn test_genetic_algorithm_stops_on_convergence() {
+    let config = GaConfig {
+        population_size: 20,
+        stall_generations: 5,
+        max_generations: 1000,
+        ..GaConfig::default()
+    };
+    let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+    let mut algorithm = GeneticAlgorithm::new(config, SpeedFitness, &mut rng);
+
+    assert_eq!(algorithm.run(&mut rng), StopReason::Converged);
+    assert!(algorithm.history().len() < 1000);
+}
+
+#[test]
+fn test_ga_options() {
+    let args: Vec<String> = [
+        "--selection",
+        "tournament",
+        "--tournament-size",
+        "5",
+        "--crossover",
+        "uniform",
+        "--mutation",
+        "0.05",
+        "--seed",
+        "9",
+    ]
+    .iter()
+    .map(|arg| arg.to_string())
+    .collect();
+
+    let options = GaOptions::parse(&args).unwrap();
+    assert_eq!(options.config.selection, Selection::Tournament { size: 5 });
+    assert_eq!(options.config.crossover, Crossover::Uniform);
+    assert_eq!(options.config.mutation_rate, 0.05);
+    assert_eq!(options.seed, 9);
+
+    assert!(GaOptions::parse(&["--mutation".to_string(), "2".to_string()]).is_err());
+    assert!(GaOptions::parse(&["--selection".to_string()]).is_err());
+}
+
+/// Founders 0 and 1. 0 has children 2 and 3, 2 has child 4, 1 has child 5.
+/// 0, 1, 3 and 5 are dead.
+fn family() -> Lineage {
+    let gene_builder = GeneBuilder::new();
+    let genome = gene_builder.build_genome(gene_builder.build_genes_from_sequences(vec!["AATTC"]));
+
+    let mut lineage = Lineage::new();
+    lineage.record_birth(0, &[], &genome, 0);
+    lineage.record_birth(1, &[], &genome, 0);
+    lineage.record_birth(2, &[0], &genome, 5);
+    lineage.record_birth(3, &[0], &genome, 8);
+    lineage.record_birth(4, &[2], &genome, 12);
+    lineage.record_birth(5, &[1], &genome, 3);
+    lineage.record_death(0, 10);
+    lineage.record_death(1, 10);
+    lineage.record_death(3, 11);
+    lineage.record_death(5, 20);
+    lineage
+}
+
+#[test]
+fn test_lineage_records_birth_and_death() {
+    let lineage = family();
+
+    let record = lineage.get(3).unwrap();
+    assert_eq!(record.parents, vec![0]);
+    assert_eq!(record.birth, 8);
+    assert_eq!(record.death, Some(11));
+    assert_eq!(lineage.get(4).unwrap().death, None);
+}
+
+#[test]
+fn test_genome_hash() {
+    let gene_builder = GeneBuilder::new();
+    let a = Genome {
+        genes: gene_builder.build_genes_from_sequences(vec!["AATTC", "CCCGT"]),
+    };
+    let b = Genome {
+        genes: gene_builder.build_genes_from_sequences(vec!["AATTCCCCGT"]),
+    };
+    let c = Genome {
+        genes: gene_builder.build_genes_from_sequences(vec!["AATTC", "CCCGA"]),
+    };
+
+    assert_eq!(genome_hash(&a), genome_hash(&b));
+    assert_ne!(genome_hash(&a), genome_hash(&c));
+}
+
+#[test]
+fn test_most_recent_common_ancestor() {
+    let lineage = family();
+
+    assert_eq!(lineage.most_recent_common_ancestor(4, 3), Some(0));
+    assert_eq!(lineage.most_recent_common_ancestor(4, 2), Some(2));
+    assert_eq!(lineage.most_recent_common_ancestor(4, 5), None);
+    assert_eq!(lineage.ancestors(4), [4, 2, 0].into_iter().collect());
+}
+
+#[test]
+fn test_surviving_lineages() {
+    let mut lineage = family();
+    assert_eq!(lineage.surviving_lineages(), vec![0]);
+    assert_eq!(lineage.living_common_ancestor(), Some(2));
+
+    lineage.record_death(2, 30);
+    lineage.record_death(4, 30);
+    assert!(lineage.surviving_lineages().is_empty());
+    assert_eq!(lineage.living_common_ancestor(), None);
+}
+
+#[test]
+fn test_newick_export() {
+    let lineage = family();
+
+    assert_eq!(lineage.to_newick(false), "(((4:7)2:5,3:8)0:0,(5:3)1:0);");
+    assert_eq!(lineage.to_newick(true), "((4:7)2:5)0:0;");
+}
+
+fn genome_of(sequences: Vec<&str>) -> Genome {
+    let gene_builder = GeneBuilder::new();
+    gene_builder.build_genome(gene_builder.build_genes_from_sequences(sequences))
+}
+
+#[test]
+fn test_genetic_distance() {
+    let a = genome_of(vec!["AATTC", "ACAAC"]);
+    let b = genome_of(vec!["AATTC", "ACGAC"]);
+    let shifted = genome_of(vec!["TAATTC", "ACAAC"]);
+
+    assert_eq!(DistanceMetric::Hamming.distance(&a, &a), 0);
+    assert_eq!(DistanceMetric::Hamming.distance(&a, &b), 1);
+    assert_eq!(DistanceMetric::Edit.distance(&a, &b), 1);
+    // Every nucleotide after the insertion is out of place for Hamming.
+    assert!(DistanceMetric::Hamming.distance(&a, &shifted) > 1);
+    assert_eq!(DistanceMetric::Edit.distance(&a, &shifted), 1);
+}
+
+#[test]
+fn test_within_matches_distance() {
+    let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+    let gene_map = create_gene_map();
+    let genomes: Vec<Genome> = (0..12)
+        .map(|_| create_random_genome(&mut rng, &gene_map))
+        .chain([
+            genome_of(vec!["AATTC", "ACAAC"]),
+            genome_of(vec!["TAATTC", "ACAAC"]),
+            genome_of(vec![]),
+        ])
+        .collect();
+
+    for metric in [DistanceMetric::Hamming, DistanceMetric::Edit] {
+        for a in genomes.iter() {
+            for b in genomes.iter() {
+                let distance = metric.distance(a, b);
+                for threshold in [0, 1, 6, distance, distance.saturating_sub(1)] {
+                    assert_eq!(metric.within(a, b, threshold), distance <= threshold);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_species_census() {
+    let near = genome_of(vec!["AAAAAAAAAA"]);
+    let close = genome_of(vec!["AAAAAAAAAT"]);
+    let far = genome_of(vec!["TTTTTTTTTT"]);
+    let mut tracker = SpeciesTracker::new(SpeciationConfig {
+        threshold: 2,
+        ..Default::default()
+    });
+
+    tracker.census(
+        &[
+            CensusEntry {
+                id: 0,
+                genome: &near,
+                parents: &[],
+            },
+            CensusEntry {
+                id: 1,
+                genome: &far,
+                parents: &[],
+            },
+            CensusEntry {
+                id: 2,
+                genome: &close,
+                parents: &[0],
+            },
+        ],
+        0,
+    );
+
+    assert_eq!(tracker.species_of(0), tracker.species_of(2));
+    assert_ne!(tracker.species_of(0), tracker.species_of(1));
+    assert_eq!(tracker.living().count(), 2);
+    assert_eq!(
+        tracker.events(),
+        &[
+            SpeciesEvent::Appeared {
+                species: 0,
+                tick: 0
+            },
+            SpeciesEvent::Appeared {
+                species: 1,
+                tick: 0
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_species_split_and_extinction() {
+    let ancestor = genome_of(vec!["AAAAAAAAAA"]);
+    let mutant = genome_of(vec!["AAAAAGGGGG"]);
+    let mut tracker = SpeciesTracker::new(SpeciationConfig {
+        threshold: 2,
+        ..Default::default()
+    });
+
+    tracker.census(
+        &[CensusEntry {
+            id: 0,
+            genome: &ancestor,
+            parents: &[],
+        }],
+        0,
+    );
+    tracker.census(
+        &[
+            CensusEntry {
+                id: 0,
+                genome: &ancestor,
+                parents: &[],
+            },
+            CensusEntry {
+                id: 1,
+                genome: &mutant,
+                parents: &[0],
+            },
+        ],
+        1,
+    );
+    tracker.census(
+        &[CensusEntry {
+            id: 1,
+            genome: &mutant,
+            parents: &[0],
+        }],
+        2,
+    );
+
+    assert_eq!(
+        tracker.events(),
+        &[
+            SpeciesEvent::Appeared {
+                species: 0,
+                tick: 0
+            },
+            SpeciesEvent::Split {
+                species: 1,
+                from: 0,
+                tick: 1
+            },
+            SpeciesEvent::Extinct {
+                species: 0,
+                tick: 2
+            },
+        ]
+    );
+    assert_eq!(tracker.species_of(1), Some(1));
+    assert_eq!(tracker.get(1).unwrap().split_from, Some(0));
+    assert_eq!(
+        tracker
+            .living()
+            .map(|species| species.id)
+            .collect::<Vec<_>>(),
+        vec![1]
+    );
+}
+
+#[test]
+fn test_breeding_at_population_cap_keeps_food() {
+    let mut evolution = Evolution::new();
+    let genome = evolution.organism_entities[0]
+        .get_component::<Organism>()
+        .unwrap()
+        .genome
+        .clone();
+    while evolution.organism_entities.len() < MAX_POPULATION - 1 {
+        evolution.spawn(genome.clone(), Vector2::new(10.0, 10.0), &[]);
+    }
+    for entity in evolution.organism_entities.iter_mut() {
+        entity.get_component_mut::<Organism>().unwrap().food = 100;
+        entity.get_component_mut::<Breeding>().unwrap().chance = 1.0;
+    }
+    let paid = |evolution: &Evolution| {
+        evolution
+            .organism_entities
+            .iter()
+            .filter(|entity| entity.get_component::<Organism>().unwrap().food < 100)
+            .count()
+    };
+
+    evolution.breed();
+    assert_eq!(evolution.organism_entities.len(), MAX_POPULATION);
+    assert_eq!(paid(&evolution), 2);
+
+    evolution.breed();
+    assert_eq!(evolution.organism_entities.len(), MAX_POPULATION);
+    assert_eq!(paid(&evolution), 2);
+}
+
+#[test]
+fn test_breeding_barrier() {
+    let a = genome_of(vec!["AAAAAAAAAA"]);
+    let b = genome_of(vec!["TTTTTTTTTT"]);
+    let mut tracker = SpeciesTracker::new(SpeciationConfig::default());
+
+    assert!(tracker.can_breed(&a, &b));
+    tracker.config.breeding_barrier = true;
+    assert!(!tracker.can_breed(&a, &b));
+    assert!(tracker.can_breed(&a, &a));
+}
+
+#[test]
+fn test_summary() {
+    let summary = Summary::of([4, 1, 7].into_iter());
+    assert_eq!(summary.min, 1);
+    assert_eq!(summary.mean, 4.0);
+    assert_eq!(summary.max, 7);
+
+    assert_eq!(Summary::of(std::iter::empty()), Summary::default());
+}
+
+#[test]
+fn test_shannon_entropy() {
+    assert_eq!(shannon_entropy(["AATTC"; 4].into_iter()), 0.0);
+    assert_eq!(shannon_entropy(["AATTC", "ATTTC"].into_iter()), 1.0);
+    assert_eq!(
+        shannon_entropy(["AATTC", "ATTTC", "ATCTC", "ATGTC"].into_iter()),
+        2.0
+    );
+}
+
+/// Output that can still be read after it was handed to the stats system.
+#[derive(Clone, Default)]
+struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl SharedBuffer {
+    fn lines(&self) -> Vec<String> {
+        String::from_utf8(self.0.borrow().clone())
+            .unwrap()
+            .lines()
+            .map(|line| line.to_string())
+            .collect()
+    }
+}
+
+fn organism_entity(id: u32, sequences: Vec<&str>, age: u32) -> Entity {
+    let gene_builder = GeneBuilder::new();
+    let mut organism = gene_builder.build_organism(
+        gene_builder.build_genome(gene_builder.build_genes_from_sequences(sequences)),
+    );
+    organism.age = age;
+    EntityBuilder::new(id).with_component(organism).build()
+}
+
+#[test]
+fn test_stats_record() {
+    let gene_map = create_gene_map();
+    let mut stats = StatsSystem::new(&gene_map);
+    let organisms = vec![
+        organism_entity(0, vec!["AATTC", "CCCGT"], 2),
+        organism_entity(1, vec!["AATTC", "CCGGT"], 4),
+    ];
+
+    stats.count_birth();
+    stats.count_death();
+    stats.count_death();
+    stats.record(1, &organisms, &gene_map).unwrap();
+    stats.record(2, &organisms, &gene_map).unwrap();
+
+    let record = &stats.records()[0];
+    assert_eq!(record.population, 2);
+    assert_eq!(record.age.mean, 3.0);
+    assert_eq!((record.births, record.deaths), (1, 2));
+    // Only the size differs, the other kinds of traits have one allele each.
+    assert_eq!(record.diversity.iter().filter(|d| **d == 1.0).count(), 1);
+    assert_eq!(record.diversity.iter().sum::<f64>(), 1.0);
+    assert_eq!(
+        record
+            .trait_frequencies
+            .iter()
+            .filter(|f| **f == 1.0)
+            .count(),
+        1
+    );
+    assert_eq!(
+        record
+            .trait_frequencies
+            .iter()
+            .filter(|f| **f == 0.5)
+            .count(),
+        2
+    );
+    assert_eq!(
+        (stats.records()[1].births, stats.records()[1].deaths),
+        (0, 0)
+    );
+}
+
+#[test]
+fn test_stats_keeps_only_the_charted_ticks() {
+    let gene_map = create_gene_map();
+    let mut stats = StatsSystem::new(&gene_map);
+    let organisms = vec![organism_entity(0, vec!["AATTC"], 3)];
+
+    for tick in 0..CHART_TICKS as u64 + 5 {
+        stats.record(tick, &organisms, &gene_map).unwrap();
+    }
+
+    assert_eq!(stats.records().len(), CHART_TICKS);
+    assert_eq!(stats.records()[0].tick, 5);
+    assert_eq!(stats.records().back().unwrap().tick, CHART_TICKS as u64 + 4);
+}
+
+#[test]
+fn test_stats_outputs() {
+    let gene_map = create_gene_map();
+    let mut stats = StatsSystem::new(&gene_map);
+    let organisms = vec![organism_entity(0, vec!["AATTC"], 3)];
+    stats.record(0, &organisms, &gene_map).unwrap();
+
+    let (csv, jsonl) = (SharedBuffer::default(), SharedBuffer::default());
+    stats.add_output(StatsFormat::Csv, csv.clone()).unwrap();
+    stats
+        .add_output(StatsFormat::JsonLines, jsonl.clone())
+        .unwrap();
+    stats.record(1, &organisms, &gene_map).unwrap();
+
+    let csv = csv.lines();
+    assert_eq!(csv.len(), 3);
+    assert!(csv[0].starts_with("tick,population,age_min,age_mean,age_max,"));
+    assert!(csv[0].contains(",Red,"));
+    assert!(csv[0]
+        .ends_with(",diversity_Speed,diversity_TemperatureResistance,diversity_WaterResistance"));
+    assert!(csv[2].starts_with("1,1,3,3.000,3,"));
+    assert_eq!(csv[0].split(',').count(), csv[2].split(',').count());
+
+    let jsonl = jsonl.lines();
+    assert_eq!(jsonl.len(), 2);
+    assert!(jsonl[1]
+        .starts_with("{\"tick\":1,\"population\":1,\"age\":{\"min\":3,\"mean\":3.000,\"max\":3}"));
+    assert!(jsonl[1].contains("\"Red\":1.0000"));
+    assert!(jsonl[1].contains("\"diversity\":{\"BreedingRate\":0.0000,"));
+    assert!(jsonl[1].ends_with("}"));
+}
+
+#[test]
+fn test_evolution_options() {
+    let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+
+    let options = EvolutionOptions::parse(&args(&["--csv", "run.csv"])).unwrap();
+    assert_eq!(options.csv.as_deref(), Some("run.csv"));
+    assert_eq!(options.jsonl, None);
+    assert!(EvolutionOptions::parse(&args(&["--jsonl"])).is_err());
+    assert!(EvolutionOptions::parse(&args(&["--stats", "run.csv"])).is_err());
+    assert_eq!(
+        EvolutionOptions::parse(&args(&["--genomes", "population.fasta"]))
+            .unwrap()
+            .genomes
+            .as_deref(),
+        Some("population.fasta")
+    );
+    assert!(EvolutionOptions::parse(&args(&["--load", "a", "--genomes", "b"])).is_err());
+}
+
+fn run_for(evolution: &mut Evolution, updates: u32) {
+    use crate::engine::Game;
+    for _ in 0..updates {
+        evolution.update(0.1);
+    }
+}
+
+#[test]
+fn test_snapshot_round_trip() {
+    let mut evolution = Evolution::new();
+    run_for(&mut evolution, 200);
+
+    let text = snapshot::write(&evolution);
+    let loaded = snapshot::read(&text).unwrap();
+
+    assert!(text.starts_with("evolution-snapshot 3\n"));
+    assert_eq!(snapshot::write(&loaded), text);
+    assert_eq!(loaded.ticks, evolution.ticks);
+    assert_eq!(
+        loaded.organism_entities.len(),
+        evolution.organism_entities.len()
+    );
+    assert_eq!(
+        loaded.lineage.to_newick(false),
+        evolution.lineage.to_newick(false)
+    );
+    assert_eq!(
+        loaded.species.living().count(),
+        evolution.species.living().count()
+    );
+}
+
+#[test]
+fn test_snapshot_resumes_deterministically() {
+    let mut evolution = Evolution::new();
+    run_for(&mut evolution, 100);
+    let mut loaded = snapshot::read(&snapshot::write(&evolution)).unwrap();
+
+    run_for(&mut evolution, 300);
+    run_for(&mut loaded, 300);
+
+    assert_eq!(snapshot::write(&loaded), snapshot::write(&evolution));
+}
+
+#[test]
+fn test_snapshot_errors() {
+    let rng = "rng 0000000000000000000000000000000000000000000000000000000000000000 0 0";
+
+    assert!(snapshot::read("").is_err());
+    assert!(snapshot::read("evolution-snapshot 99").is_err());
+    assert!(snapshot::read("evolution-snapshot 1\ntick 0 0").is_err());
+    assert_eq!(
+        snapshot::read(&format!("evolution-snapshot 1\n{}\ntick 5 x", rng)).err(),
+        Some("Line 3: invalid value \"x\"".to_string())
+    );
+    assert_eq!(
+        snapshot::read(&format!(
+            "evolution-snapshot 1\n{}\nfood 1 2 3\norganism 1 - 10 0 50 50 0 0 0 0 0 0 0 AAXTC",
+            rng
+        ))
+        .err(),
+        Some("Line 4: invalid gene \"AAXTC\"".to_string())
+    );
+    assert!(snapshot::read(&format!("evolution-snapshot 1\n{}\nweather sunny", rng)).is_err());
+}
+
+#[test]
+fn test_snapshot_keeps_genes_the_gene_map_does_not_know() {
+    let text = "evolution-snapshot 2\n\
+        rng 0000000000000000000000000000000000000000000000000000000000000000 0 0\n\
+        organism 3 1,2 12 4 30 80 1.5 2.5 0 0 50 0.25 0.5 ATGAATTCTAA,ATGGGGGGTAA,CCATGCACACTAA\n";
+
+    let evolution = snapshot::read(text).unwrap();
+    let organism = evolution.organism_entities[0]
+        .get_component::<Organism>()
+        .unwrap();
+
+    assert_eq!(
+        sequence_str(&organism.genome),
+        "ATGAATTCTAAATGGGGGGTAACCATGCACACTAA"
+    );
+    assert_eq!(organism.parents, vec![1, 2]);
+    assert_eq!(
+        (organism.food, organism.health, organism.lifespan),
+        (12, 30, 80)
+    );
+    assert_eq!(
+        evolution.organism_entities[0]
+            .get_component::<Body>()
+            .unwrap()
+            .position,
+        Vector2::new(1.5, 2.5)
+    );
+    assert_eq!(
+        evolution.organism_entities[0]
+            .get_component::<Movement>()
+            .unwrap()
+            .speed,
+        50.0
+    );
+    assert_eq!(
+        evolution.organism_entities[0]
+            .get_component::<Appearance>()
+            .unwrap()
+            .color,
+        Color::RED
+    );
+}
+
+#[test]
+fn test_snapshot_reads_version_1_genomes() {
+    let text = "evolution-snapshot 1\n\
+        rng 0000000000000000000000000000000000000000000000000000000000000000 0 0\n\
+        organism 3 - 12 4 30 80 1.5 2.5 0 0 50 0.25 0.5 AATTC,CCCGT\n\
+        species 0 0 - - 3 AATTC\n";
+
+    let evolution = snapshot::read(text).unwrap();
+    let organism = evolution.organism_entities[0]
+        .get_component::<Organism>()
+        .unwrap();
+
+    assert_eq!(sequence_str(&organism.genome), "ATGAATTCTAAATGCCCGTTAA");
+    assert_eq!(
+        evolution.organism_entities[0]
+            .get_component::<Appearance>()
+            .unwrap()
+            .color,
+        Color::RED
+    );
+    assert_eq!(
+        sequence_str(&evolution.species.get(0).unwrap().representative),
+        "ATGAATTCTAA"
+    );
+    assert!(snapshot::write(&evolution).starts_with("evolution-snapshot 3\n"));
+}
+
+#[test]
+fn test_snapshot_upgrades_old_speed_codes() {
+    let text = "evolution-snapshot 2\n\
+        rng 0000000000000000000000000000000000000000000000000000000000000000 0 0\n\
+        organism 3 - 30 4 30 80 1.5 2.5 0 0 80 0.25 0.5 CCATGTTTTTTAA,ATGTTAATTAA\n";
+
+    let mut evolution = snapshot::read(text).unwrap();
+    let genome = evolution.organism_entities[0]
+        .get_component::<Organism>()
+        .unwrap()
+        .genome
+        .clone();
+    assert_eq!(sequence_str(&genome), "CCATGTCTCTTAAATGTCAATTAA");
+
+    evolution.spawn(genome, Vector2::new(10.0, 10.0), &[3]);
+    let child = evolution.organism_entities.last().unwrap();
+    assert_eq!(child.get_component::<Movement>().unwrap().speed, 80.0);
+}
+
+#[test]
+fn test_fasta_write() {
+    let gene_builder = GeneBuilder::new();
+    let mut organism = gene_builder.build_organism(
+        gene_builder.build_genome(gene_builder.build_genes_from_sequences(vec!["AATTC", "CCCGT"])),
+    );
+    organism.id = 7;
+    let long = genome_of(vec!["ACGTA"; 13]);
+
+    let mut fasta = Vec::new();
+    write_fasta(
+        &mut fasta,
+        &[
+            FastaRecord::from_organism(&organism, &create_gene_map()),
+            FastaRecord {
+                id: "long".to_string(),
+                description: String::new(),
+                genome: long,
+            },
+        ],
+    )
+    .unwrap();
+
+    let fasta = String::from_utf8(fasta).unwrap();
+    let lines: Vec<&str> = fasta.lines().collect();
+    assert_eq!(lines[0], ">organism_7 Color(Red) Size(Small)");
+    assert_eq!(lines[1], "ATGAATTCTAAATGCCCGTTAA");
+    assert_eq!(lines[2], ">long");
+    assert_eq!(lines[3].len(), FASTA_LINE_WIDTH);
+    assert_eq!(lines[4].len(), FASTA_LINE_WIDTH);
+    assert_eq!(lines[5], "AATGACGTATAAATGACGTATAA");
+}
+
+#[test]
+fn test_fasta_round_trip() {
+    let genome = genome_of(vec!["AATTC", "CCCGT", "GGCGA"]);
+    let mut fasta = Vec::new();
+    write_fasta(
+        &mut fasta,
+        &[FastaRecord {
+            id: "a".to_string(),
+            description: "first genome".to_string(),
+            genome: genome.clone(),
+        }],
+    )
+    .unwrap();
+
+    let records = read_fasta(&String::from_utf8(fasta).unwrap()).unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].id, "a");
+    assert_eq!(records[0].description, "first genome");
+    assert_eq!(records[0].genome.genes.len(), 3);
+    assert_eq!(sequence_str(&records[0].genome), sequence_str(&genome));
+}
+
+#[test]
+fn test_fasta_read() {
+    let records = read_fasta(">one\naattc\nCCCGT\n\n>two tool output\r\nGGGGA\r\n").unwrap();
+
+    assert_eq!(records.len(), 2);
+    assert_eq!(sequence_str(&records[0].genome), "AATTCCCCGT");
+    assert_eq!(records[1].id, "two");
+    assert_eq!(records[1].description, "tool output");
+}
+
+#[test]
+fn test_fasta_read_non_coding_nucleotides() {
+    let records = read_fasta(">one\nCCATGAATTCTAAGATGCCCGTTAAG\n>two\nAATT").unwrap();
+    let genome = &records[0].genome;
+
+    // Genes end after their stop codon, the last nucleotide is a gene of its own.
+    assert_eq!(genome.genes.len(), 3);
+    assert_eq!(sequence_str(genome), "CCATGAATTCTAAGATGCCCGTTAAG");
+    let appearance = Appearance::from_traits(&get_genome_traits(genome, &create_gene_map()));
+    assert_eq!(appearance.color, Color::RED);
+    assert_eq!(appearance.radius, 6.0);
+    assert_eq!(sequence_str(&records[1].genome), "AATT");
+}
+
+#[test]
+fn test_fasta_errors() {
+    assert_eq!(
+        read_fasta(">one\nAATTC\nAANTC").err(),
+        Some("Line 3, column 3: invalid nucleotide 'N'".to_string())
+    );
+    assert_eq!(
+        read_fasta("AATTC\n>one").err(),
+        Some("Line 1: sequence before the first header".to_string())
+    );
+    assert_eq!(
+        read_fasta(">one\nAATTC\n>two\n>three\nAATTC").err(),
+        Some("Line 3: two has no sequence".to_string())
+    );
+    assert!(read_fasta(">\nAATTC").is_err());
+    assert!(read_fasta(">one\nAAUTC").is_err());
+}
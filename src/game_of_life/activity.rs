@@ -0,0 +1,200 @@
+use raylib::color::Color;
+
+use crate::engine::Entity;
+
+use super::{history::CellChange, Cell, CellState};
+
+/// Ages at or above this are drawn with the oldest colour.
+const MAX_AGE_SHOWN: f32 = 100.0;
+
+/// How many generations a dead cell stays visible in the trails mode.
+const TRAIL_LENGTH: u32 = 20;
+
+const YOUNG_COLOR: Color = Color::new(255, 255, 160, 255);
+const OLD_COLOR: Color = Color::new(120, 0, 160, 255);
+const TRAIL_COLOR: Color = Color::new(0, 140, 255, 255);
+
+/// What the colour of a cell shows.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RenderMode {
+    /// The colour of the state in the palette of the rule.
+    State,
+    /// How many generations alive cells have been alive, from light yellow when just
+    /// born to purple.
+    Age,
+    /// How often each cell changed state, relative to the busiest cell of the board.
+    /// Oscillators and the paths of spaceships stand out.
+    Heat,
+    /// Alive cells as usual, and cells that died recently fading out behind them.
+    Trails,
+}
+
+impl RenderMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            RenderMode::State => "State",
+            RenderMode::Age => "Age",
+            RenderMode::Heat => "Heat map",
+            RenderMode::Trails => "Trails",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            RenderMode::State => RenderMode::Age,
+            RenderMode::Age => RenderMode::Heat,
+            RenderMode::Heat => RenderMode::Trails,
+            RenderMode::Trails => RenderMode::State,
+        }
+    }
+}
+
+/// Keeps the age, change count and time since death of every `Cell` up to date.
+pub struct ActivitySystem;
+
+impl ActivitySystem {
+    /// Updates the cells after a generation was computed from the `before` states.
+    pub fn record(&self, entities: &mut [Entity], before: &[CellState]) {
+        for (entity, before) in entities.iter_mut().zip(before) {
+            let Some(cell) = entity.get_component_mut::<Cell>() else {
+                continue;
+            };
+
+            if cell.state != *before {
+                cell.changes += 1;
+            }
+
+            if cell.state == CellState::ALIVE {
+                cell.age = if *before == CellState::ALIVE {
+                    cell.age + 1
+                } else {
+                    1
+                };
+                cell.since_alive = Some(0);
+            } else {
+                cell.age = 0;
+                cell.since_alive = if *before == CellState::ALIVE {
+                    Some(1)
+                } else {
+                    cell.since_alive.map(|since| since.saturating_add(1))
+                };
+            }
+        }
+    }
+
+    /// Updates the cells the editor changed. Drawn cells count as just born and erased
+    /// ones start a trail, but edits are not counted as changes for the heat map.
+    pub fn edited(&self, entities: &mut [Entity], changes: &[CellChange]) {
+        for change in changes {
+            let Some(cell) = entities[change.index].get_component_mut::<Cell>() else {
+                continue;
+            };
+
+            if change.after == CellState::ALIVE {
+                cell.age = 1;
+                cell.since_alive = Some(0);
+            } else {
+                cell.age = 0;
+                if change.before == CellState::ALIVE {
+                    cell.since_alive = Some(1);
+                }
+            }
+        }
+    }
+
+    /// Forgets the history of every cell, for example after the board was replaced or
+    /// moved through the undo history, where ages and counts no longer fit the board.
+    pub fn reset(&self, entities: &mut [Entity]) {
+        for entity in entities.iter_mut() {
+            if let Some(cell) = entity.get_component_mut::<Cell>() {
+                *cell = Cell::new(cell.state);
+            }
+        }
+    }
+}
+
+/// Picks the colour of every cell for a render mode.
+pub struct CellColoring<'a> {
+    mode: RenderMode,
+    palette: &'a [Color],
+    /// Most changes of any cell, what the heat map is scaled to.
+    max_changes: u32,
+}
+
+impl<'a> CellColoring<'a> {
+    pub fn new(mode: RenderMode, palette: &'a [Color], entities: &[Entity]) -> Self {
+        let max_changes = match mode {
+            RenderMode::Heat => entities
+                .iter()
+                .filter_map(|entity| entity.get_component::<Cell>())
+                .map(|cell| cell.changes)
+                .max()
+                .unwrap_or(0),
+            _ => 0,
+        };
+
+        Self {
+            mode,
+            palette,
+            max_changes,
+        }
+    }
+
+    /// Colour of a cell, `None` if nothing should be drawn over the background.
+    pub fn color(&self, cell: &Cell) -> Option<Color> {
+        match self.mode {
+            RenderMode::State | RenderMode::Trails if cell.state != CellState::DEAD => {
+                Some(self.state_color(cell.state))
+            }
+            RenderMode::State => None,
+            RenderMode::Age if cell.state == CellState::ALIVE => {
+                let t = (cell.age as f32 / MAX_AGE_SHOWN).min(1.0);
+                Some(lerp_color(YOUNG_COLOR, OLD_COLOR, t))
+            }
+            RenderMode::Age if cell.state != CellState::DEAD => Some(self.state_color(cell.state)),
+            RenderMode::Age => None,
+            RenderMode::Heat if cell.changes > 0 => {
+                Some(heat_color(cell.changes as f32 / self.max_changes as f32))
+            }
+            RenderMode::Heat => None,
+            RenderMode::Trails => match cell.since_alive {
+                Some(since) if since <= TRAIL_LENGTH => {
+                    let t = since as f32 / (TRAIL_LENGTH + 1) as f32;
+                    Some(lerp_color(TRAIL_COLOR, Color::BLACK, t))
+                }
+                _ => None,
+            },
+        }
+    }
+
+    fn state_color(&self, state: CellState) -> Color {
+        self.palette
+            .get(state.0 as usize)
+            .copied()
+            .unwrap_or(Color::MAGENTA)
+    }
+}
+
+/// Black through red and yellow to white as `t` goes from 0 to 1.
+fn heat_color(t: f32) -> Color {
+    let stops = [
+        Color::new(40, 0, 0, 255),
+        Color::new(230, 40, 0, 255),
+        Color::new(255, 220, 0, 255),
+        Color::WHITE,
+    ];
+    let scaled = t.clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+    let index = (scaled as usize).min(stops.len() - 2);
+
+    lerp_color(stops[index], stops[index + 1], scaled - index as f32)
+}
+
+fn lerp_color(from: Color, to: Color, t: f32) -> Color {
+    let lerp = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+    Color::new(
+        lerp(from.r, to.r),
+        lerp(from.g, to.g),
+        lerp(from.b, to.b),
+        lerp(from.a, to.a),
+    )
+}
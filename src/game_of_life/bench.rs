@@ -0,0 +1,55 @@
+use std::time::Instant;
+
+use crate::engine::System;
+
+use super::{
+    bitgrid::{BitGrid, BitLifeSystem},
+    create_grid,
+    rules::Rule,
+    LifeSystem,
+};
+
+const WIDTH: u32 = 1024;
+const HEIGHT: u32 = 1024;
+
+/// Compares generations per second of the entity-based and bit-packed Life backends
+/// on a random 1024x1024 board. Meant to be run with `cargo run --release -- life-bench`.
+pub fn run() {
+    println!("Game of Life benchmark, {}x{} board", WIDTH, HEIGHT);
+
+    let mut entities = create_grid(WIDTH, HEIGHT);
+    let mut grid = BitGrid::from_entities(&entities, WIDTH, HEIGHT);
+
+    let mut life_system = LifeSystem::new(Rule::life());
+    let entity_rate = generations_per_second(5, || life_system.update(&mut entities, 0.0));
+    report("entity LifeSystem", entity_rate, entity_rate);
+
+    let mut bit_life_system = BitLifeSystem::new(WIDTH, HEIGHT);
+    let bit_system_rate = generations_per_second(5, || bit_life_system.update(&mut entities, 0.0));
+    report(
+        "BitLifeSystem (with entity sync)",
+        bit_system_rate,
+        entity_rate,
+    );
+
+    let bit_grid_rate = generations_per_second(500, || grid.step());
+    report("BitGrid::step", bit_grid_rate, entity_rate);
+}
+
+fn generations_per_second(generations: u32, mut step: impl FnMut()) -> f64 {
+    let start = Instant::now();
+    for _ in 0..generations {
+        step();
+    }
+
+    generations as f64 / start.elapsed().as_secs_f64()
+}
+
+fn report(name: &str, rate: f64, baseline_rate: f64) {
+    println!(
+        "{:<34} {:>12.2} gen/s ({:.1}x)",
+        name,
+        rate,
+        rate / baseline_rate
+    );
+}
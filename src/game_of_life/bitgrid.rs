@@ -0,0 +1,195 @@
+use crate::engine::{Entity, System};
+
+use super::{Cell, CellState};
+
+const WORD_BITS: usize = 64;
+
+/// Bit-packed Game of Life board.
+/// Every row is stored as a run of `u64` words with one bit per cell, so a generation
+/// is computed 64 cells at a time with bitwise adder logic instead of per-cell lookups.
+/// Cells outside the board are treated as dead, same as in `LifeSystem`.
+pub struct BitGrid {
+    width: u32,
+    height: u32,
+    words_per_row: usize,
+    cells: Vec<u64>,
+    /// Scratch buffer for the next generation, swapped with `cells` after each step.
+    next: Vec<u64>,
+}
+
+impl BitGrid {
+    pub fn new(width: u32, height: u32) -> Self {
+        let words_per_row = (width as usize).div_ceil(WORD_BITS);
+        let len = words_per_row * height as usize;
+
+        Self {
+            width,
+            height,
+            words_per_row,
+            cells: vec![0; len],
+            next: vec![0; len],
+        }
+    }
+
+    pub fn from_entities(entities: &[Entity], width: u32, height: u32) -> Self {
+        let mut grid = Self::new(width, height);
+        grid.load_entities(entities);
+        grid
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> bool {
+        let (word, bit) = self.locate(x, y);
+        self.cells[word] & (1 << bit) != 0
+    }
+
+    pub fn set(&mut self, x: u32, y: u32, alive: bool) {
+        let (word, bit) = self.locate(x, y);
+        if alive {
+            self.cells[word] |= 1 << bit;
+        } else {
+            self.cells[word] &= !(1 << bit);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.iter_mut().for_each(|word| *word = 0);
+    }
+
+    /// Copies cell states from entities laid out as `x + y * width`.
+    pub fn load_entities(&mut self, entities: &[Entity]) {
+        self.clear();
+
+        for (i, entity) in entities.iter().enumerate() {
+            if let Some(cell) = entity.get_component::<Cell>() {
+                if cell.state == CellState::ALIVE {
+                    self.set(i as u32 % self.width, i as u32 / self.width, true);
+                }
+            }
+        }
+    }
+
+    /// Writes cell states back to entities laid out as `x + y * width`.
+    pub fn store_entities(&self, entities: &mut [Entity]) {
+        for (i, entity) in entities.iter_mut().enumerate() {
+            if let Some(cell) = entity.get_component_mut::<Cell>() {
+                cell.state = if self.get(i as u32 % self.width, i as u32 / self.width) {
+                    CellState::ALIVE
+                } else {
+                    CellState::DEAD
+                };
+            }
+        }
+    }
+
+    /// Cell states laid out as `x + y * width`.
+    pub fn cell_states(&self) -> Vec<CellState> {
+        (0..self.width * self.height)
+            .map(|i| {
+                if self.get(i % self.width, i / self.width) {
+                    CellState::ALIVE
+                } else {
+                    CellState::DEAD
+                }
+            })
+            .collect()
+    }
+
+    /// Advances the board by one generation using B3/S23.
+    pub fn step(&mut self) {
+        let height = self.height as usize;
+        let last_word_mask = self.last_word_mask();
+
+        for y in 0..height {
+            for w in 0..self.words_per_row {
+                let (up_left, up, up_right) = if y > 0 {
+                    self.row_neighbours(y - 1, w)
+                } else {
+                    (0, 0, 0)
+                };
+                let (left, center, right) = self.row_neighbours(y, w);
+                let (down_left, down, down_right) = if y + 1 < height {
+                    self.row_neighbours(y + 1, w)
+                } else {
+                    (0, 0, 0)
+                };
+
+                // Three bit planes of a per-cell neighbour counter. The third plane saturates,
+                // which is all B3/S23 needs: it only cares about exactly two or three neighbours.
+                let mut sum = [0u64; 3];
+                for neighbours in [
+                    up_left, up, up_right, left, right, down_left, down, down_right,
+                ] {
+                    add_to_counter(&mut sum, neighbours);
+                }
+
+                let mut alive = !sum[2] & sum[1] & (sum[0] | center);
+                if w + 1 == self.words_per_row {
+                    alive &= last_word_mask;
+                }
+
+                self.next[y * self.words_per_row + w] = alive;
+            }
+        }
+
+        std::mem::swap(&mut self.cells, &mut self.next);
+    }
+
+    fn locate(&self, x: u32, y: u32) -> (usize, usize) {
+        let x = x as usize;
+        (
+            y as usize * self.words_per_row + x / WORD_BITS,
+            x % WORD_BITS,
+        )
+    }
+
+    /// Returns the left-shifted, unshifted and right-shifted word `w` of row `y`,
+    /// so that bit `i` of each holds the state of cell `x - 1`, `x` and `x + 1`.
+    fn row_neighbours(&self, y: usize, w: usize) -> (u64, u64, u64) {
+        let row = &self.cells[y * self.words_per_row..(y + 1) * self.words_per_row];
+        let word = row[w];
+        let previous = if w > 0 { row[w - 1] } else { 0 };
+        let following = if w + 1 < row.len() { row[w + 1] } else { 0 };
+
+        let left = (word << 1) | (previous >> (WORD_BITS - 1));
+        let right = (word >> 1) | (following << (WORD_BITS - 1));
+
+        (left, word, right)
+    }
+
+    fn last_word_mask(&self) -> u64 {
+        match self.width as usize % WORD_BITS {
+            0 => u64::MAX,
+            bits => (1 << bits) - 1,
+        }
+    }
+}
+
+fn add_to_counter(sum: &mut [u64; 3], bits: u64) {
+    let carry = sum[0] & bits;
+    sum[0] ^= bits;
+    let carry_over = sum[1] & carry;
+    sum[1] ^= carry;
+    sum[2] |= carry_over;
+}
+
+/// Drop-in replacement for `LifeSystem` backed by a `BitGrid`.
+/// The board is packed from the cell entities, stepped and unpacked again on every update.
+pub struct BitLifeSystem {
+    grid: BitGrid,
+}
+
+impl BitLifeSystem {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            grid: BitGrid::new(width, height),
+        }
+    }
+}
+
+impl System for BitLifeSystem {
+    fn update(&mut self, entities: &mut [Entity], _delta_time: f32) {
+        self.grid.load_entities(entities);
+        self.grid.step();
+        self.grid.store_entities(entities);
+    }
+}
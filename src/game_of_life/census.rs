@@ -0,0 +1,319 @@
+use std::collections::{HashMap, HashSet};
+
+use super::{editor::Pattern, CellState};
+
+/// Alive cells closer than this many cells (in both directions) belong to the same
+/// object. Two rather than one keeps objects like the pulsar, whose quarters never
+/// touch, in one piece.
+const OBJECT_REACH: i32 = 2;
+
+/// Generations an object is run in isolation to find its period.
+const MAX_PERIOD: u32 = 64;
+
+/// What kind of object a connected group of cells is.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ObjectKind {
+    StillLife,
+    Oscillator {
+        period: u32,
+    },
+    /// Comes back to the same shape `dx` cells further along one axis and `dy` along
+    /// the other after `period` generations. `dx` is the larger of the two, so the
+    /// direction of travel does not matter.
+    Spaceship {
+        period: u32,
+        dx: u32,
+        dy: u32,
+    },
+    /// Did not repeat within `MAX_PERIOD` generations, for example because it dies out,
+    /// grows, or consists of several objects that interact when left alone.
+    Unknown,
+}
+
+/// A classified object. The shape is the same for every phase, orientation and
+/// position of the object, so it can be used to tell objects apart.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Object {
+    pub kind: ObjectKind,
+    pub shape: Vec<(i32, i32)>,
+}
+
+impl Object {
+    /// Runs a group of cells on an empty, unbounded board to find what it is.
+    pub fn classify(cells: &[(i32, i32)]) -> Self {
+        let start = normalise(cells);
+        let mut phases = vec![start.clone()];
+        let mut current = cells.to_vec();
+
+        for period in 1..=MAX_PERIOD {
+            current = step(&current);
+            let phase = normalise(&current);
+
+            if phase == start {
+                let (dx, dy) = offset(&current, cells);
+                let kind = match (period, dx, dy) {
+                    (1, 0, 0) => ObjectKind::StillLife,
+                    (_, 0, 0) => ObjectKind::Oscillator { period },
+                    _ => ObjectKind::Spaceship {
+                        period,
+                        dx: dx.unsigned_abs().max(dy.unsigned_abs()),
+                        dy: dx.unsigned_abs().min(dy.unsigned_abs()),
+                    },
+                };
+                let shape = phases.iter().map(|phase| canonical(phase)).min();
+
+                return Self {
+                    kind,
+                    shape: shape.unwrap_or_default(),
+                };
+            }
+            phases.push(phase);
+        }
+
+        Self {
+            kind: ObjectKind::Unknown,
+            shape: canonical(cells),
+        }
+    }
+
+    /// A name for the object: the one from `known_objects` if it is there, otherwise
+    /// its kind and size.
+    pub fn name(&self, known: &[(&'static str, Object)]) -> String {
+        if let Some((name, _)) = known.iter().find(|(_, object)| *object == *self) {
+            return name.to_string();
+        }
+
+        let cells = self.shape.len();
+        match self.kind {
+            ObjectKind::StillLife => format!("{}-cell still life", cells),
+            ObjectKind::Oscillator { period } => format!("{}-cell p{} oscillator", cells, period),
+            ObjectKind::Spaceship { period, .. } => {
+                format!("{}-cell p{} spaceship", cells, period)
+            }
+            ObjectKind::Unknown => format!("{}-cell unknown object", cells),
+        }
+    }
+}
+
+/// Common objects by name, already classified.
+pub fn known_objects() -> Vec<(&'static str, Object)> {
+    let patterns = [
+        ("Block", &["##", "##"][..]),
+        ("Beehive", &[".##.", "#..#", ".##."][..]),
+        ("Loaf", &[".##.", "#..#", ".#.#", "..#."][..]),
+        ("Boat", &["##.", "#.#", ".#."][..]),
+        ("Ship", &["##.", "#.#", ".##"][..]),
+        ("Tub", &[".#.", "#.#", ".#."][..]),
+        ("Pond", &[".##.", "#..#", "#..#", ".##."][..]),
+        ("Long boat", &["##..", "#.#.", ".#.#", "..#."][..]),
+        ("Blinker", &["###"][..]),
+        ("Toad", &[".###", "###."][..]),
+        ("Beacon", &["##..", "##..", "..##", "..##"][..]),
+        (
+            "Traffic light",
+            &[
+                "..###..", ".......", "#.....#", "#.....#", "#.....#", ".......", "..###..",
+            ][..],
+        ),
+        (
+            "Pulsar",
+            &[
+                "..###...###..",
+                ".............",
+                "#....#.#....#",
+                "#....#.#....#",
+                "#....#.#....#",
+                "..###...###..",
+                ".............",
+                "..###...###..",
+                "#....#.#....#",
+                "#....#.#....#",
+                "#....#.#....#",
+                ".............",
+                "..###...###..",
+            ][..],
+        ),
+        (
+            "Pentadecathlon",
+            &["..#....#..", "##.####.##", "..#....#.."][..],
+        ),
+        ("Glider", &[".#.", "..#", "###"][..]),
+        ("LWSS", &[".#..#", "#....", "#...#", "####."][..]),
+        (
+            "MWSS",
+            &["...#..", ".#...#", "#.....", "#....#", "#####."][..],
+        ),
+        (
+            "HWSS",
+            &["...##..", ".#....#", "#......", "#.....#", "######."][..],
+        ),
+    ];
+
+    patterns
+        .into_iter()
+        .map(|(name, rows)| {
+            let cells: Vec<(i32, i32)> = Pattern::from_rows(rows)
+                .alive_cells()
+                .map(|(x, y)| (x as i32, y as i32))
+                .collect();
+            (name, Object::classify(&cells))
+        })
+        .collect()
+}
+
+/// Splits the alive cells of a board laid out as `x + y * width` into objects,
+/// see `OBJECT_REACH`.
+pub fn components(states: &[CellState], width: u32) -> Vec<Vec<(i32, i32)>> {
+    let mut unvisited: HashSet<(i32, i32)> = states
+        .iter()
+        .enumerate()
+        .filter(|(_, state)| **state == CellState::ALIVE)
+        .map(|(i, _)| ((i as u32 % width) as i32, (i as u32 / width) as i32))
+        .collect();
+
+    let mut starts: Vec<(i32, i32)> = unvisited.iter().copied().collect();
+    starts.sort_by_key(|&(x, y)| (y, x));
+
+    let mut components = Vec::new();
+    for start in starts {
+        if !unvisited.remove(&start) {
+            continue;
+        }
+
+        let mut component = vec![start];
+        let mut next = 0;
+        while next < component.len() {
+            let (x, y) = component[next];
+            next += 1;
+
+            for dy in -OBJECT_REACH..=OBJECT_REACH {
+                for dx in -OBJECT_REACH..=OBJECT_REACH {
+                    if unvisited.remove(&(x + dx, y + dy)) {
+                        component.push((x + dx, y + dy));
+                    }
+                }
+            }
+        }
+
+        component.sort_by_key(|&(x, y)| (y, x));
+        components.push(component);
+    }
+
+    components
+}
+
+/// How often each object appears on a board.
+#[derive(Debug, Default)]
+pub struct Census {
+    counts: HashMap<String, u32>,
+}
+
+impl Census {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Classifies every object of a board laid out as `x + y * width` and adds it
+    /// to the tally.
+    pub fn take(&mut self, states: &[CellState], width: u32, known: &[(&'static str, Object)]) {
+        for component in components(states, width) {
+            *self
+                .counts
+                .entry(Object::classify(&component).name(known))
+                .or_insert(0) += 1;
+        }
+    }
+
+    /// Adds every count of another census to this one.
+    pub fn merge(&mut self, other: &Census) {
+        for (name, count) in other.counts.iter() {
+            *self.counts.entry(name.clone()).or_insert(0) += count;
+        }
+    }
+
+    /// Objects with their counts, the most common first and by name for equal counts.
+    pub fn tally(&self) -> Vec<(&str, u32)> {
+        let mut tally: Vec<(&str, u32)> = self
+            .counts
+            .iter()
+            .map(|(name, count)| (name.as_str(), *count))
+            .collect();
+        tally.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+        tally
+    }
+}
+
+/// Advances a set of cells by one generation of B3/S23 on an unbounded board.
+fn step(cells: &[(i32, i32)]) -> Vec<(i32, i32)> {
+    let alive: HashSet<(i32, i32)> = cells.iter().copied().collect();
+    let mut neighbours: HashMap<(i32, i32), u32> = HashMap::new();
+    for &(x, y) in cells {
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                if (dx, dy) != (0, 0) {
+                    *neighbours.entry((x + dx, y + dy)).or_insert(0) += 1;
+                }
+            }
+        }
+    }
+
+    neighbours
+        .into_iter()
+        .filter(|(cell, count)| *count == 3 || (*count == 2 && alive.contains(cell)))
+        .map(|(cell, _)| cell)
+        .collect()
+}
+
+/// Top-left corner of the bounding box of some cells.
+fn corner(cells: &[(i32, i32)]) -> (i32, i32) {
+    (
+        cells.iter().map(|&(x, _)| x).min().unwrap_or(0),
+        cells.iter().map(|&(_, y)| y).min().unwrap_or(0),
+    )
+}
+
+fn offset(to: &[(i32, i32)], from: &[(i32, i32)]) -> (i32, i32) {
+    let (to_x, to_y) = corner(to);
+    let (from_x, from_y) = corner(from);
+    (to_x - from_x, to_y - from_y)
+}
+
+/// Moves cells so their bounding box starts at `(0, 0)` and sorts them.
+fn normalise(cells: &[(i32, i32)]) -> Vec<(i32, i32)> {
+    let (min_x, min_y) = corner(cells);
+    let mut normalised: Vec<(i32, i32)> =
+        cells.iter().map(|&(x, y)| (x - min_x, y - min_y)).collect();
+    normalised.sort_by_key(|&(x, y)| (y, x));
+    normalised
+}
+
+/// A rotation or reflection of a cell around the origin.
+type Transform = fn((i32, i32)) -> (i32, i32);
+
+/// The smallest of the normalised cells over every rotation and reflection, so all
+/// orientations of a shape give the same result.
+fn canonical(cells: &[(i32, i32)]) -> Vec<(i32, i32)> {
+    let transforms: [Transform; 8] = [
+        |(x, y)| (x, y),
+        |(x, y)| (-y, x),
+        |(x, y)| (-x, -y),
+        |(x, y)| (y, -x),
+        |(x, y)| (-x, y),
+        |(x, y)| (x, -y),
+        |(x, y)| (y, x),
+        |(x, y)| (-y, -x),
+    ];
+
+    transforms
+        .iter()
+        .map(|transform| {
+            normalise(
+                &cells
+                    .iter()
+                    .map(|&cell| transform(cell))
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .min()
+        .unwrap_or_default()
+}
@@ -0,0 +1,530 @@
+use raylib::{
+    color::Color,
+    consts::{KeyboardKey, MouseButton},
+    math::{Rectangle, Vector2},
+    prelude::{RaylibDraw, RaylibDrawHandle},
+    RaylibHandle,
+};
+
+use crate::engine::{camera::Camera, Entity, Event, EventQueue};
+
+use super::{Cell, CellState, Grid, Layout, SetCellsEventData, ToggleCellEventData};
+
+/// A rectangular block of cells, used for the clipboard and the stamp library.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pattern {
+    pub width: u32,
+    pub height: u32,
+    cells: Vec<CellState>,
+}
+
+impl Pattern {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            cells: vec![CellState::DEAD; (width * height) as usize],
+        }
+    }
+
+    /// Builds a pattern from rows where `#` is an alive cell and anything else is dead.
+    pub fn from_rows(rows: &[&str]) -> Self {
+        let width = rows.iter().map(|row| row.len()).max().unwrap_or(0) as u32;
+        let mut pattern = Self::new(width, rows.len() as u32);
+
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                if c == '#' {
+                    pattern.set(x as u32, y as u32, CellState::ALIVE);
+                }
+            }
+        }
+
+        pattern
+    }
+
+    /// Copies the cells inside `area` of a board laid out as `x + y * board_width`.
+    pub fn from_board(entities: &[Entity], board_width: u32, area: CellRect) -> Self {
+        let mut pattern = Self::new(area.width, area.height);
+
+        for y in 0..area.height {
+            for x in 0..area.width {
+                let index = (area.x + x + (area.y + y) * board_width) as usize;
+                if let Some(cell) = entities[index].get_component::<Cell>() {
+                    pattern.set(x, y, cell.state);
+                }
+            }
+        }
+
+        pattern
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> CellState {
+        self.cells[(x + y * self.width) as usize]
+    }
+
+    pub fn set(&mut self, x: u32, y: u32, state: CellState) {
+        self.cells[(x + y * self.width) as usize] = state;
+    }
+
+    /// Positions of every cell that is not dead.
+    pub fn alive_cells(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+        (0..self.height)
+            .flat_map(move |y| (0..self.width).map(move |x| (x, y)))
+            .filter(|&(x, y)| self.get(x, y) != CellState::DEAD)
+    }
+
+    pub fn rotate_clockwise(&self) -> Self {
+        let mut rotated = Self::new(self.height, self.width);
+
+        for y in 0..rotated.height {
+            for x in 0..rotated.width {
+                rotated.set(x, y, self.get(y, self.height - 1 - x));
+            }
+        }
+
+        rotated
+    }
+
+    pub fn flip_horizontal(&self) -> Self {
+        let mut flipped = Self::new(self.width, self.height);
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                flipped.set(x, y, self.get(self.width - 1 - x, y));
+            }
+        }
+
+        flipped
+    }
+
+    pub fn flip_vertical(&self) -> Self {
+        let mut flipped = Self::new(self.width, self.height);
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                flipped.set(x, y, self.get(x, self.height - 1 - y));
+            }
+        }
+
+        flipped
+    }
+}
+
+/// Patterns that can be placed at the cursor with the stamp key.
+pub fn stamps() -> Vec<(&'static str, Pattern)> {
+    vec![
+        ("Glider", Pattern::from_rows(&[".#.", "..#", "###"])),
+        (
+            "LWSS",
+            Pattern::from_rows(&[".#..#", "#....", "#...#", "####."]),
+        ),
+        (
+            "Gosper glider gun",
+            Pattern::from_rows(&[
+                "........................#...........",
+                "......................#.#...........",
+                "............##......##............##",
+                "...........#...#....##............##",
+                "##........#.....#...##..............",
+                "##........#...#.##....#.#...........",
+                "..........#.....#.......#...........",
+                "...........#...#....................",
+                "............##......................",
+            ]),
+        ),
+    ]
+}
+
+/// A rectangle of cells on the board.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CellRect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl CellRect {
+    /// The smallest rectangle containing both corner cells.
+    pub fn from_corners(a: (u32, u32), b: (u32, u32)) -> Self {
+        Self {
+            x: a.0.min(b.0),
+            y: a.1.min(b.1),
+            width: a.0.abs_diff(b.0) + 1,
+            height: a.1.abs_diff(b.1) + 1,
+        }
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+        (self.y..self.y + self.height)
+            .flat_map(move |y| (self.x..self.x + self.width).map(move |x| (x, y)))
+    }
+}
+
+/// Returns the cells on the straight line between two cells, both ends included.
+pub fn line_cells(from: (u32, u32), to: (u32, u32)) -> Vec<(u32, u32)> {
+    let (mut x, mut y) = (from.0 as i64, from.1 as i64);
+    let (end_x, end_y) = (to.0 as i64, to.1 as i64);
+    let dx = (end_x - x).abs();
+    let dy = -(end_y - y).abs();
+    let step_x = if x < end_x { 1 } else { -1 };
+    let step_y = if y < end_y { 1 } else { -1 };
+    let mut error = dx + dy;
+    let mut cells = Vec::new();
+
+    loop {
+        cells.push((x as u32, y as u32));
+        if x == end_x && y == end_y {
+            break;
+        }
+
+        let doubled_error = 2 * error;
+        if doubled_error >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if doubled_error <= dx {
+            error += dx;
+            y += step_y;
+        }
+    }
+
+    cells
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Tool {
+    Toggle,
+    Brush,
+    Eraser,
+    Line,
+    Rectangle,
+    Select,
+    Paste,
+}
+
+impl Tool {
+    fn name(&self) -> &'static str {
+        match self {
+            Tool::Toggle => "Toggle",
+            Tool::Brush => "Brush",
+            Tool::Eraser => "Eraser",
+            Tool::Line => "Line",
+            Tool::Rectangle => "Rectangle",
+            Tool::Select => "Select",
+            Tool::Paste => "Paste",
+        }
+    }
+}
+
+const TOOL_KEYS: [(KeyboardKey, Tool); 7] = [
+    (KeyboardKey::KEY_ONE, Tool::Toggle),
+    (KeyboardKey::KEY_TWO, Tool::Brush),
+    (KeyboardKey::KEY_THREE, Tool::Eraser),
+    (KeyboardKey::KEY_FOUR, Tool::Line),
+    (KeyboardKey::KEY_FIVE, Tool::Rectangle),
+    (KeyboardKey::KEY_SIX, Tool::Select),
+    (KeyboardKey::KEY_SEVEN, Tool::Paste),
+];
+
+/// Turns mouse and keyboard input into board edits.
+/// Edits are pushed as `toggle_cell` and `set_cells` events for `CellToggleSystem`.
+///
+/// Keys: 1-7 pick a tool, Ctrl+C / Ctrl+X copy or cut the selection, Ctrl+V pastes,
+/// T cycles through the stamp library, R rotates and F / Shift+F flip the clipboard.
+pub struct EditorSystem {
+    tool: Tool,
+    hovered_cell: Option<(u32, u32)>,
+    drag_start: Option<(u32, u32)>,
+    last_brush_cell: Option<(u32, u32)>,
+    continuing_stroke: bool,
+    selection: Option<CellRect>,
+    clipboard: Option<Pattern>,
+    stamps: Vec<(&'static str, Pattern)>,
+    stamp_index: Option<usize>,
+    paint_state: CellState,
+    /// States of the current rule. Pasted cells in other states are left out.
+    state_count: u8,
+}
+
+impl EditorSystem {
+    pub fn new() -> Self {
+        Self {
+            tool: Tool::Toggle,
+            hovered_cell: None,
+            drag_start: None,
+            last_brush_cell: None,
+            continuing_stroke: false,
+            selection: None,
+            clipboard: None,
+            stamps: stamps(),
+            stamp_index: None,
+            paint_state: CellState::ALIVE,
+            state_count: u8::MAX,
+        }
+    }
+
+    /// Sets the state drawn by the brush, line and rectangle tools.
+    pub fn set_paint_state(&mut self, state: CellState) {
+        self.paint_state = state;
+    }
+
+    /// Sets how many states the current rule has, so patterns copied under another
+    /// rule only paste the states this one knows.
+    pub fn set_state_count(&mut self, states: u8) {
+        self.state_count = states;
+    }
+
+    pub fn handle_input(
+        &mut self,
+        rl: &RaylibHandle,
+        hovered_cell: Option<(u32, u32)>,
+        entities: &[Entity],
+        grid: &Grid,
+        cell_event_queue: &mut EventQueue,
+    ) {
+        self.hovered_cell = hovered_cell;
+        self.handle_keys(rl, entities, grid, cell_event_queue);
+
+        let pressed = rl.is_mouse_button_pressed(MouseButton::MOUSE_BUTTON_LEFT);
+        let down = rl.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT);
+        let released = rl.is_mouse_button_released(MouseButton::MOUSE_BUTTON_LEFT);
+
+        self.continuing_stroke = down && self.last_brush_cell.is_some();
+        if !down {
+            self.last_brush_cell = None;
+        }
+
+        let Some(cell) = hovered_cell else {
+            if released {
+                self.drag_start = None;
+            }
+            return;
+        };
+
+        match self.tool {
+            Tool::Toggle => {
+                if pressed {
+                    cell_event_queue.push(Event {
+                        event_type: "toggle_cell".to_string(),
+                        data: Box::new(ToggleCellEventData {
+                            x: cell.0,
+                            y: cell.1,
+                        }),
+                    });
+                }
+            }
+            Tool::Brush | Tool::Eraser => {
+                if down {
+                    let state = if self.tool == Tool::Brush {
+                        self.paint_state
+                    } else {
+                        CellState::DEAD
+                    };
+                    let from = self.last_brush_cell.unwrap_or(cell);
+                    push_set_cells(
+                        cell_event_queue,
+                        line_cells(from, cell)
+                            .into_iter()
+                            .map(|(x, y)| (x, y, state))
+                            .collect(),
+                    );
+                    self.last_brush_cell = Some(cell);
+                }
+            }
+            Tool::Line | Tool::Rectangle | Tool::Select => {
+                if pressed {
+                    self.drag_start = Some(cell);
+                }
+
+                if let Some(start) = self.drag_start {
+                    if self.tool == Tool::Select {
+                        self.selection = Some(CellRect::from_corners(start, cell));
+                    }
+
+                    if released {
+                        self.drag_start = None;
+                        let cells = match self.tool {
+                            Tool::Line => line_cells(start, cell),
+                            Tool::Rectangle => {
+                                CellRect::from_corners(start, cell).cells().collect()
+                            }
+                            _ => Vec::new(),
+                        };
+                        if !cells.is_empty() {
+                            push_set_cells(
+                                cell_event_queue,
+                                cells
+                                    .into_iter()
+                                    .map(|(x, y)| (x, y, self.paint_state))
+                                    .collect(),
+                            );
+                        }
+                    }
+                }
+            }
+            Tool::Paste => {
+                if pressed {
+                    if let Some(clipboard) = &self.clipboard {
+                        push_set_cells(
+                            cell_event_queue,
+                            place_pattern(clipboard, cell, grid, self.state_count),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn handle_keys(
+        &mut self,
+        rl: &RaylibHandle,
+        entities: &[Entity],
+        grid: &Grid,
+        cell_event_queue: &mut EventQueue,
+    ) {
+        for (key, tool) in TOOL_KEYS {
+            if rl.is_key_pressed(key) {
+                self.tool = tool;
+                self.drag_start = None;
+            }
+        }
+
+        let control = rl.is_key_down(KeyboardKey::KEY_LEFT_CONTROL)
+            || rl.is_key_down(KeyboardKey::KEY_RIGHT_CONTROL);
+        let shift = rl.is_key_down(KeyboardKey::KEY_LEFT_SHIFT)
+            || rl.is_key_down(KeyboardKey::KEY_RIGHT_SHIFT);
+
+        if control && rl.is_key_pressed(KeyboardKey::KEY_C) {
+            self.copy_selection(entities, grid);
+        }
+
+        if control && rl.is_key_pressed(KeyboardKey::KEY_X) {
+            self.copy_selection(entities, grid);
+            if let Some(selection) = self.selection {
+                push_set_cells(
+                    cell_event_queue,
+                    selection
+                        .cells()
+                        .map(|(x, y)| (x, y, CellState::DEAD))
+                        .collect(),
+                );
+            }
+        }
+
+        if control && rl.is_key_pressed(KeyboardKey::KEY_V) && self.clipboard.is_some() {
+            self.tool = Tool::Paste;
+        }
+
+        if rl.is_key_pressed(KeyboardKey::KEY_T) {
+            let index = self.stamp_index.map_or(0, |i| (i + 1) % self.stamps.len());
+            self.stamp_index = Some(index);
+            self.clipboard = Some(self.stamps[index].1.clone());
+            self.tool = Tool::Paste;
+        }
+
+        if let Some(clipboard) = &self.clipboard {
+            let transformed = if rl.is_key_pressed(KeyboardKey::KEY_R) {
+                Some(clipboard.rotate_clockwise())
+            } else if rl.is_key_pressed(KeyboardKey::KEY_F) && shift {
+                Some(clipboard.flip_vertical())
+            } else if rl.is_key_pressed(KeyboardKey::KEY_F) {
+                Some(clipboard.flip_horizontal())
+            } else {
+                None
+            };
+
+            if transformed.is_some() {
+                self.clipboard = transformed;
+            }
+        }
+    }
+
+    /// Whether the brush or eraser is in the middle of a stroke started in an earlier frame.
+    pub fn is_continuing_stroke(&self) -> bool {
+        self.continuing_stroke
+    }
+
+    fn copy_selection(&mut self, entities: &[Entity], grid: &Grid) {
+        if let Some(selection) = self.selection {
+            self.clipboard = Some(Pattern::from_board(entities, grid.width, selection));
+            self.stamp_index = None;
+        }
+    }
+
+    pub fn render(&self, rl: &mut RaylibDrawHandle, grid: &Grid, camera: &Camera) {
+        let preview_color = Color::GREEN.fade(0.4);
+
+        if let (Some(start), Some(cell)) = (self.drag_start, self.hovered_cell) {
+            let preview = match self.tool {
+                Tool::Line => line_cells(start, cell),
+                Tool::Rectangle => CellRect::from_corners(start, cell).cells().collect(),
+                _ => Vec::new(),
+            };
+            for (x, y) in preview {
+                grid.draw_cell(rl, camera, x, y, preview_color);
+            }
+        }
+
+        if let (Tool::Paste, Some(clipboard), Some(cell)) =
+            (self.tool, &self.clipboard, self.hovered_cell)
+        {
+            for (x, y, _) in place_pattern(clipboard, cell, grid, self.state_count) {
+                grid.draw_cell(rl, camera, x, y, preview_color);
+            }
+        }
+
+        if let (Some(selection), Layout::Hexagonal) = (self.selection, grid.layout) {
+            for (x, y) in selection.cells() {
+                grid.draw_cell(rl, camera, x, y, Color::YELLOW.fade(0.3));
+            }
+        } else if let Some(selection) = self.selection {
+            let cell_size = grid.cell_size as f32;
+            let top_left = camera.world_to_screen(Vector2::new(
+                selection.x as f32 * cell_size,
+                selection.y as f32 * cell_size,
+            ));
+            rl.draw_rectangle_lines_ex(
+                Rectangle::new(
+                    top_left.x,
+                    top_left.y,
+                    selection.width as f32 * cell_size * camera.zoom,
+                    selection.height as f32 * cell_size * camera.zoom,
+                ),
+                2.0,
+                Color::YELLOW,
+            );
+        }
+
+        let label = match (self.tool, self.stamp_index) {
+            (Tool::Paste, Some(index)) => format!("Tool: Stamp ({})", self.stamps[index].0),
+            (tool, _) => format!("Tool: {}", tool.name()),
+        };
+        let y = rl.get_screen_height() - 30;
+        rl.draw_text(&label, 10, y, 20, Color::WHITE);
+    }
+}
+
+/// Returns the non-dead cells of `pattern` with its top-left corner at `origin`,
+/// leaving out cells that fall off the board and cells in states a rule with `states`
+/// states does not have.
+pub fn place_pattern(
+    pattern: &Pattern,
+    origin: (u32, u32),
+    grid: &Grid,
+    states: u8,
+) -> Vec<(u32, u32, CellState)> {
+    pattern
+        .alive_cells()
+        .map(|(x, y)| (origin.0 + x, origin.1 + y, pattern.get(x, y).within(states)))
+        .filter(|&(x, y, state)| x < grid.width && y < grid.height && state != CellState::DEAD)
+        .collect()
+}
+
+fn push_set_cells(event_queue: &mut EventQueue, cells: Vec<(u32, u32, CellState)>) {
+    event_queue.push(Event {
+        event_type: "set_cells".to_string(),
+        data: Box::new(SetCellsEventData { cells }),
+    });
+}
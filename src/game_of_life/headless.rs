@@ -0,0 +1,97 @@
+use super::{rules::Rule, GameOfLife};
+
+/// Command line options of the headless runner.
+pub struct HeadlessOptions {
+    pub rule: Rule,
+    pub size: u32,
+    pub generations: u32,
+    pub scale: u32,
+    pub png: Option<String>,
+    pub gif: Option<String>,
+}
+
+impl HeadlessOptions {
+    /// Parses `--rule`, `--size`, `--generations`, `--scale`, `--png` and `--gif`,
+    /// each followed by a value.
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = Self {
+            rule: Rule::life(),
+            size: 80,
+            generations: 100,
+            scale: 4,
+            png: None,
+            gif: None,
+        };
+
+        let mut args = args.iter();
+        while let Some(flag) = args.next() {
+            let value = args
+                .next()
+                .ok_or_else(|| format!("Missing value for {}", flag))?;
+            let number = || {
+                value
+                    .parse::<u32>()
+                    .map_err(|_| format!("Invalid number \"{}\" for {}", value, flag))
+            };
+
+            match flag.as_str() {
+                "--rule" => {
+                    options.rule = Rule::parse(value).or_else(|_| Rule::turmite(value))?;
+                }
+                "--size" => options.size = number()?,
+                "--generations" => options.generations = number()?,
+                "--scale" => options.scale = number()?,
+                "--png" => options.png = Some(value.clone()),
+                "--gif" => options.gif = Some(value.clone()),
+                _ => return Err(format!("Unknown option {}", flag)),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+/// Runs a random board without opening a window. With `--gif` every generation is
+/// recorded, with `--png` a picture of the last generation is saved.
+pub fn run(args: Vec<String>) {
+    let options = match HeadlessOptions::parse(&args) {
+        Ok(options) => options,
+        Err(error) => {
+            println!("{}", error);
+            println!(
+                "Usage: life-export [--rule B3/S23] [--size 80] [--generations 100] [--scale 4] \
+                 [--png board.png] [--gif board.gif]"
+            );
+            return;
+        }
+    };
+
+    let rule_name = Rule::presets()
+        .into_iter()
+        .find(|(_, rule)| *rule == options.rule)
+        .map_or("Custom", |(name, _)| name);
+    let mut game = GameOfLife::with_rule(options.size, options.size, rule_name, options.rule);
+
+    if let Some(path) = &options.gif {
+        if let Err(error) = game.record_gif(path, options.generations, options.scale) {
+            println!("Could not write GIF: {}", error);
+            return;
+        }
+    } else {
+        for _ in 0..options.generations {
+            game.step_generation(0.0);
+        }
+    }
+
+    if let Some(path) = &options.png {
+        if let Err(error) = game.save_png(path, options.scale) {
+            println!("Could not write PNG: {}", error);
+            return;
+        }
+    }
+
+    println!(
+        "Ran {} generations of {} on a {}x{} board",
+        options.generations, rule_name, options.size, options.size
+    );
+}
@@ -0,0 +1,333 @@
+use std::collections::VecDeque;
+
+use crate::engine::{Entity, EventQueue};
+
+use super::{
+    turmite::{create_ant, Ant},
+    Cell, CellState,
+};
+
+/// A single cell that changed, stored as its index and the state before and after.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CellChange {
+    pub index: usize,
+    pub before: CellState,
+    pub after: CellState,
+}
+
+/// An ant that changed, stored as its entity id and the ant before and after.
+/// An ant that was added has no `before`, one that was removed has no `after`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AntChange {
+    pub id: u32,
+    pub before: Option<Ant>,
+    pub after: Option<Ant>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChangeKind {
+    /// Cells changed by the editor.
+    Edit,
+    /// Cells changed by stepping the simulation one generation.
+    Generation,
+}
+
+/// What happened while processing history events.
+pub struct HistoryUpdate {
+    /// Cells were changed by undo, redo or stepping through recorded generations.
+    pub board_changed: bool,
+    /// A `step_forward` event found nothing to replay, so a new generation has to be computed.
+    pub needs_generation: bool,
+}
+
+struct HistoryEntry {
+    kind: ChangeKind,
+    changes: Vec<CellChange>,
+    ant_changes: Vec<AntChange>,
+}
+
+/// Undo/redo history of the board, kept as deltas rather than full snapshots.
+/// Edits and generations share one timeline, so undoing always goes back in the order
+/// things happened. Only the most recent `capacity` entries are kept.
+///
+/// Handles `undo`, `redo`, `step_back` and `step_forward` events.
+pub struct HistorySystem {
+    undo_stack: VecDeque<HistoryEntry>,
+    redo_stack: Vec<HistoryEntry>,
+    capacity: usize,
+    /// Generations from the start of the run to the current position in the timeline.
+    generation: u64,
+    /// States of the current rule. Entries recorded under another rule can hold states
+    /// it does not have, which are restored as dead.
+    state_count: u8,
+}
+
+impl HistorySystem {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            undo_stack: VecDeque::new(),
+            redo_stack: Vec::new(),
+            capacity,
+            generation: 0,
+            state_count: u8::MAX,
+        }
+    }
+
+    /// Sets how many states the current rule has.
+    pub fn set_state_count(&mut self, states: u8) {
+        self.state_count = states;
+    }
+
+    /// Records a batch of changes that were already applied to the board.
+    /// When `merge` is set, an edit is added to the previous edit entry instead of
+    /// starting a new one, so a whole brush stroke is undone at once. Generations are
+    /// recorded even when no cell changed, so stepping back counts them too.
+    pub fn record(&mut self, kind: ChangeKind, changes: Vec<CellChange>, merge: bool) {
+        self.push(kind, changes, Vec::new(), merge);
+    }
+
+    /// Records a generation that was already applied, together with the ants it moved.
+    pub fn record_generation(&mut self, changes: Vec<CellChange>, ant_changes: Vec<AntChange>) {
+        self.push(ChangeKind::Generation, changes, ant_changes, false);
+    }
+
+    /// Records an edit of cells and ants that was already applied, for example ants
+    /// that were placed or removed.
+    pub fn record_edit(&mut self, changes: Vec<CellChange>, ant_changes: Vec<AntChange>) {
+        self.push(ChangeKind::Edit, changes, ant_changes, false);
+    }
+
+    fn push(
+        &mut self,
+        kind: ChangeKind,
+        changes: Vec<CellChange>,
+        ant_changes: Vec<AntChange>,
+        merge: bool,
+    ) {
+        if changes.is_empty() && ant_changes.is_empty() && kind == ChangeKind::Edit {
+            return;
+        }
+
+        self.redo_stack.clear();
+        if kind == ChangeKind::Generation {
+            self.generation += 1;
+        }
+
+        if merge && kind == ChangeKind::Edit {
+            if let Some(last) = self.undo_stack.back_mut() {
+                if last.kind == ChangeKind::Edit {
+                    last.changes.extend(changes);
+                    last.ant_changes.extend(ant_changes);
+                    return;
+                }
+            }
+        }
+
+        self.undo_stack.push_back(HistoryEntry {
+            kind,
+            changes,
+            ant_changes,
+        });
+        if self.undo_stack.len() > self.capacity {
+            self.undo_stack.pop_front();
+        }
+    }
+
+    /// Reverts the most recent entry. Returns its kind, or `None` if there was nothing to undo.
+    pub fn undo(&mut self, entities: &mut [Entity], ants: &mut Vec<Entity>) -> Option<ChangeKind> {
+        let entry = self.undo_stack.pop_back()?;
+        for change in entry.changes.iter().rev() {
+            set_state(
+                entities,
+                change.index,
+                change.before.within(self.state_count),
+            );
+        }
+        for ant_change in entry.ant_changes.iter().rev() {
+            set_ant(ants, ant_change.id, ant_change.before);
+        }
+        if entry.kind == ChangeKind::Generation {
+            self.generation -= 1;
+        }
+
+        let kind = entry.kind;
+        self.redo_stack.push(entry);
+        Some(kind)
+    }
+
+    /// Re-applies the most recently undone entry.
+    pub fn redo(&mut self, entities: &mut [Entity], ants: &mut Vec<Entity>) -> Option<ChangeKind> {
+        let entry = self.redo_stack.pop()?;
+        for change in entry.changes.iter() {
+            set_state(
+                entities,
+                change.index,
+                change.after.within(self.state_count),
+            );
+        }
+        for ant_change in entry.ant_changes.iter() {
+            set_ant(ants, ant_change.id, ant_change.after);
+        }
+        if entry.kind == ChangeKind::Generation {
+            self.generation += 1;
+        }
+
+        let kind = entry.kind;
+        self.undo_stack.push_back(entry);
+        Some(kind)
+    }
+
+    /// The generation the board is at, counting undone generations back.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Goes back to the previous generation, undoing any edits made since.
+    /// Returns `false` if there was nothing to undo.
+    pub fn step_back(&mut self, entities: &mut [Entity], ants: &mut Vec<Entity>) -> bool {
+        let mut changed = false;
+        while let Some(kind) = self.undo(entities, ants) {
+            changed = true;
+            if kind == ChangeKind::Generation {
+                break;
+            }
+        }
+
+        changed
+    }
+
+    /// Replays the next generation if it was undone before.
+    /// Returns `false` if a new generation has to be computed instead.
+    pub fn step_forward(&mut self, entities: &mut [Entity], ants: &mut Vec<Entity>) -> bool {
+        match self.redo_stack.last() {
+            Some(entry) if entry.kind == ChangeKind::Generation => {
+                self.redo(entities, ants);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Handles history events and reports what the caller has to do next.
+    pub fn process_events(
+        &mut self,
+        entities: &mut [Entity],
+        ants: &mut Vec<Entity>,
+        event_queue: &mut EventQueue,
+    ) -> HistoryUpdate {
+        let mut update = HistoryUpdate {
+            board_changed: false,
+            needs_generation: false,
+        };
+
+        while let Some(event) = event_queue.pop() {
+            let changed = match event.event_type.as_str() {
+                "undo" => self.undo(entities, ants).is_some(),
+                "redo" => self.redo(entities, ants).is_some(),
+                "step_back" => self.step_back(entities, ants),
+                "step_forward" => {
+                    let replayed = self.step_forward(entities, ants);
+                    update.needs_generation |= !replayed;
+                    replayed
+                }
+                _ => false,
+            };
+            update.board_changed |= changed;
+        }
+
+        update
+    }
+}
+
+pub fn cell_states(entities: &[Entity]) -> Vec<CellState> {
+    entities
+        .iter()
+        .map(|entity| {
+            entity
+                .get_component::<Cell>()
+                .map_or(CellState::DEAD, |cell| cell.state)
+        })
+        .collect()
+}
+
+/// Compares two boards taken with `cell_states`.
+pub fn diff_cell_states(before: &[CellState], after: &[CellState]) -> Vec<CellChange> {
+    before
+        .iter()
+        .zip(after)
+        .enumerate()
+        .filter(|(_, (before, after))| before != after)
+        .map(|(index, (&before, &after))| CellChange {
+            index,
+            before,
+            after,
+        })
+        .collect()
+}
+
+/// Every ant with its entity id.
+pub fn ant_states(ants: &[Entity]) -> Vec<(u32, Ant)> {
+    ants.iter()
+        .filter_map(|entity| Some((entity.id(), *entity.get_component::<Ant>()?)))
+        .collect()
+}
+
+/// Compares two sets of ants taken with `ant_states`, including ants that are only in
+/// one of them because they were added or removed.
+pub fn diff_ant_states(before: &[(u32, Ant)], after: &[(u32, Ant)]) -> Vec<AntChange> {
+    let find = |ants: &[(u32, Ant)], id: u32| {
+        ants.iter()
+            .find(|(other, _)| *other == id)
+            .map(|&(_, ant)| ant)
+    };
+
+    let changed_or_removed = before.iter().filter_map(|&(id, ant)| {
+        let after = find(after, id);
+        (after != Some(ant)).then_some(AntChange {
+            id,
+            before: Some(ant),
+            after,
+        })
+    });
+    let added = after
+        .iter()
+        .filter(|&&(id, _)| find(before, id).is_none())
+        .map(|&(id, ant)| AntChange {
+            id,
+            before: None,
+            after: Some(ant),
+        });
+
+    changed_or_removed.chain(added).collect()
+}
+
+/// Puts an ant in `state`, adding or removing its entity when it has no state on one
+/// side. Ants are kept in the order of their ids, which is the order they were placed.
+fn set_ant(ants: &mut Vec<Entity>, id: u32, state: Option<Ant>) {
+    let index = ants.iter().position(|entity| entity.id() == id);
+
+    match (index, state) {
+        (Some(index), Some(state)) => {
+            if let Some(ant) = ants[index].get_component_mut::<Ant>() {
+                *ant = state;
+            }
+        }
+        (Some(index), None) => {
+            ants.remove(index);
+        }
+        (None, Some(state)) => {
+            let index = ants
+                .iter()
+                .position(|entity| entity.id() > id)
+                .unwrap_or(ants.len());
+            ants.insert(index, create_ant(id, state.x, state.y, state.direction));
+        }
+        (None, None) => {}
+    }
+}
+
+fn set_state(entities: &mut [Entity], index: usize, state: CellState) {
+    if let Some(cell) = entities[index].get_component_mut::<Cell>() {
+        cell.state = state;
+    }
+}
@@ -0,0 +1,450 @@
+use rand::Rng;
+use raylib::{
+    color::Color,
+    math::Vector2,
+    prelude::{RaylibDraw, RaylibDrawHandle},
+    RaylibHandle, RaylibThread,
+};
+
+use crate::engine::{
+    camera::Camera, Entity, EntityBuilder, Game, GameState, System, SystemManager,
+};
+
+use super::{Grid, Layout};
+
+/// A board of continuous cell values between 0 and 1, laid out as `x + y * width`.
+pub struct Field {
+    pub width: u32,
+    pub height: u32,
+    pub values: Vec<f32>,
+}
+
+impl Field {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            values: vec![0.0; (width * height) as usize],
+        }
+    }
+
+    /// Fills the centre of the field with random values and clears the rest.
+    pub fn seed(&mut self, rng: &mut impl Rng) {
+        let (first_x, last_x) = (self.width / 4, self.width * 3 / 4);
+        let (first_y, last_y) = (self.height / 4, self.height * 3 / 4);
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let inside = (first_x..last_x).contains(&x) && (first_y..last_y).contains(&y);
+                self.values[(x + y * self.width) as usize] =
+                    if inside { rng.gen_range(0.0..1.0) } else { 0.0 };
+            }
+        }
+    }
+
+    /// Sets every cell within `radius` of a cell to `value`.
+    pub fn paint(&mut self, x: u32, y: u32, radius: u32, value: f32) {
+        let radius = radius as i32;
+        for dy in -radius..=radius {
+            for dx in -radius..=radius {
+                let (px, py) = (x as i32 + dx, y as i32 + dy);
+                if dx * dx + dy * dy <= radius * radius
+                    && (0..self.width as i32).contains(&px)
+                    && (0..self.height as i32).contains(&py)
+                {
+                    self.values[(px + py * self.width as i32) as usize] = value;
+                }
+            }
+        }
+    }
+
+    /// Sum of all cell values.
+    pub fn mass(&self) -> f32 {
+        self.values.iter().sum()
+    }
+}
+
+/// Parameters of a Lenia world.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LeniaConfig {
+    /// Kernel radius in cells.
+    pub radius: u32,
+    /// Height of each concentric ring of the kernel, from the inside out.
+    pub peaks: Vec<f32>,
+    /// Potential at which cells grow fastest.
+    pub mu: f32,
+    /// How far the potential can be from `mu` and still give growth.
+    pub sigma: f32,
+    /// Fraction of the growth applied per step.
+    pub dt: f32,
+}
+
+impl LeniaConfig {
+    /// Orbium, a glider-like creature with a single-ring kernel.
+    pub fn orbium() -> Self {
+        Self {
+            radius: 13,
+            peaks: vec![1.0],
+            mu: 0.15,
+            sigma: 0.015,
+            dt: 0.1,
+        }
+    }
+
+    /// Hydrogeminium, which uses a three-ring kernel.
+    pub fn hydrogeminium() -> Self {
+        Self {
+            radius: 18,
+            peaks: vec![0.5, 1.0, 2.0 / 3.0],
+            mu: 0.26,
+            sigma: 0.036,
+            dt: 0.1,
+        }
+    }
+
+    pub fn presets() -> Vec<(&'static str, LeniaConfig)> {
+        vec![
+            ("Orbium", LeniaConfig::orbium()),
+            ("Hydrogeminium", LeniaConfig::hydrogeminium()),
+        ]
+    }
+}
+
+/// Radial convolution kernel, stored as the offsets with a non-zero weight.
+/// The weights add up to 1, so the potential of a cell is a weighted average.
+pub struct Kernel {
+    weights: Vec<(i32, i32, f32)>,
+}
+
+impl Kernel {
+    pub fn new(radius: u32, peaks: &[f32]) -> Self {
+        let r = radius as i32;
+        let mut weights = Vec::new();
+
+        for dy in -r..=r {
+            for dx in -r..=r {
+                let distance = ((dx * dx + dy * dy) as f32).sqrt() / radius as f32;
+                let weight = kernel_shell(distance, peaks);
+                if weight > 0.0 {
+                    weights.push((dx, dy, weight));
+                }
+            }
+        }
+
+        let total: f32 = weights.iter().map(|(_, _, weight)| weight).sum();
+        for (_, _, weight) in weights.iter_mut() {
+            *weight /= total;
+        }
+
+        Self { weights }
+    }
+
+    pub fn weights(&self) -> &[(i32, i32, f32)] {
+        &self.weights
+    }
+}
+
+/// Kernel height at `distance` (1 is the kernel radius). Each ring is a smooth bump
+/// scaled by its peak.
+fn kernel_shell(distance: f32, peaks: &[f32]) -> f32 {
+    if distance >= 1.0 {
+        return 0.0;
+    }
+
+    let scaled = distance * peaks.len() as f32;
+    let ring = scaled as usize;
+    let x = scaled.fract();
+    if x <= 0.0 {
+        return 0.0;
+    }
+
+    peaks[ring] * (4.0 - 1.0 / (x * (1.0 - x))).exp()
+}
+
+/// Lenia growth function. Gives 1 at a potential of `mu` and falls off to -1 further away.
+pub fn growth(potential: f32, mu: f32, sigma: f32) -> f32 {
+    let distance = (potential - mu) / sigma;
+    2.0 * (-distance * distance / 2.0).exp() - 1.0
+}
+
+/// Steps every `Field` with the Lenia rule: convolve with the kernel, apply the growth
+/// function and clamp to 0..1. The convolution is done directly on the CPU and wraps
+/// around the edges.
+pub struct LeniaSystem {
+    config: LeniaConfig,
+    kernel: Kernel,
+    potential: Vec<f32>,
+}
+
+impl LeniaSystem {
+    pub fn new(config: LeniaConfig) -> Self {
+        Self {
+            kernel: Kernel::new(config.radius, &config.peaks),
+            config,
+            potential: Vec::new(),
+        }
+    }
+
+    pub fn step(&mut self, field: &mut Field) {
+        let width = field.width as usize;
+        let height = field.height as usize;
+
+        self.potential.clear();
+        self.potential.resize(width * height, 0.0);
+
+        // One pass over the board per kernel offset keeps the inner loop a simple
+        // walk along a row.
+        for &(dx, dy, weight) in self.kernel.weights() {
+            let shift_x = dx.rem_euclid(width as i32) as usize;
+            let shift_y = dy.rem_euclid(height as i32) as usize;
+
+            for y in 0..height {
+                let source_row = &field.values[((y + shift_y) % height) * width..][..width];
+                let potential_row = &mut self.potential[y * width..][..width];
+
+                for (x, potential) in potential_row.iter_mut().enumerate() {
+                    let source_x = if x + shift_x < width {
+                        x + shift_x
+                    } else {
+                        x + shift_x - width
+                    };
+                    *potential += weight * source_row[source_x];
+                }
+            }
+        }
+
+        for (value, &potential) in field.values.iter_mut().zip(self.potential.iter()) {
+            let change = self.config.dt * growth(potential, self.config.mu, self.config.sigma);
+            *value = (*value + change).clamp(0.0, 1.0);
+        }
+    }
+}
+
+impl System for LeniaSystem {
+    fn update(&mut self, entities: &mut [Entity], _delta_time: f32) {
+        for entity in entities.iter_mut() {
+            if let Some(field) = entity.get_component_mut::<Field>() {
+                self.step(field);
+            }
+        }
+    }
+}
+
+/// Colour of a cell value, going from black through blue and teal to yellow and white.
+pub fn gradient(value: f32) -> Color {
+    const STOPS: [(f32, (f32, f32, f32)); 5] = [
+        (0.0, (0.0, 0.0, 0.0)),
+        (0.25, (20.0, 30.0, 120.0)),
+        (0.5, (0.0, 160.0, 170.0)),
+        (0.75, (250.0, 220.0, 60.0)),
+        (1.0, (255.0, 255.0, 255.0)),
+    ];
+
+    let value = value.clamp(0.0, 1.0);
+    let upper = STOPS
+        .iter()
+        .position(|(stop, _)| value <= *stop)
+        .unwrap_or(STOPS.len() - 1)
+        .max(1);
+    let (start, from) = STOPS[upper - 1];
+    let (end, to) = STOPS[upper];
+    let t = (value - start) / (end - start);
+
+    Color::new(
+        (from.0 + (to.0 - from.0) * t) as u8,
+        (from.1 + (to.1 - from.1) * t) as u8,
+        (from.2 + (to.2 - from.2) * t) as u8,
+        255,
+    )
+}
+
+/// Draws the visible cells of a field with `gradient`, skipping cells that are near 0.
+struct FieldRenderSystem;
+
+impl FieldRenderSystem {
+    pub fn render(&self, rl: &mut RaylibDrawHandle, field: &Field, grid: &Grid, camera: &Camera) {
+        let (columns, rows) =
+            grid.visible_cells(camera, rl.get_screen_width(), rl.get_screen_height());
+        let cell_size = grid.cell_size as f32;
+        let size = Vector2::new(cell_size * camera.zoom, cell_size * camera.zoom);
+
+        for y in rows {
+            for x in columns.clone() {
+                let value = field.values[(x + y * field.width) as usize];
+                if value < 0.01 {
+                    continue;
+                }
+
+                let position = camera
+                    .world_to_screen(Vector2::new(x as f32 * cell_size, y as f32 * cell_size));
+                rl.draw_rectangle_v(position, size, gradient(value));
+            }
+        }
+    }
+}
+
+/// Continuous cellular automaton in the style of Lenia.
+///
+/// Keys: Space pauses, N switches preset, R reseeds, left mouse paints, right or middle
+/// mouse pans, the wheel zooms and Home fits the field to the screen.
+pub struct Lenia {
+    entities: Vec<Entity>,
+    system_manager: SystemManager,
+    render_system: FieldRenderSystem,
+    grid: Grid,
+    camera: Camera,
+    preset_index: usize,
+    game_state: GameState,
+}
+
+impl Lenia {
+    pub fn new(width: u32, height: u32) -> Self {
+        let mut lenia = Self {
+            entities: vec![EntityBuilder::new(0)
+                .with_component(Field::new(width, height))
+                .build()],
+            system_manager: SystemManager::new(),
+            render_system: FieldRenderSystem,
+            grid: Grid {
+                width,
+                height,
+                cell_size: 4,
+                show: false,
+                layout: Layout::Square,
+            },
+            camera: Camera::new(),
+            preset_index: 0,
+            game_state: GameState::Playing,
+        };
+        lenia.apply_preset(0);
+        lenia
+    }
+
+    pub fn fit_camera_to_field(&mut self, screen_width: i32, screen_height: i32) {
+        self.camera.fit(
+            self.grid.bounds(),
+            screen_width as f32,
+            screen_height as f32,
+        );
+    }
+
+    fn field_mut(&mut self) -> Option<&mut Field> {
+        self.entities[0].get_component_mut::<Field>()
+    }
+
+    /// Switches to a preset from `LeniaConfig::presets` and reseeds the field.
+    fn apply_preset(&mut self, index: usize) {
+        let (_, config) = LeniaConfig::presets().swap_remove(index);
+
+        self.preset_index = index;
+        self.system_manager = SystemManager::new();
+        self.system_manager.add_system(LeniaSystem::new(config));
+
+        if let Some(field) = self.field_mut() {
+            field.seed(&mut rand::thread_rng());
+        }
+    }
+}
+
+impl Game for Lenia {
+    fn handle_input(&mut self, rl: &RaylibHandle) {
+        use raylib::consts::{KeyboardKey, MouseButton};
+
+        if rl.is_key_pressed(KeyboardKey::KEY_SPACE) {
+            match self.game_state {
+                GameState::Playing => self.change_state(GameState::Paused),
+                GameState::Paused => self.change_state(GameState::Playing),
+            }
+        }
+
+        if rl.is_key_pressed(KeyboardKey::KEY_N) {
+            self.apply_preset((self.preset_index + 1) % LeniaConfig::presets().len());
+        }
+
+        if rl.is_key_pressed(KeyboardKey::KEY_R) {
+            if let Some(field) = self.field_mut() {
+                field.seed(&mut rand::thread_rng());
+            }
+        }
+
+        if rl.is_key_pressed(KeyboardKey::KEY_HOME) {
+            self.fit_camera_to_field(rl.get_screen_width(), rl.get_screen_height());
+        }
+
+        let mouse_position = rl.get_mouse_position();
+        let wheel = rl.get_mouse_wheel_move();
+        if wheel != 0.0 {
+            self.camera.zoom_at(mouse_position, 1.1f32.powf(wheel));
+        }
+
+        if rl.is_mouse_button_down(MouseButton::MOUSE_BUTTON_RIGHT)
+            || rl.is_mouse_button_down(MouseButton::MOUSE_BUTTON_MIDDLE)
+        {
+            self.camera.pan(rl.get_mouse_delta());
+        }
+
+        if rl.is_mouse_button_down(MouseButton::MOUSE_BUTTON_LEFT) {
+            let world = self.camera.screen_to_world(mouse_position);
+            if let Some((x, y)) = self.grid.cell_at(world) {
+                if let Some(field) = self.field_mut() {
+                    field.paint(x, y, 3, 1.0);
+                }
+            }
+        }
+    }
+
+    fn update(&mut self, delta_time: f32) {
+        if let GameState::Playing = self.game_state {
+            self.system_manager.update(&mut self.entities, delta_time);
+        }
+    }
+
+    fn render(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread) {
+        let mut d = rl.begin_drawing(thread);
+
+        d.clear_background(Color::BLACK);
+
+        if let Some(field) = self.entities[0].get_component::<Field>() {
+            self.render_system
+                .render(&mut d, field, &self.grid, &self.camera);
+
+            let (name, config) = &LeniaConfig::presets()[self.preset_index];
+            let lines = [
+                format!("Preset: {} (N to change, R to reseed)", name),
+                format!(
+                    "R = {}  mu = {}  sigma = {}  dt = {}",
+                    config.radius, config.mu, config.sigma, config.dt
+                ),
+                format!("Mass: {:.1}", field.mass()),
+            ];
+            for (i, line) in lines.iter().enumerate() {
+                d.draw_text(line, 10, 10 + i as i32 * 20, 18, Color::WHITE);
+            }
+        }
+
+        if let GameState::Paused = self.game_state {
+            d.draw_text("Paused", 10, d.get_screen_height() - 30, 20, Color::WHITE);
+        }
+    }
+
+    fn change_state(&mut self, new_state: GameState) {
+        self.game_state = new_state;
+    }
+}
+
+pub fn run() {
+    let (mut rl, thread) = raylib::init().size(800, 600).title("Lenia").vsync().build();
+
+    let mut lenia = Lenia::new(128, 128);
+    lenia.fit_camera_to_field(rl.get_screen_width(), rl.get_screen_height());
+
+    while !rl.window_should_close() {
+        let delta_time = rl.get_frame_time();
+
+        lenia.handle_input(&rl);
+
+        lenia.update(delta_time);
+
+        lenia.render(&mut rl, &thread);
+    }
+}
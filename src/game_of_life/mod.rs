@@ -1,33 +1,140 @@
 #[allow(dead_code)]
+use std::ops::Range;
+
 use rand::Rng;
 use raylib::{
     color::Color,
-    prelude::{RaylibDraw, RaylibDrawHandle},
+    math::{Rectangle, Vector2},
+    prelude::{RaylibDraw, RaylibDrawHandle, RaylibTexture2D},
+    texture::Texture2D,
     RaylibHandle, RaylibThread,
 };
 
 use crate::engine::{
+    camera::Camera,
+    export::{GifEncoder, Image},
+    ui::{Ui, UiInput},
     Entity, EntityBuilder, Event, EventQueue, Game, GameState, System, SystemManager,
 };
 
+use activity::{ActivitySystem, CellColoring, RenderMode};
+use bitgrid::BitLifeSystem;
+use editor::EditorSystem;
+use history::{
+    ant_states, cell_states, diff_ant_states, diff_cell_states, CellChange, ChangeKind,
+    HistorySystem,
+};
+use neighbourhood::{AliveSums, Neighbourhood};
+use rules::Rule;
+use soup::{SoupConfig, Symmetry};
+use stats::{StatsRenderSystem, StatsSystem};
+use turmite::{create_ant, AntRenderSystem, Direction, TurmiteSystem};
+
+mod activity;
+pub mod bench;
+mod bitgrid;
+mod census;
+mod editor;
+pub mod headless;
+mod history;
+pub mod lenia;
+mod neighbourhood;
+mod rules;
+pub mod soup;
+mod stats;
+#[cfg(test)]
+mod tests;
+mod turmite;
+
+/// Boards running Conway's rule with at least this many cells are stepped by the
+/// bit-packed backend.
+const BIT_PACKED_MIN_CELLS: u32 = 64 * 64;
+
+/// How many edits and generations can be undone.
+const HISTORY_CAPACITY: usize = 1000;
+
+/// Where the E key writes the statistics of the current run.
+const STATS_EXPORT_PATH: &str = "life_stats.csv";
+
+/// Pixels per cell in exported pictures.
+const EXPORT_SCALE: u32 = 4;
+
+/// How many generations Shift+P records to a GIF.
+const GIF_GENERATIONS: u32 = 100;
+
+/// Delay between GIF frames in hundredths of a second.
+const GIF_FRAME_DELAY: u16 = 10;
+
+/// Range of the speed slider in generations per second.
+const MIN_GENERATIONS_PER_SECOND: f32 = 1.0;
+const MAX_GENERATIONS_PER_SECOND: f32 = 60.0;
+
+/// A slow frame computes at most this many generations, the rest is skipped.
+const MAX_GENERATIONS_PER_FRAME: u32 = 4;
+
+/// Width of the control panel in pixels.
+const CONTROL_PANEL_WIDTH: f32 = 200.0;
+
+/// Which implementation steps the board.
+/// `Entity` runs any rule per `Cell` entity, `BitPacked` runs B3/S23 on a `BitGrid`.
+pub enum LifeBackend {
+    Entity,
+    BitPacked,
+}
+
 pub struct GameOfLife {
     cell_entities: Vec<Entity>,
+    ant_entities: Vec<Entity>,
+    next_ant_id: u32,
     grid_entity: Entity,
     render_system: RenderSystem,
+    render_mode: RenderMode,
+    activity_system: ActivitySystem,
     grid_render_system: GridRenderSystem,
     input_system: InputSystem,
+    editor_system: EditorSystem,
     cell_toggle_system: CellToggleSystem,
     grid_toggle_system: GridToggleSystem,
+    history_system: HistorySystem,
+    stats_system: StatsSystem,
+    stats_render_system: StatsRenderSystem,
+    turmite_system: TurmiteSystem,
+    ant_render_system: AntRenderSystem,
     system_manager: SystemManager,
+    rule: Rule,
+    rule_name: &'static str,
+    palette: Vec<Color>,
+    /// Settings for the next soup placed with S. The seed goes up after every soup.
+    soup: SoupConfig,
+    ui: Ui,
+    generations_per_second: f32,
+    /// Time since the last generation while playing.
+    generation_timer: f32,
+    /// Chance of a cell being alive after Randomize.
+    randomize_density: f32,
+    camera: Camera,
     width: u32,
     height: u32,
     game_state: GameState,
     toggle_cells_event_queue: EventQueue,
     toggle_grid_event_queue: EventQueue,
+    history_event_queue: EventQueue,
 }
 
 impl GameOfLife {
     pub fn new(width: u32, height: u32) -> Self {
+        Self::with_rule(width, height, "Life", Rule::life())
+    }
+
+    /// Centres the board on the screen and zooms so that all of it is visible.
+    pub fn fit_camera_to_board(&mut self, screen_width: i32, screen_height: i32) {
+        if let Some(grid) = self.grid_entity.get_component::<Grid>() {
+            self.camera
+                .fit(grid.bounds(), screen_width as f32, screen_height as f32);
+        }
+    }
+
+    pub fn with_rule(width: u32, height: u32, rule_name: &'static str, rule: Rule) -> Self {
         let cell_entities = create_grid(width, height);
         let grid_entity = EntityBuilder::new(0)
             .with_component(Grid {
@@ -35,43 +142,366 @@ impl GameOfLife {
                 height,
                 cell_size: 10,
                 show: false,
+                layout: Layout::for_rule(&rule),
             })
             .build();
 
         let game_state = GameState::Paused;
-        let mut system_manager = SystemManager::new();
+        let system_manager = create_system_manager(&rule, width, height);
         let toggle_cells_event_queue = EventQueue::new();
         let toggle_grid_event_queue = EventQueue::new();
+        let history_event_queue = EventQueue::new();
 
-        let life_system = LifeSystem;
-        let render_system = RenderSystem;
+        let render_system = RenderSystem::new();
         let grid_render_system = GridRenderSystem;
-        let input_system = InputSystem::new(10);
+        let input_system = InputSystem;
+        let mut editor_system = EditorSystem::new();
+        editor_system.set_paint_state(rule.paint_state());
+        editor_system.set_state_count(rule.states());
         let cell_toggle_system = CellToggleSystem { width };
         let grid_toggle_system = GridToggleSystem;
+        let mut history_system = HistorySystem::new(HISTORY_CAPACITY);
+        history_system.set_state_count(rule.states());
+        let stats_system = StatsSystem::new(&cell_states(&cell_entities), width);
+        let stats_render_system = StatsRenderSystem;
+        let turmite_system = TurmiteSystem::new(width, height);
+        let ant_render_system = AntRenderSystem;
 
-        system_manager.add_system(life_system);
-
-        Self {
+        let mut game = Self {
             cell_entities,
+            ant_entities: Vec::new(),
+            next_ant_id: width * height,
             grid_entity,
             render_system,
+            render_mode: RenderMode::State,
+            activity_system: ActivitySystem,
             grid_render_system,
             input_system,
+            editor_system,
             cell_toggle_system,
             grid_toggle_system,
+            history_system,
+            stats_system,
+            stats_render_system,
+            turmite_system,
+            ant_render_system,
             system_manager,
+            palette: rule.palette(),
+            rule,
+            rule_name,
+            soup: SoupConfig::default(),
+            ui: Ui::new(),
+            generations_per_second: MAX_GENERATIONS_PER_SECOND,
+            generation_timer: 0.0,
+            randomize_density: 0.5,
+            camera: Camera::new(),
             width,
             height,
             game_state,
             toggle_cells_event_queue,
             toggle_grid_event_queue,
+            history_event_queue,
+        };
+
+        if game.rule.is_turmite() {
+            game.add_ant(width / 2, height / 2);
+        }
+
+        game
+    }
+
+    /// Puts a new ant facing up on a cell.
+    pub fn add_ant(&mut self, x: u32, y: u32) {
+        self.ant_entities
+            .push(create_ant(self.next_ant_id, x, y, Direction::Up));
+        self.next_ant_id += 1;
+    }
+
+    /// Switches to another rule. Cells in states the new rule does not have are cleared
+    /// and ants are removed or placed, which can be undone like any other edit.
+    pub fn set_rule(&mut self, rule_name: &'static str, rule: Rule) {
+        let before = cell_states(&self.cell_entities);
+        let ants_before = ant_states(&self.ant_entities);
+        for entity in self.cell_entities.iter_mut() {
+            if let Some(cell) = entity.get_component_mut::<Cell>() {
+                cell.state = cell.state.within(rule.states());
+            }
+        }
+        let after = cell_states(&self.cell_entities);
+        self.stats_system
+            .restart(&after, self.history_system.generation());
+        self.activity_system.reset(&mut self.cell_entities);
+
+        self.system_manager = create_system_manager(&rule, self.width, self.height);
+        if let Some(grid) = self.grid_entity.get_component_mut::<Grid>() {
+            grid.layout = Layout::for_rule(&rule);
+        }
+        self.editor_system.set_paint_state(rule.paint_state());
+        self.editor_system.set_state_count(rule.states());
+        self.palette = rule.palette();
+        self.rule = rule;
+        self.rule_name = rule_name;
+
+        if !self.rule.is_turmite() {
+            self.ant_entities.clear();
+        } else if self.ant_entities.is_empty() {
+            self.add_ant(self.width / 2, self.height / 2);
+        }
+
+        self.history_system.record_edit(
+            diff_cell_states(&before, &after),
+            diff_ant_states(&ants_before, &ant_states(&self.ant_entities)),
+        );
+        self.history_system.set_state_count(self.rule.states());
+    }
+
+    /// Replaces the board with a random soup, which can be undone like any other edit.
+    pub fn fill_soup(&mut self, soup: &SoupConfig) {
+        let before = cell_states(&self.cell_entities);
+        for entity in self.cell_entities.iter_mut() {
+            if let Some(cell) = entity.get_component_mut::<Cell>() {
+                cell.state = CellState::DEAD;
+            }
+        }
+        for (x, y) in soup.alive_cells(self.width, self.height) {
+            if let Some(cell) =
+                self.cell_entities[(x + y * self.width) as usize].get_component_mut::<Cell>()
+            {
+                cell.state = CellState::ALIVE;
+            }
+        }
+        let after = cell_states(&self.cell_entities);
+        self.history_system
+            .record(ChangeKind::Edit, diff_cell_states(&before, &after), false);
+        self.stats_system
+            .restart(&after, self.history_system.generation());
+        self.activity_system.reset(&mut self.cell_entities);
+    }
+
+    /// Kills every cell, which can be undone like any other edit.
+    pub fn clear(&mut self) {
+        let before = cell_states(&self.cell_entities);
+        for entity in self.cell_entities.iter_mut() {
+            if let Some(cell) = entity.get_component_mut::<Cell>() {
+                cell.state = CellState::DEAD;
+            }
+        }
+        let after = cell_states(&self.cell_entities);
+        self.history_system
+            .record(ChangeKind::Edit, diff_cell_states(&before, &after), false);
+        self.stats_system
+            .restart(&after, self.history_system.generation());
+        self.activity_system.reset(&mut self.cell_entities);
+    }
+
+    /// Switches to the rule after the current one in `Rule::presets`, or the one
+    /// before it if `forward` is false.
+    fn next_rule_preset(&mut self, forward: bool) {
+        let presets = Rule::presets();
+        let current = presets
+            .iter()
+            .position(|(_, rule)| *rule == self.rule)
+            .unwrap_or(presets.len() - 1);
+        let next = if forward {
+            (current + 1) % presets.len()
+        } else {
+            (current + presets.len() - 1) % presets.len()
+        };
+        let (name, rule) = presets[next].clone();
+
+        self.set_rule(name, rule);
+    }
+
+    /// Draws the board into an image with `scale` pixels per cell, using the colours
+    /// of the current rule. Ants are drawn as red cells, and states the rule has no
+    /// colour for are magenta like on screen.
+    pub fn render_image(&self, scale: u32) -> Image {
+        let mut image = Image::new(self.width * scale, self.height * scale, self.palette[0]);
+
+        for (index, entity) in self.cell_entities.iter().enumerate() {
+            if let Some(cell) = entity.get_component::<Cell>() {
+                if cell.state != CellState::DEAD {
+                    let (x, y) = (index as u32 % self.width, index as u32 / self.width);
+                    let color = self
+                        .palette
+                        .get(cell.state.0 as usize)
+                        .copied()
+                        .unwrap_or(Color::MAGENTA);
+                    image.fill_rect(x * scale, y * scale, scale, scale, color);
+                }
+            }
+        }
+
+        for entity in self.ant_entities.iter() {
+            if let Some(ant) = entity.get_component::<turmite::Ant>() {
+                image.fill_rect(ant.x * scale, ant.y * scale, scale, scale, Color::RED);
+            }
+        }
+
+        image
+    }
+
+    pub fn save_png(&self, path: &str, scale: u32) -> std::io::Result<()> {
+        self.render_image(scale).save_png(path)
+    }
+
+    /// Writes the current board and the next `generations` generations as an animated
+    /// GIF. The simulation is stepped while recording.
+    pub fn record_gif(&mut self, path: &str, generations: u32, scale: u32) -> std::io::Result<()> {
+        let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        let mut encoder = GifEncoder::new(
+            file,
+            self.width * scale,
+            self.height * scale,
+            GIF_FRAME_DELAY,
+        )?;
+
+        encoder.add_frame(&self.render_image(scale))?;
+        for _ in 0..generations {
+            self.step_generation(0.0);
+            encoder.add_frame(&self.render_image(scale))?;
+        }
+
+        encoder.finish()?;
+        Ok(())
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.stats_system
+            .latest()
+            .map_or(0, |latest| latest.generation)
+    }
+
+    /// Advances the simulation by one generation and records it in the history.
+    fn step_generation(&mut self, delta_time: f32) {
+        let before = cell_states(&self.cell_entities);
+        let ants_before = ant_states(&self.ant_entities);
+        self.system_manager
+            .update(&mut self.cell_entities, delta_time);
+        if let Rule::Turmite { turns } = &self.rule {
+            self.turmite_system
+                .update(&mut self.cell_entities, &mut self.ant_entities, turns);
+        }
+        let after = cell_states(&self.cell_entities);
+        self.activity_system
+            .record(&mut self.cell_entities, &before);
+
+        self.history_system.record_generation(
+            diff_cell_states(&before, &after),
+            diff_ant_states(&ants_before, &ant_states(&self.ant_entities)),
+        );
+        self.stats_system.record(&after);
+    }
+
+    /// Writes the statistics of the run so far as CSV.
+    pub fn export_stats(&self, path: &str) -> std::io::Result<()> {
+        let file = std::fs::File::create(path)?;
+        self.stats_system.write_csv(std::io::BufWriter::new(file))
+    }
+
+    /// Declares the control panel in the top-right corner and acts on what was clicked.
+    fn handle_control_panel(&mut self, rl: &RaylibHandle) {
+        self.ui.begin(UiInput::from_raylib(rl));
+        let x = rl.get_screen_width() as f32 - CONTROL_PANEL_WIDTH - 5.0;
+        self.ui.begin_panel(x, 5.0, CONTROL_PANEL_WIDTH);
+
+        let (state, play) = match self.game_state {
+            GameState::Playing => ("Running", "Pause"),
+            GameState::Paused => ("Paused", "Play"),
+        };
+        self.ui.label(state);
+        match self.ui.button_row(&[play, "Step"]) {
+            Some(0) => match self.game_state {
+                GameState::Playing => self.change_state(GameState::Paused),
+                GameState::Paused => self.change_state(GameState::Playing),
+            },
+            Some(_) => {
+                self.change_state(GameState::Paused);
+                self.history_event_queue.push(Event {
+                    event_type: "step_forward".to_string(),
+                    data: Box::new(()),
+                });
+            }
+            None => {}
+        }
+
+        let speed = format!("Speed: {:.0} gen/s", self.generations_per_second);
+        if self.ui.slider(
+            &speed,
+            &mut self.generations_per_second,
+            MIN_GENERATIONS_PER_SECOND..=MAX_GENERATIONS_PER_SECOND,
+        ) {
+            self.generations_per_second = self.generations_per_second.round();
+        }
+
+        if self.ui.button("Clear") {
+            self.clear();
+        }
+        let density = format!("Density: {:.0}%", self.randomize_density * 100.0);
+        self.ui
+            .slider(&density, &mut self.randomize_density, 0.0..=1.0);
+        if self.ui.button("Randomize") {
+            self.fill_soup(&SoupConfig {
+                density: self.randomize_density as f64,
+                region: None,
+                symmetry: Symmetry::C1,
+                seed: rand::thread_rng().gen(),
+            });
+        }
+
+        if self
+            .ui
+            .button(&format!("View: {} (M)", self.render_mode.name()))
+        {
+            self.render_mode = self.render_mode.next();
+        }
+
+        self.ui.label(&format!("Rule: {}", self.rule_name));
+        match self.ui.button_row(&["<", ">"]) {
+            Some(0) => self.next_rule_preset(false),
+            Some(_) => self.next_rule_preset(true),
+            None => {}
+        }
+
+        self.ui.end_panel();
+    }
+
+    /// Ctrl+Z / Ctrl+Y undo and redo, and while paused the left and right arrows
+    /// step one generation back or forward.
+    fn handle_history_input(&mut self, rl: &RaylibHandle) {
+        let control = rl.is_key_down(raylib::consts::KeyboardKey::KEY_LEFT_CONTROL)
+            || rl.is_key_down(raylib::consts::KeyboardKey::KEY_RIGHT_CONTROL);
+
+        let mut events = Vec::new();
+        if control && rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_Z) {
+            events.push("undo");
+        }
+        if control && rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_Y) {
+            events.push("redo");
+        }
+
+        if let GameState::Paused = self.game_state {
+            if rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_LEFT) {
+                events.push("step_back");
+            }
+            if rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_RIGHT) {
+                events.push("step_forward");
+            }
+        }
+
+        for event_type in events {
+            self.history_event_queue.push(Event {
+                event_type: event_type.to_string(),
+                data: Box::new(()),
+            });
         }
     }
 }
 
 impl Game for GameOfLife {
     fn handle_input(&mut self, rl: &RaylibHandle) {
+        self.handle_control_panel(rl);
+
         if rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_SPACE) {
             match self.game_state {
                 GameState::Playing => self.change_state(GameState::Paused),
@@ -79,46 +509,190 @@ impl Game for GameOfLife {
             }
         }
 
-        self.input_system.handle_input(
-            rl,
-            self.width,
-            self.height,
-            &mut self.toggle_cells_event_queue,
-            &mut self.toggle_grid_event_queue,
-        );
+        if rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_N) {
+            self.next_rule_preset(true);
+        }
+
+        // S places the next soup, Shift+S switches to the next symmetry.
+        if rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_S) {
+            let shift = rl.is_key_down(raylib::consts::KeyboardKey::KEY_LEFT_SHIFT)
+                || rl.is_key_down(raylib::consts::KeyboardKey::KEY_RIGHT_SHIFT);
+            if shift {
+                let current = Symmetry::ALL
+                    .iter()
+                    .position(|symmetry| *symmetry == self.soup.symmetry)
+                    .unwrap_or(0);
+                self.soup.symmetry = Symmetry::ALL[(current + 1) % Symmetry::ALL.len()];
+                println!("Soup symmetry: {}", self.soup.symmetry.name());
+            } else {
+                let soup = self.soup.clone();
+                self.fill_soup(&soup);
+                println!("Soup seed {} ({})", soup.seed, soup.symmetry.name());
+                self.soup.seed += 1;
+            }
+        }
+
+        if rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_M) {
+            self.render_mode = self.render_mode.next();
+        }
+
+        if rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_HOME) {
+            self.fit_camera_to_board(rl.get_screen_width(), rl.get_screen_height());
+        }
+
+        self.handle_history_input(rl);
+
+        if rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_E) {
+            match self.export_stats(STATS_EXPORT_PATH) {
+                Ok(()) => println!("Statistics exported to {}", STATS_EXPORT_PATH),
+                Err(error) => println!("Could not export statistics: {}", error),
+            }
+        }
+
+        // P saves a picture of the board, Shift+P records the next generations as a GIF.
+        if rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_P) {
+            let shift = rl.is_key_down(raylib::consts::KeyboardKey::KEY_LEFT_SHIFT)
+                || rl.is_key_down(raylib::consts::KeyboardKey::KEY_RIGHT_SHIFT);
+            let (path, result) = if shift {
+                let path = format!("life_gen{}.gif", self.generation());
+                let result = self.record_gif(&path, GIF_GENERATIONS, EXPORT_SCALE);
+                (path, result)
+            } else {
+                let path = format!("life_gen{}.png", self.generation());
+                let result = self.save_png(&path, EXPORT_SCALE);
+                (path, result)
+            };
+
+            match result {
+                Ok(()) => println!("Exported {}", path),
+                Err(error) => println!("Could not export {}: {}", path, error),
+            }
+        }
+
+        let hovered_cell = match self.grid_entity.get_component::<Grid>() {
+            Some(grid) => self.input_system.handle_input(
+                rl,
+                grid,
+                &mut self.camera,
+                &mut self.toggle_grid_event_queue,
+                self.ui.wants_mouse(),
+            ),
+            None => None,
+        };
+
+        if self.rule.is_turmite() && rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_A) {
+            let shift = rl.is_key_down(raylib::consts::KeyboardKey::KEY_LEFT_SHIFT)
+                || rl.is_key_down(raylib::consts::KeyboardKey::KEY_RIGHT_SHIFT);
+            let before = ant_states(&self.ant_entities);
+            match hovered_cell {
+                _ if shift => self.ant_entities.clear(),
+                Some((x, y)) => self.add_ant(x, y),
+                None => {}
+            }
+            self.history_system.record_edit(
+                Vec::new(),
+                diff_ant_states(&before, &ant_states(&self.ant_entities)),
+            );
+        }
+
+        if let Some(grid) = self.grid_entity.get_component::<Grid>() {
+            self.editor_system.handle_input(
+                rl,
+                hovered_cell,
+                &self.cell_entities,
+                grid,
+                &mut self.toggle_cells_event_queue,
+            );
+        }
     }
 
     fn update(&mut self, delta_time: f32) {
-        self.cell_toggle_system
-            .process_events(&mut self.cell_entities, &mut self.toggle_cells_event_queue);
+        let edits = self.cell_toggle_system.process_events(
+            &mut self.cell_entities,
+            &mut self.toggle_cells_event_queue,
+            &self.rule,
+        );
+        let edited = !edits.is_empty();
+        self.activity_system.edited(&mut self.cell_entities, &edits);
+        self.history_system.record(
+            ChangeKind::Edit,
+            edits,
+            self.editor_system.is_continuing_stroke(),
+        );
 
         self.grid_toggle_system
             .process_events(&mut self.grid_entity, &mut self.toggle_grid_event_queue);
 
+        let history_update = self.history_system.process_events(
+            &mut self.cell_entities,
+            &mut self.ant_entities,
+            &mut self.history_event_queue,
+        );
+
+        if history_update.board_changed {
+            self.activity_system.reset(&mut self.cell_entities);
+        }
+        if edited || history_update.board_changed {
+            self.stats_system.restart(
+                &cell_states(&self.cell_entities),
+                self.history_system.generation(),
+            );
+        }
+
         match self.game_state {
             GameState::Playing => {
-                self.system_manager
-                    .update(&mut self.cell_entities, delta_time);
+                let interval = 1.0 / self.generations_per_second;
+                self.generation_timer += delta_time;
+
+                let mut generations = 0;
+                while self.generation_timer >= interval && generations < MAX_GENERATIONS_PER_FRAME {
+                    self.step_generation(interval);
+                    self.generation_timer -= interval;
+                    generations += 1;
+                }
+                self.generation_timer = self.generation_timer.min(interval);
+            }
+            GameState::Paused if history_update.needs_generation => {
+                self.step_generation(delta_time)
             }
             _ => {}
         }
     }
 
     fn render(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread) {
+        let coloring = CellColoring::new(self.render_mode, &self.palette, &self.cell_entities);
+        if let Some(grid) = self.grid_entity.get_component::<Grid>() {
+            self.render_system
+                .prepare(rl, thread, &self.cell_entities, grid, &coloring);
+        }
+
         let mut d = rl.begin_drawing(thread);
 
         d.clear_background(Color::BLACK);
 
-        self.render_system
-            .render(&mut d, &self.cell_entities, self.width);
-        self.grid_render_system.render(&mut d, &self.grid_entity);
-
-        match self.game_state {
-            GameState::Paused => {
-                PauseRenderSystem.render(&mut d);
-            }
-            _ => {}
+        if let Some(grid) = self.grid_entity.get_component::<Grid>() {
+            self.render_system
+                .render(&mut d, &self.cell_entities, grid, &self.camera, &coloring);
+            self.grid_render_system.render(&mut d, grid, &self.camera);
+            self.ant_render_system
+                .render(&mut d, &self.ant_entities, grid, &self.camera);
+            self.editor_system.render(&mut d, grid, &self.camera);
         }
+
+        self.stats_render_system.render(&mut d, &self.stats_system);
+
+        let rule_label = if self.rule.is_turmite() {
+            format!(
+                "Rule: {} (N to change, A adds an ant, Shift+A removes all)",
+                self.rule_name
+            )
+        } else {
+            format!("Rule: {} (N to change)", self.rule_name)
+        };
+        let y = d.get_screen_height() - 55;
+        d.draw_text(&rule_label, 10, y, 20, Color::WHITE);
+
+        self.ui.render(&mut d);
     }
 
     fn change_state(&mut self, new_state: GameState) {
@@ -126,21 +700,203 @@ impl Game for GameOfLife {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-enum CellState {
-    Alive,
-    Dead,
+/// State of a cell. 0 is always the dead (empty) state, what the other values mean
+/// depends on the `Rule` being run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct CellState(u8);
+
+impl CellState {
+    const DEAD: CellState = CellState(0);
+    const ALIVE: CellState = CellState(1);
+
+    /// This state, or dead if a rule with `states` states does not have it.
+    fn within(self, states: u8) -> CellState {
+        if self.0 < states {
+            self
+        } else {
+            CellState::DEAD
+        }
+    }
 }
 
 struct Cell {
     pub state: CellState,
+    /// Generations the cell has been alive in a row, 0 when it is not alive.
+    pub age: u32,
+    /// How many generations changed the state of the cell.
+    pub changes: u32,
+    /// Generations since the cell was last alive, `None` if it never was.
+    pub since_alive: Option<u32>,
 }
 
+impl Cell {
+    fn new(state: CellState) -> Self {
+        Self {
+            state,
+            age: 0,
+            changes: 0,
+            since_alive: (state == CellState::ALIVE).then_some(0),
+        }
+    }
+}
+
+/// How cells are placed on the screen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Layout {
+    Square,
+    /// Pointy-top hexagons with every row shifted half a cell further right than the
+    /// one above, so the neighbours are the ones of `Neighbourhood::Hexagonal`.
+    Hexagonal,
+}
+
+impl Layout {
+    fn for_rule(rule: &Rule) -> Self {
+        match rule.neighbourhood() {
+            (Neighbourhood::Hexagonal, _) => Layout::Hexagonal,
+            _ => Layout::Square,
+        }
+    }
+}
+
+const SQRT_3: f32 = 1.732_050_8;
+
 struct Grid {
     pub width: u32,
     pub height: u32,
     pub cell_size: i32,
     pub show: bool,
+    pub layout: Layout,
+}
+
+impl Grid {
+    /// The board in world coordinates.
+    fn bounds(&self) -> Rectangle {
+        let cell_size = self.cell_size as f32;
+        match self.layout {
+            Layout::Square => Rectangle::new(
+                0.0,
+                0.0,
+                self.width as f32 * cell_size,
+                self.height as f32 * cell_size,
+            ),
+            Layout::Hexagonal => {
+                let rows = self.height.max(1) as f32;
+                Rectangle::new(
+                    0.0,
+                    0.0,
+                    (self.width as f32 + (rows - 1.0) * 0.5) * cell_size,
+                    ((rows - 1.0) * SQRT_3 / 2.0 + 2.0 / SQRT_3) * cell_size,
+                )
+            }
+        }
+    }
+
+    /// World position of the centre of a cell.
+    fn cell_center(&self, x: u32, y: u32) -> Vector2 {
+        let cell_size = self.cell_size as f32;
+        match self.layout {
+            Layout::Square => {
+                Vector2::new((x as f32 + 0.5) * cell_size, (y as f32 + 0.5) * cell_size)
+            }
+            Layout::Hexagonal => Vector2::new(
+                (x as f32 + 0.5 + y as f32 * 0.5) * cell_size,
+                (1.0 / SQRT_3 + y as f32 * SQRT_3 / 2.0) * cell_size,
+            ),
+        }
+    }
+
+    /// Returns the cell at a world position, if there is one.
+    fn cell_at(&self, world: Vector2) -> Option<(u32, u32)> {
+        let cell_size = self.cell_size as f32;
+        let (x, y) = match self.layout {
+            Layout::Square => ((world.x / cell_size).floor(), (world.y / cell_size).floor()),
+            Layout::Hexagonal => {
+                // Axial coordinates relative to the centre of cell (0, 0), rounded to
+                // the nearest hexagon.
+                let row = (world.y / cell_size - 1.0 / SQRT_3) * 2.0 / SQRT_3;
+                let column = world.x / cell_size - 0.5 - row * 0.5;
+                round_hex(column, row)
+            }
+        };
+
+        if x >= 0.0 && y >= 0.0 && (x as u32) < self.width && (y as u32) < self.height {
+            Some((x as u32, y as u32))
+        } else {
+            None
+        }
+    }
+
+    /// Fills a cell with a colour.
+    fn draw_cell(&self, rl: &mut RaylibDrawHandle, camera: &Camera, x: u32, y: u32, color: Color) {
+        let cell_size = self.cell_size as f32;
+        let center = camera.world_to_screen(self.cell_center(x, y));
+
+        match self.layout {
+            Layout::Square => {
+                let size = cell_size * camera.zoom;
+                let top_left = center - Vector2::new(size / 2.0, size / 2.0);
+                rl.draw_rectangle_v(top_left, Vector2::new(size, size), color);
+            }
+            Layout::Hexagonal => {
+                rl.draw_poly(center, 6, cell_size / SQRT_3 * camera.zoom, 30.0, color);
+            }
+        }
+    }
+
+    /// Returns the ranges of cell columns and rows that the camera can see.
+    fn visible_cells(
+        &self,
+        camera: &Camera,
+        screen_width: i32,
+        screen_height: i32,
+    ) -> (Range<u32>, Range<u32>) {
+        let visible = camera.visible_world(screen_width as f32, screen_height as f32);
+        let cell_size = self.cell_size as f32;
+
+        if self.layout == Layout::Hexagonal {
+            let row_height = cell_size * SQRT_3 / 2.0;
+            let first_row = (visible.y / row_height).floor() - 1.0;
+            let last_row = ((visible.y + visible.height) / row_height).ceil() + 1.0;
+            // Rows are shifted right, so lower rows start further left in columns.
+            let first_column = (visible.x / cell_size - last_row * 0.5).floor() - 1.0;
+            let last_column =
+                ((visible.x + visible.width) / cell_size - first_row * 0.5).ceil() + 1.0;
+
+            let clamp = |value: f32, max: u32| (value.max(0.0) as u32).min(max);
+            return (
+                clamp(first_column, self.width)..clamp(last_column, self.width),
+                clamp(first_row, self.height)..clamp(last_row, self.height),
+            );
+        }
+
+        let first_column = (visible.x / cell_size).floor().max(0.0) as u32;
+        let first_row = (visible.y / cell_size).floor().max(0.0) as u32;
+        let last_column = ((visible.x + visible.width) / cell_size).ceil().max(0.0) as u32;
+        let last_row = ((visible.y + visible.height) / cell_size).ceil().max(0.0) as u32;
+
+        (
+            first_column.min(self.width)..last_column.min(self.width),
+            first_row.min(self.height)..last_row.min(self.height),
+        )
+    }
+}
+
+/// Rounds fractional axial hex coordinates to the hexagon containing them.
+fn round_hex(column: f32, row: f32) -> (f32, f32) {
+    let third = -column - row;
+    let (mut rounded_column, mut rounded_row) = (column.round(), row.round());
+    let rounded_third = third.round();
+
+    let column_error = (rounded_column - column).abs();
+    let row_error = (rounded_row - row).abs();
+    let third_error = (rounded_third - third).abs();
+    if column_error > row_error && column_error > third_error {
+        rounded_column = -rounded_row - rounded_third;
+    } else if row_error > third_error {
+        rounded_row = -rounded_column - rounded_third;
+    }
+
+    (rounded_column, rounded_row)
 }
 
 fn create_grid(width: u32, height: u32) -> Vec<Entity> {
@@ -150,13 +906,11 @@ fn create_grid(width: u32, height: u32) -> Vec<Entity> {
     for x in 0..width {
         for y in 0..height {
             let cell_entity = EntityBuilder::new((x + y * width) as u32)
-                .with_component(Cell {
-                    state: if rng.gen_bool(0.5) {
-                        CellState::Alive
-                    } else {
-                        CellState::Dead
-                    },
-                })
+                .with_component(Cell::new(if rng.gen_bool(0.5) {
+                    CellState::ALIVE
+                } else {
+                    CellState::DEAD
+                }))
                 .build();
             entities.push(cell_entity);
         }
@@ -165,119 +919,232 @@ fn create_grid(width: u32, height: u32) -> Vec<Entity> {
     entities
 }
 
-struct LifeSystem;
+fn create_system_manager(rule: &Rule, width: u32, height: u32) -> SystemManager {
+    let mut system_manager = SystemManager::new();
+    // Turmite cells only change when an ant walks over them.
+    if rule.is_turmite() {
+        return system_manager;
+    }
+
+    let backend = if rule.is_conway() && width * height >= BIT_PACKED_MIN_CELLS {
+        LifeBackend::BitPacked
+    } else {
+        LifeBackend::Entity
+    };
+
+    match backend {
+        LifeBackend::Entity => system_manager.add_system(LifeSystem::new(rule.clone())),
+        LifeBackend::BitPacked => system_manager.add_system(BitLifeSystem::new(width, height)),
+    }
+
+    system_manager
+}
+
+struct LifeSystem {
+    rule: Rule,
+}
+
+impl LifeSystem {
+    fn new(rule: Rule) -> Self {
+        Self { rule }
+    }
+}
 
 impl System for LifeSystem {
     fn update(&mut self, entities: &mut [Entity], _delta_time: f32) {
         let width = (entities.len() as f64).sqrt() as u32;
+        let height = width; // Assume square grid
 
-        let next_states: Vec<CellState> = entities
-            .iter()
-            .enumerate()
-            .map(|(i, entity)| {
-                if let Some(cell) = entity.get_component::<Cell>() {
-                    let alive_neighbors = count_alive_neighbors(i, entities, width);
-                    match (cell.state, alive_neighbors) {
-                        (CellState::Alive, 2..=3) => CellState::Alive,
-                        (CellState::Dead, 3) => CellState::Alive,
-                        _ => CellState::Dead,
-                    }
-                } else {
-                    CellState::Dead
-                }
-            })
-            .collect();
+        // The counts are taken from the board before any cell is updated.
+        let alive_sums = AliveSums::new(&cell_states(entities), width, height);
+        let (neighbourhood, radius) = self.rule.neighbourhood();
 
         for (i, entity) in entities.iter_mut().enumerate() {
             if let Some(cell) = entity.get_component_mut::<Cell>() {
-                cell.state = next_states[i];
+                let (x, y) = (i as u32 % width, i as u32 / width);
+                let mut alive_neighbors = alive_sums.count(neighbourhood, radius, x, y);
+                if self.rule.counts_middle() && cell.state == CellState::ALIVE {
+                    alive_neighbors += 1;
+                }
+
+                cell.state = self.rule.next_state(cell.state, alive_neighbors as usize);
             }
         }
     }
 }
 
-fn count_alive_neighbors(index: usize, entities: &[Entity], width: u32) -> usize {
-    let mut count = 0;
-    let height = width; // Assume square grid
-
-    let x = index as u32 % width;
-    let y = index as u32 / height;
-
-    for dx in -1..=1 {
-        for dy in -1..=1 {
-            if dx == 0 && dy == 0 {
-                continue;
-            }
-
-            let nx = x as i32 + dx;
-            let ny = y as i32 + dy;
-
-            if nx < 0 || nx >= width as i32 || ny < 0 || ny >= height as i32 {
-                continue;
-            }
+/// Draws the cells in the colours of a `CellColoring`, cells it gives no colour are
+/// left to the black background.
+/// On a square layout the board is kept in a texture with one pixel per cell, which
+/// is drawn stretched over the board in a single call. Hexagonal boards draw every
+/// visible coloured cell as a hexagon.
+struct RenderSystem {
+    texture: Option<Texture2D>,
+    pixels: Vec<u8>,
+}
 
-            let neighbor_index = (nx + ny * width as i32) as usize;
-            if let Some(cell) = entities[neighbor_index].get_component::<Cell>() {
-                if cell.state == CellState::Alive {
-                    count += 1;
-                }
-            }
+impl RenderSystem {
+    fn new() -> Self {
+        Self {
+            texture: None,
+            pixels: Vec::new(),
         }
     }
 
-    count
-}
+    /// Uploads the board to the texture. Textures can only be created and updated
+    /// before drawing starts, so this is called before `render` every frame.
+    pub fn prepare(
+        &mut self,
+        rl: &mut RaylibHandle,
+        thread: &RaylibThread,
+        entities: &[Entity],
+        grid: &Grid,
+        coloring: &CellColoring,
+    ) {
+        if grid.layout != Layout::Square {
+            return;
+        }
 
-struct RenderSystem;
+        let (width, height) = (grid.width as i32, grid.height as i32);
+        if !matches!(&self.texture, Some(texture) if texture.width == width && texture.height == height)
+        {
+            let image = raylib::texture::Image::gen_image_color(width, height, Color::BLANK);
+            self.texture = match rl.load_texture_from_image(thread, &image) {
+                Ok(texture) => Some(texture),
+                Err(error) => {
+                    println!("Could not create the board texture: {}", error);
+                    None
+                }
+            };
+        }
 
-impl RenderSystem {
-    pub fn render(&self, rl: &mut RaylibDrawHandle, entities: &[Entity], width: u32) {
-        let cell_size = 10i32;
+        if let Some(texture) = &mut self.texture {
+            cell_pixels(entities, coloring, &mut self.pixels);
+            texture.update_texture(&self.pixels);
+        }
+    }
 
-        for (i, entity) in entities.iter().enumerate() {
-            if let Some(cell) = entity.get_component::<Cell>() {
-                let x = (i as u32 % width) * cell_size as u32;
-                let y = (i as u32 / width) * cell_size as u32;
+    pub fn render(
+        &self,
+        rl: &mut RaylibDrawHandle,
+        entities: &[Entity],
+        grid: &Grid,
+        camera: &Camera,
+        coloring: &CellColoring,
+    ) {
+        if let (Layout::Square, Some(texture)) = (grid.layout, &self.texture) {
+            let bounds = grid.bounds();
+            let top_left = camera.world_to_screen(Vector2::new(bounds.x, bounds.y));
+            rl.draw_texture_pro(
+                texture,
+                Rectangle::new(0.0, 0.0, grid.width as f32, grid.height as f32),
+                Rectangle::new(
+                    top_left.x,
+                    top_left.y,
+                    bounds.width * camera.zoom,
+                    bounds.height * camera.zoom,
+                ),
+                Vector2::new(0.0, 0.0),
+                0.0,
+                Color::WHITE,
+            );
+            return;
+        }
 
-                let color = match cell.state {
-                    CellState::Alive => Color::GREEN,
-                    CellState::Dead => Color::BLACK,
-                };
+        let (columns, rows) =
+            grid.visible_cells(camera, rl.get_screen_width(), rl.get_screen_height());
 
-                rl.draw_rectangle(x as i32, y as i32, cell_size, cell_size, color);
+        for y in rows {
+            for x in columns.clone() {
+                let index = (x + y * grid.width) as usize;
+                if let Some(color) = entities[index]
+                    .get_component::<Cell>()
+                    .and_then(|cell| coloring.color(cell))
+                {
+                    grid.draw_cell(rl, camera, x, y, color);
+                }
             }
         }
     }
 }
 
-struct PauseRenderSystem;
-impl PauseRenderSystem {
-    pub fn render(&self, rl: &mut RaylibDrawHandle) {
-        rl.draw_text("Paused", 400 - 48, 300 - 36, 36, Color::WHITE);
+/// RGBA pixels of a board with one pixel per cell, transparent where nothing is drawn.
+fn cell_pixels(entities: &[Entity], coloring: &CellColoring, pixels: &mut Vec<u8>) {
+    pixels.clear();
+    for entity in entities.iter() {
+        let color = entity
+            .get_component::<Cell>()
+            .and_then(|cell| coloring.color(cell))
+            .unwrap_or(Color::BLANK);
+        pixels.extend_from_slice(&[color.r, color.g, color.b, color.a]);
     }
 }
 
+/// On-screen cell width in pixels at which grid lines start to show, and from which
+/// they are fully visible.
+const GRID_FADE_START: f32 = 4.0;
+const GRID_FADE_END: f32 = 12.0;
+
+/// Opacity of the grid lines when cells are `cell_pixels` wide on screen.
+fn grid_alpha(cell_pixels: f32) -> f32 {
+    ((cell_pixels - GRID_FADE_START) / (GRID_FADE_END - GRID_FADE_START)).clamp(0.0, 1.0)
+}
+
+/// Draws the grid over the visible part of the board, fading it out as the camera
+/// zooms out. Square grids take one line per row and column.
 struct GridRenderSystem;
 
 impl GridRenderSystem {
-    pub fn render(&self, rl: &mut RaylibDrawHandle, grid_entity: &Entity) {
-        if let Some(grid) = grid_entity.get_component::<Grid>() {
-            if grid.show {
-                for x in 0..grid.width {
-                    for y in 0..grid.height {
-                        let x = x * grid.cell_size as u32;
-                        let y = y * grid.cell_size as u32;
-
-                        rl.draw_rectangle_lines(
-                            x as i32,
-                            y as i32,
-                            grid.cell_size,
-                            grid.cell_size,
-                            Color::DARKGRAY,
-                        );
-                    }
+    pub fn render(&self, rl: &mut RaylibDrawHandle, grid: &Grid, camera: &Camera) {
+        let cell_size = grid.cell_size as f32;
+        let alpha = grid_alpha(cell_size * camera.zoom);
+        if !grid.show || alpha <= 0.0 {
+            return;
+        }
+
+        let color = Color::DARKGRAY.fade(alpha);
+        let (columns, rows) =
+            grid.visible_cells(camera, rl.get_screen_width(), rl.get_screen_height());
+
+        if grid.layout == Layout::Hexagonal {
+            let radius = cell_size / SQRT_3 * camera.zoom;
+            for y in rows {
+                for x in columns.clone() {
+                    let center = camera.world_to_screen(grid.cell_center(x, y));
+                    rl.draw_poly_lines(center, 6, radius, 30.0, color);
                 }
             }
+            return;
+        }
+
+        let top_left = camera.world_to_screen(Vector2::new(
+            columns.start as f32 * cell_size,
+            rows.start as f32 * cell_size,
+        ));
+        let bottom_right = camera.world_to_screen(Vector2::new(
+            columns.end as f32 * cell_size,
+            rows.end as f32 * cell_size,
+        ));
+
+        for x in columns.start..=columns.end {
+            let screen_x = camera
+                .world_to_screen(Vector2::new(x as f32 * cell_size, 0.0))
+                .x;
+            rl.draw_line_v(
+                Vector2::new(screen_x, top_left.y),
+                Vector2::new(screen_x, bottom_right.y),
+                color,
+            );
+        }
+        for y in rows.start..=rows.end {
+            let screen_y = camera
+                .world_to_screen(Vector2::new(0.0, y as f32 * cell_size))
+                .y;
+            rl.draw_line_v(
+                Vector2::new(top_left.x, screen_y),
+                Vector2::new(bottom_right.x, screen_y),
+                color,
+            );
         }
     }
 }
@@ -287,42 +1154,28 @@ struct ToggleCellEventData {
     y: u32,
 }
 
+struct SetCellsEventData {
+    cells: Vec<(u32, u32, CellState)>,
+}
+
 struct ToggleGridEventData;
 
-struct InputSystem {
-    cell_size: i32,
-}
-impl InputSystem {
-    pub fn new(cell_size: i32) -> Self {
-        Self { cell_size }
-    }
+/// How much one notch of the mouse wheel zooms in or out.
+const ZOOM_STEP: f32 = 1.1;
 
+struct InputSystem;
+impl InputSystem {
+    /// Moves the camera and toggles the grid. Returns the cell under the mouse, if any.
+    /// While the mouse is over the UI (`ui_wants_mouse`) it neither moves the camera nor
+    /// hovers a cell, but keys still work.
     pub fn handle_input(
         &mut self,
         rl: &RaylibHandle,
-        width: u32,
-        height: u32,
-        toggle_cells_event_queue: &mut EventQueue,
+        grid: &Grid,
+        camera: &mut Camera,
         toggle_grid_event_queue: &mut EventQueue,
-    ) {
-        if rl.is_mouse_button_pressed(raylib::consts::MouseButton::MOUSE_BUTTON_LEFT) {
-            let mouse_position = rl.get_mouse_position();
-
-            let cell_x = (mouse_position.x / self.cell_size as f32) as u32;
-            let cell_y = (mouse_position.y / self.cell_size as f32) as u32;
-
-            if cell_x < width && cell_y < height {
-                let event = Event {
-                    event_type: "toggle_cell".to_string(),
-                    data: Box::new(ToggleCellEventData {
-                        x: cell_x,
-                        y: cell_y,
-                    }),
-                };
-                toggle_cells_event_queue.push(event);
-            }
-        }
-
+        ui_wants_mouse: bool,
+    ) -> Option<(u32, u32)> {
         if rl.is_key_pressed(raylib::consts::KeyboardKey::KEY_G) {
             let event = Event {
                 event_type: "toggle_grid".to_string(),
@@ -331,6 +1184,25 @@ impl InputSystem {
 
             toggle_grid_event_queue.push(event);
         }
+
+        if ui_wants_mouse {
+            return None;
+        }
+
+        let mouse_position = rl.get_mouse_position();
+
+        let wheel = rl.get_mouse_wheel_move();
+        if wheel != 0.0 {
+            camera.zoom_at(mouse_position, ZOOM_STEP.powf(wheel));
+        }
+
+        if rl.is_mouse_button_down(raylib::consts::MouseButton::MOUSE_BUTTON_RIGHT)
+            || rl.is_mouse_button_down(raylib::consts::MouseButton::MOUSE_BUTTON_MIDDLE)
+        {
+            camera.pan(rl.get_mouse_delta());
+        }
+
+        grid.cell_at(camera.screen_to_world(mouse_position))
     }
 }
 
@@ -339,20 +1211,49 @@ struct CellToggleSystem {
 }
 
 impl CellToggleSystem {
-    fn process_events(&mut self, entities: &mut [Entity], event_queue: &mut EventQueue) {
+    /// Applies edit events and returns the cells that actually changed.
+    fn process_events(
+        &mut self,
+        entities: &mut [Entity],
+        event_queue: &mut EventQueue,
+        rule: &Rule,
+    ) -> Vec<CellChange> {
+        let mut changes = Vec::new();
+
         while let Some(event) = event_queue.pop() {
             if event.event_type == "toggle_cell" {
                 if let Some(data) = event.data.downcast_ref::<ToggleCellEventData>() {
                     let index = (data.x + data.y * self.width) as usize;
                     if let Some(cell) = entities[index].get_component_mut::<Cell>() {
-                        cell.state = match cell.state {
-                            CellState::Alive => CellState::Dead,
-                            CellState::Dead => CellState::Alive,
-                        };
+                        let before = cell.state;
+                        cell.state = rule.toggle(cell.state);
+                        changes.push(CellChange {
+                            index,
+                            before,
+                            after: cell.state,
+                        });
+                    }
+                }
+            } else if event.event_type == "set_cells" {
+                if let Some(data) = event.data.downcast_ref::<SetCellsEventData>() {
+                    for &(x, y, state) in data.cells.iter() {
+                        let index = (x + y * self.width) as usize;
+                        if let Some(cell) = entities[index].get_component_mut::<Cell>() {
+                            if cell.state != state {
+                                changes.push(CellChange {
+                                    index,
+                                    before: cell.state,
+                                    after: state,
+                                });
+                                cell.state = state;
+                            }
+                        }
                     }
                 }
             }
         }
+
+        changes
     }
 }
 
@@ -380,6 +1281,7 @@ pub fn run() {
     let height = 80;
 
     let mut game = GameOfLife::new(width, height);
+    game.fit_camera_to_board(rl.get_screen_width(), rl.get_screen_height());
 
     while !rl.window_should_close() {
         let delta_time = rl.get_frame_time();
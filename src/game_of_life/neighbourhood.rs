@@ -0,0 +1,106 @@
+use super::CellState;
+
+/// Which cells around a cell count as its neighbours.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Neighbourhood {
+    /// Every cell within the radius in both directions, a square.
+    Moore,
+    /// Cells whose horizontal plus vertical distance is within the radius, a diamond.
+    VonNeumann,
+    /// Cells within the radius on a hexagonal board. Hex cells use axial coordinates,
+    /// so `(x + 1, y - 1)` and `(x - 1, y + 1)` are neighbours but the other diagonals are not.
+    Hexagonal,
+}
+
+impl Neighbourhood {
+    /// How many cells the neighbourhood has, not counting the centre.
+    pub fn size(self, radius: u32) -> u32 {
+        let r = radius as i32;
+        (-r..=r)
+            .map(|dy| {
+                let (first, last) = self.row_span(r, dy);
+                (last - first + 1) as u32
+            })
+            .sum::<u32>()
+            - 1
+    }
+
+    /// First and last x offset covered in the row `dy` away from the centre.
+    fn row_span(self, radius: i32, dy: i32) -> (i32, i32) {
+        match self {
+            Neighbourhood::Moore => (-radius, radius),
+            Neighbourhood::VonNeumann => {
+                let reach = radius - dy.abs();
+                (-reach, reach)
+            }
+            Neighbourhood::Hexagonal => ((-radius).max(-radius - dy), radius.min(radius - dy)),
+        }
+    }
+}
+
+/// Summed-area table of the alive cells of a board. Any rectangle of the board can be
+/// counted with four lookups, so a neighbourhood costs one lookup per row at most and
+/// a Moore neighbourhood a single rectangle, whatever the radius.
+/// Cells outside the board count as dead.
+pub struct AliveSums {
+    width: u32,
+    height: u32,
+    sums: Vec<u32>,
+}
+
+impl AliveSums {
+    pub fn new(states: &[CellState], width: u32, height: u32) -> Self {
+        let stride = (width + 1) as usize;
+        let mut sums = vec![0; stride * (height + 1) as usize];
+
+        for y in 0..height as usize {
+            let mut row_sum = 0;
+            for x in 0..width as usize {
+                if states[x + y * width as usize] == CellState::ALIVE {
+                    row_sum += 1;
+                }
+                sums[(x + 1) + (y + 1) * stride] = sums[(x + 1) + y * stride] + row_sum;
+            }
+        }
+
+        Self {
+            width,
+            height,
+            sums,
+        }
+    }
+
+    /// Number of alive cells from `(x0, y0)` to `(x1, y1)` inclusive, clipped to the board.
+    pub fn rect(&self, x0: i32, y0: i32, x1: i32, y1: i32) -> u32 {
+        let x0 = x0.max(0) as usize;
+        let y0 = y0.max(0) as usize;
+        let x1 = (x1 + 1).min(self.width as i32);
+        let y1 = (y1 + 1).min(self.height as i32);
+        if x1 <= x0 as i32 || y1 <= y0 as i32 {
+            return 0;
+        }
+
+        let (x1, y1) = (x1 as usize, y1 as usize);
+        let stride = (self.width + 1) as usize;
+        self.sums[x1 + y1 * stride] + self.sums[x0 + y0 * stride]
+            - self.sums[x0 + y1 * stride]
+            - self.sums[x1 + y0 * stride]
+    }
+
+    /// Alive neighbours of a cell, not counting the cell itself.
+    pub fn count(&self, neighbourhood: Neighbourhood, radius: u32, x: u32, y: u32) -> u32 {
+        let (x, y, r) = (x as i32, y as i32, radius as i32);
+
+        let total = match neighbourhood {
+            Neighbourhood::Moore => self.rect(x - r, y - r, x + r, y + r),
+            _ => (-r..=r)
+                .map(|dy| {
+                    let (first, last) = neighbourhood.row_span(r, dy);
+                    self.rect(x + first, y + dy, x + last, y + dy)
+                })
+                .sum(),
+        };
+
+        total - self.rect(x, y, x, y)
+    }
+}
@@ -0,0 +1,388 @@
+use std::ops::RangeInclusive;
+
+use raylib::color::Color;
+
+use super::{
+    neighbourhood::Neighbourhood,
+    turmite::{parse_turns, Turn},
+    CellState,
+};
+
+/// Cellular automaton rule run on the board.
+/// Every rule counts the neighbours in state 1, which is `CellState::ALIVE` for the
+/// Life-like rules and an electron head in Wireworld.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Rule {
+    /// Two-state rule listing the neighbour counts that give birth to a dead cell and
+    /// that let an alive cell survive, e.g. B3/S23 for Conway's Game of Life.
+    LifeLike {
+        birth: [bool; 9],
+        survival: [bool; 9],
+        neighbourhood: Neighbourhood,
+    },
+    /// Like `LifeLike`, but a cell that does not survive goes through `states - 2` dying
+    /// states before it is dead again. Dying cells are not counted as neighbours.
+    Generations {
+        birth: [bool; 9],
+        survival: [bool; 9],
+        states: u8,
+        neighbourhood: Neighbourhood,
+    },
+    /// Larger than Life: neighbours are counted within `radius` and birth and survival
+    /// are ranges of counts. With more than 2 `states` cells decay like in `Generations`.
+    Larger {
+        radius: u32,
+        birth: RangeInclusive<u32>,
+        survival: RangeInclusive<u32>,
+        states: u8,
+        /// Whether an alive cell counts itself as one of its neighbours.
+        counts_middle: bool,
+        neighbourhood: Neighbourhood,
+    },
+    /// Four states: empty, electron head, electron tail and conductor.
+    Wireworld,
+    /// Langton's ant and other turmites. Cells never change on their own, only when
+    /// an ant walks over them, and `turns[state]` is how the ant turns on a cell.
+    Turmite { turns: Vec<Turn> },
+}
+
+const WIREWORLD_HEAD: CellState = CellState(1);
+const WIREWORLD_TAIL: CellState = CellState(2);
+const WIREWORLD_CONDUCTOR: CellState = CellState(3);
+
+impl Rule {
+    /// Conway's Game of Life, B3/S23.
+    pub fn life() -> Self {
+        Rule::parse("B3/S23").unwrap()
+    }
+
+    /// Brian's Brain, the Generations rule B2/S/C3.
+    pub fn brians_brain() -> Self {
+        Rule::parse("B2/S/C3").unwrap()
+    }
+
+    pub fn wireworld() -> Self {
+        Rule::Wireworld
+    }
+
+    /// Turmite with a rulestring such as "RL" (Langton's ant) or "RLR".
+    pub fn turmite(rule: &str) -> Result<Rule, String> {
+        Ok(Rule::Turmite {
+            turns: parse_turns(rule)?,
+        })
+    }
+
+    /// Rules that can be picked in the game.
+    pub fn presets() -> Vec<(&'static str, Rule)> {
+        vec![
+            ("Life", Rule::life()),
+            ("HighLife", Rule::parse("B36/S23").unwrap()),
+            ("Seeds", Rule::parse("B2/S").unwrap()),
+            ("Brian's Brain", Rule::brians_brain()),
+            ("Star Wars", Rule::parse("B2/S345/C4").unwrap()),
+            ("Wireworld", Rule::wireworld()),
+            ("Langton's Ant", Rule::turmite("RL").unwrap()),
+            ("Turmite RLR", Rule::turmite("RLR").unwrap()),
+            ("Turmite LLRR", Rule::turmite("LLRR").unwrap()),
+            ("Hex B2/S34", Rule::parse("B2/S34H").unwrap()),
+            ("von Neumann B2/S12", Rule::parse("B2/S12V").unwrap()),
+            ("Bugs", Rule::parse("R5,C2,M1,S34..58,B34..45,NM").unwrap()),
+        ]
+    }
+
+    /// Parses a rulestring such as "B3/S23" (Life-like) or "B2/S/C3" (Generations).
+    /// A trailing "H" or "V" picks the hexagonal or von Neumann neighbourhood, and
+    /// Larger than Life rules are written like "R5,C2,M1,S34..58,B34..45,NM".
+    pub fn parse(rule: &str) -> Result<Rule, String> {
+        if rule.contains(',') {
+            return parse_larger(rule);
+        }
+
+        let (counts, neighbourhood) = match rule.chars().last().map(|c| c.to_ascii_uppercase()) {
+            Some('H') => (&rule[..rule.len() - 1], Neighbourhood::Hexagonal),
+            Some('V') => (&rule[..rule.len() - 1], Neighbourhood::VonNeumann),
+            _ => (rule, Neighbourhood::Moore),
+        };
+        let max_count = neighbourhood.size(1);
+
+        let mut birth = [false; 9];
+        let mut survival = [false; 9];
+        let mut states = 2;
+
+        for part in counts.split('/') {
+            let mut chars = part.chars();
+            match chars.next().map(|c| c.to_ascii_uppercase()) {
+                Some('B') => parse_counts(chars.as_str(), max_count, &mut birth)?,
+                Some('S') => parse_counts(chars.as_str(), max_count, &mut survival)?,
+                Some('C') => {
+                    states = chars
+                        .as_str()
+                        .parse()
+                        .map_err(|_| format!("Invalid number of states in \"{}\"", part))?;
+                    if states < 2 {
+                        return Err(format!("A rule needs at least 2 states, got {}", states));
+                    }
+                }
+                _ => return Err(format!("Invalid rule part \"{}\" in \"{}\"", part, rule)),
+            }
+        }
+
+        if states == 2 {
+            Ok(Rule::LifeLike {
+                birth,
+                survival,
+                neighbourhood,
+            })
+        } else {
+            Ok(Rule::Generations {
+                birth,
+                survival,
+                states,
+                neighbourhood,
+            })
+        }
+    }
+
+    /// Number of states a cell can be in, including the dead/empty state 0.
+    pub fn states(&self) -> u8 {
+        match self {
+            Rule::LifeLike { .. } => 2,
+            Rule::Generations { states, .. } | Rule::Larger { states, .. } => *states,
+            Rule::Wireworld => 4,
+            Rule::Turmite { turns } => turns.len() as u8,
+        }
+    }
+
+    pub fn is_conway(&self) -> bool {
+        *self == Rule::life()
+    }
+
+    pub fn is_turmite(&self) -> bool {
+        matches!(self, Rule::Turmite { .. })
+    }
+
+    /// Neighbourhood and radius the rule counts alive cells in.
+    pub fn neighbourhood(&self) -> (Neighbourhood, u32) {
+        match self {
+            Rule::LifeLike { neighbourhood, .. } | Rule::Generations { neighbourhood, .. } => {
+                (*neighbourhood, 1)
+            }
+            Rule::Larger {
+                radius,
+                neighbourhood,
+                ..
+            } => (*neighbourhood, *radius),
+            _ => (Neighbourhood::Moore, 1),
+        }
+    }
+
+    pub fn counts_middle(&self) -> bool {
+        matches!(
+            self,
+            Rule::Larger {
+                counts_middle: true,
+                ..
+            }
+        )
+    }
+
+    /// State of a cell in the next generation given how many of its neighbours are in state 1.
+    pub fn next_state(&self, state: CellState, alive_neighbours: usize) -> CellState {
+        match self {
+            Rule::LifeLike {
+                birth, survival, ..
+            } => decay(
+                state,
+                birth[alive_neighbours],
+                survival[alive_neighbours],
+                2,
+            ),
+            Rule::Generations {
+                birth,
+                survival,
+                states,
+                ..
+            } => decay(
+                state,
+                birth[alive_neighbours],
+                survival[alive_neighbours],
+                *states,
+            ),
+            Rule::Larger {
+                birth,
+                survival,
+                states,
+                ..
+            } => {
+                let count = alive_neighbours as u32;
+                decay(
+                    state,
+                    birth.contains(&count),
+                    survival.contains(&count),
+                    *states,
+                )
+            }
+            Rule::Wireworld => match state {
+                WIREWORLD_HEAD => WIREWORLD_TAIL,
+                WIREWORLD_TAIL => WIREWORLD_CONDUCTOR,
+                WIREWORLD_CONDUCTOR if (1..=2).contains(&alive_neighbours) => WIREWORLD_HEAD,
+                _ => state,
+            },
+            Rule::Turmite { .. } => state,
+        }
+    }
+
+    /// State a clicked cell changes to. Life-like rules switch between dead and alive,
+    /// Wireworld cycles empty, conductor, head and tail.
+    pub fn toggle(&self, state: CellState) -> CellState {
+        match self {
+            Rule::Wireworld => match state {
+                CellState::DEAD => WIREWORLD_CONDUCTOR,
+                WIREWORLD_CONDUCTOR => WIREWORLD_HEAD,
+                WIREWORLD_HEAD => WIREWORLD_TAIL,
+                _ => CellState::DEAD,
+            },
+            Rule::Turmite { turns } => CellState((state.0 + 1) % turns.len() as u8),
+            _ if state == CellState::DEAD => CellState::ALIVE,
+            _ => CellState::DEAD,
+        }
+    }
+
+    /// State drawn by the brush and shape tools.
+    pub fn paint_state(&self) -> CellState {
+        match self {
+            Rule::Wireworld => WIREWORLD_CONDUCTOR,
+            _ => CellState::ALIVE,
+        }
+    }
+
+    /// Colour of every state, indexed by the state value.
+    pub fn palette(&self) -> Vec<Color> {
+        match self {
+            Rule::LifeLike { .. } => vec![Color::BLACK, Color::GREEN],
+            Rule::Generations { states, .. } | Rule::Larger { states, .. } => {
+                let dying_states = (*states - 2) as f32;
+                let mut palette = vec![Color::BLACK, Color::GREEN];
+                // Dying cells fade from bright to dark blue as they get closer to death.
+                palette.extend((0..*states - 2).map(|i| {
+                    let t = if dying_states > 1.0 {
+                        i as f32 / (dying_states - 1.0)
+                    } else {
+                        0.0
+                    };
+                    Color::new(
+                        0,
+                        (160.0 * (1.0 - t) + 20.0 * t) as u8,
+                        (255.0 * (1.0 - t) + 80.0 * t) as u8,
+                        255,
+                    )
+                }));
+                palette
+            }
+            Rule::Wireworld => vec![Color::BLACK, Color::BLUE, Color::RED, Color::YELLOW],
+            Rule::Turmite { turns } => {
+                let mut palette = vec![Color::BLACK];
+                let colours = turns.len() - 1;
+                palette.extend(
+                    (0..colours).map(|i| {
+                        Color::color_from_hsv(360.0 * i as f32 / colours as f32, 0.7, 0.9)
+                    }),
+                );
+                palette
+            }
+        }
+    }
+}
+
+/// Next state of a cell for rules where alive cells that do not survive go through
+/// dying states 2 to `states - 1`. With 2 states they die straight away.
+fn decay(state: CellState, born: bool, survives: bool, states: u8) -> CellState {
+    match state {
+        CellState::DEAD if born => CellState::ALIVE,
+        CellState::DEAD => CellState::DEAD,
+        CellState::ALIVE if survives => CellState::ALIVE,
+        CellState(dying) if dying + 1 < states => CellState(dying + 1),
+        _ => CellState::DEAD,
+    }
+}
+
+fn parse_counts(counts: &str, max_count: u32, allowed: &mut [bool; 9]) -> Result<(), String> {
+    for c in counts.chars() {
+        match c.to_digit(10) {
+            Some(count) if count <= max_count => allowed[count as usize] = true,
+            _ => return Err(format!("Invalid neighbour count '{}'", c)),
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_larger(rule: &str) -> Result<Rule, String> {
+    let mut radius = None;
+    let mut birth = None;
+    let mut survival = None;
+    let mut states = 2;
+    let mut counts_middle = false;
+    let mut neighbourhood = Neighbourhood::Moore;
+
+    for part in rule.split(',').map(str::trim) {
+        let mut chars = part.chars();
+        let key = chars.next().map(|c| c.to_ascii_uppercase());
+        let value = chars.as_str();
+        let invalid = || format!("Invalid rule part \"{}\" in \"{}\"", part, rule);
+
+        match key {
+            Some('R') => radius = Some(value.parse::<u32>().map_err(|_| invalid())?),
+            Some('C') => {
+                states = value.parse::<u8>().map_err(|_| invalid())?;
+                if states < 2 {
+                    return Err(invalid());
+                }
+            }
+            Some('M') => {
+                counts_middle = match value {
+                    "0" => false,
+                    "1" => true,
+                    _ => return Err(invalid()),
+                }
+            }
+            Some('S') => survival = Some(parse_range(value).ok_or_else(invalid)?),
+            Some('B') => birth = Some(parse_range(value).ok_or_else(invalid)?),
+            Some('N') => {
+                neighbourhood = match value.to_ascii_uppercase().as_str() {
+                    "M" => Neighbourhood::Moore,
+                    "N" => Neighbourhood::VonNeumann,
+                    "H" => Neighbourhood::Hexagonal,
+                    _ => return Err(invalid()),
+                }
+            }
+            _ => return Err(invalid()),
+        }
+    }
+
+    match (radius, birth, survival) {
+        (Some(radius), Some(birth), Some(survival)) if radius > 0 => Ok(Rule::Larger {
+            radius,
+            birth,
+            survival,
+            states,
+            counts_middle,
+            neighbourhood,
+        }),
+        _ => Err(format!(
+            "\"{}\" needs a radius of at least 1 and birth and survival ranges",
+            rule
+        )),
+    }
+}
+
+/// Parses "34..58" or a single count like "5".
+fn parse_range(value: &str) -> Option<RangeInclusive<u32>> {
+    match value.split_once("..") {
+        Some((first, last)) => Some(first.parse().ok()?..=last.parse().ok()?),
+        None => {
+            let count = value.parse().ok()?;
+            Some(count..=count)
+        }
+    }
+}
@@ -0,0 +1,357 @@
+use std::io::{self, Write};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::{
+    bitgrid::BitGrid,
+    census::{known_objects, Census, Object},
+    editor::CellRect,
+    stats::{Stability, StatsSystem},
+};
+
+/// Symmetry of a soup, named as on Catagolue.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Symmetry {
+    /// No symmetry.
+    C1,
+    /// Unchanged by a half turn.
+    C2,
+    /// Unchanged by a quarter turn.
+    C4,
+    /// Mirrored both horizontally and vertically.
+    D4,
+    /// Unchanged by every rotation and reflection of the square.
+    D8,
+}
+
+impl Symmetry {
+    pub const ALL: [Symmetry; 5] = [
+        Symmetry::C1,
+        Symmetry::C2,
+        Symmetry::C4,
+        Symmetry::D4,
+        Symmetry::D8,
+    ];
+
+    pub fn parse(name: &str) -> Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|symmetry| symmetry.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| {
+                format!(
+                    "Unknown symmetry \"{}\", expected C1, C2, C4, D4 or D8",
+                    name
+                )
+            })
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Symmetry::C1 => "C1",
+            Symmetry::C2 => "C2",
+            Symmetry::C4 => "C4",
+            Symmetry::D4 => "D4",
+            Symmetry::D8 => "D8",
+        }
+    }
+
+    /// Quarter turns and diagonal reflections only map a square onto itself.
+    fn needs_square(self) -> bool {
+        matches!(self, Symmetry::C4 | Symmetry::D8)
+    }
+
+    /// Cells of a `width` x `height` region that the symmetry maps `(x, y)` to,
+    /// `(x, y)` itself included. C4 and D8 expect a square region.
+    fn orbit(self, x: u32, y: u32, width: u32, height: u32) -> Vec<(u32, u32)> {
+        let (mirror_x, mirror_y) = (width - 1 - x, height - 1 - y);
+        match self {
+            Symmetry::C1 => vec![(x, y)],
+            Symmetry::C2 => vec![(x, y), (mirror_x, mirror_y)],
+            Symmetry::C4 => vec![
+                (x, y),
+                (width - 1 - y, x),
+                (mirror_x, mirror_y),
+                (y, height - 1 - x),
+            ],
+            Symmetry::D4 => vec![(x, y), (mirror_x, y), (x, mirror_y), (mirror_x, mirror_y)],
+            Symmetry::D8 => vec![
+                (x, y),
+                (mirror_x, y),
+                (x, mirror_y),
+                (mirror_x, mirror_y),
+                (y, x),
+                (mirror_y, x),
+                (y, mirror_x),
+                (mirror_y, mirror_x),
+            ],
+        }
+    }
+}
+
+/// How to fill a board with a random soup.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SoupConfig {
+    /// Chance of each cell in the region being alive.
+    pub density: f64,
+    /// Width and height of the patch in the centre of the board that is filled,
+    /// `None` for the whole board.
+    pub region: Option<(u32, u32)>,
+    pub symmetry: Symmetry,
+    /// The same seed always gives the same soup.
+    pub seed: u64,
+}
+
+impl Default for SoupConfig {
+    fn default() -> Self {
+        Self {
+            density: 0.5,
+            region: Some((16, 16)),
+            symmetry: Symmetry::C1,
+            seed: 0,
+        }
+    }
+}
+
+impl SoupConfig {
+    /// The patch of a `width` x `height` board that gets filled, clipped to the board.
+    /// Symmetries that need a square use the largest square that fits.
+    pub fn region(&self, width: u32, height: u32) -> CellRect {
+        let (mut region_width, mut region_height) = self.region.unwrap_or((width, height));
+        region_width = region_width.min(width);
+        region_height = region_height.min(height);
+        if self.symmetry.needs_square() {
+            region_width = region_width.min(region_height);
+            region_height = region_width;
+        }
+
+        CellRect {
+            x: (width - region_width) / 2,
+            y: (height - region_height) / 2,
+            width: region_width,
+            height: region_height,
+        }
+    }
+
+    /// Alive cells of the soup on a `width` x `height` board. Every cell of the region
+    /// gets a random state, then takes the state of the first cell of its orbit so the
+    /// symmetric copies agree.
+    pub fn alive_cells(&self, width: u32, height: u32) -> Vec<(u32, u32)> {
+        let region = self.region(width, height);
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let random: Vec<bool> = (0..region.width * region.height)
+            .map(|_| rng.gen_bool(self.density.clamp(0.0, 1.0)))
+            .collect();
+
+        let mut alive = Vec::new();
+        for y in 0..region.height {
+            for x in 0..region.width {
+                let first = self
+                    .symmetry
+                    .orbit(x, y, region.width, region.height)
+                    .into_iter()
+                    .map(|(x, y)| x + y * region.width)
+                    .min()
+                    .unwrap_or(x + y * region.width);
+
+                if random[first as usize] {
+                    alive.push((region.x + x, region.y + y));
+                }
+            }
+        }
+
+        alive
+    }
+}
+
+/// What a soup settled into.
+pub struct SoupOutcome {
+    pub seed: u64,
+    pub stability: Stability,
+    pub population: u32,
+    /// Objects left on the board when the run stopped.
+    pub census: Census,
+}
+
+/// Runs a soup with B3/S23 on a `size` x `size` board until it becomes still or
+/// periodic, or `max_generations` have passed, then takes a census of what is left.
+pub fn run_soup(
+    config: &SoupConfig,
+    size: u32,
+    max_generations: u64,
+    known: &[(&'static str, Object)],
+) -> SoupOutcome {
+    let mut grid = BitGrid::new(size, size);
+    for (x, y) in config.alive_cells(size, size) {
+        grid.set(x, y, true);
+    }
+
+    let mut stats = StatsSystem::new(&grid.cell_states(), size);
+    let mut generations = 0;
+    while stats.stability() == Stability::Evolving && generations < max_generations {
+        grid.step();
+        stats.record(&grid.cell_states());
+        generations += 1;
+    }
+
+    let mut census = Census::new();
+    census.take(&grid.cell_states(), size, known);
+
+    SoupOutcome {
+        seed: config.seed,
+        stability: stats.stability(),
+        population: stats.latest().map_or(0, |latest| latest.population),
+        census,
+    }
+}
+
+/// Writes one CSV line per soup. The objects are listed in the last column as
+/// `Block x3; Blinker x1`.
+pub fn write_outcome(mut writer: impl Write, outcome: &SoupOutcome) -> io::Result<()> {
+    let (state, since, period) = match outcome.stability {
+        Stability::Evolving => ("unsettled", String::new(), String::new()),
+        Stability::Still { since } => ("still", since.to_string(), "1".to_string()),
+        Stability::Cycle { period, since } => ("periodic", since.to_string(), period.to_string()),
+    };
+
+    let objects: Vec<String> = outcome
+        .census
+        .tally()
+        .into_iter()
+        .map(|(name, count)| format!("{} x{}", name, count))
+        .collect();
+
+    writeln!(
+        writer,
+        "{},{},{},{},{},{}",
+        outcome.seed,
+        state,
+        since,
+        period,
+        outcome.population,
+        objects.join("; ")
+    )
+}
+
+/// Command line options of the soup batch runner.
+pub struct SoupOptions {
+    pub soup: SoupConfig,
+    pub count: u64,
+    pub size: u32,
+    pub generations: u64,
+    pub log: Option<String>,
+}
+
+impl SoupOptions {
+    /// Parses `--seed`, `--count`, `--density`, `--region` (`16` or `16x8`),
+    /// `--symmetry`, `--size`, `--generations` and `--log`, each followed by a value.
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = Self {
+            soup: SoupConfig::default(),
+            count: 1000,
+            size: 64,
+            generations: 10000,
+            log: None,
+        };
+
+        let mut args = args.iter();
+        while let Some(flag) = args.next() {
+            let value = args
+                .next()
+                .ok_or_else(|| format!("Missing value for {}", flag))?;
+            let invalid = || format!("Invalid value \"{}\" for {}", value, flag);
+            let number = || value.parse::<u64>().map_err(|_| invalid());
+
+            match flag.as_str() {
+                "--seed" => options.soup.seed = number()?,
+                "--count" => options.count = number()?,
+                "--density" => {
+                    options.soup.density = value
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|density| (0.0..=1.0).contains(density))
+                        .ok_or_else(invalid)?;
+                }
+                "--region" => {
+                    let (width, height) = value.split_once('x').unwrap_or((value, value));
+                    let width = width.parse::<u32>().map_err(|_| invalid())?;
+                    let height = height.parse::<u32>().map_err(|_| invalid())?;
+                    options.soup.region = Some((width, height));
+                }
+                "--symmetry" => options.soup.symmetry = Symmetry::parse(value)?,
+                "--size" => options.size = value.parse::<u32>().map_err(|_| invalid())?,
+                "--generations" => options.generations = number()?,
+                "--log" => options.log = Some(value.clone()),
+                _ => return Err(format!("Unknown option {}", flag)),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+/// Runs `--count` soups with consecutive seeds without opening a window and logs what
+/// each one settled into and which objects remain, to `--log` or the console.
+/// A tally of the objects over all soups is printed at the end.
+pub fn run(args: Vec<String>) {
+    let options = match SoupOptions::parse(&args) {
+        Ok(options) => options,
+        Err(error) => {
+            println!("{}", error);
+            println!(
+                "Usage: life-soup [--seed 0] [--count 1000] [--density 0.5] [--region 16x16] \
+                 [--symmetry C1|C2|C4|D4|D8] [--size 64] [--generations 10000] [--log soups.csv]"
+            );
+            return;
+        }
+    };
+
+    let mut log: Box<dyn Write> = match &options.log {
+        Some(path) => match std::fs::File::create(path) {
+            Ok(file) => Box::new(io::BufWriter::new(file)),
+            Err(error) => {
+                println!("Could not create {}: {}", path, error);
+                return;
+            }
+        },
+        None => Box::new(io::stdout()),
+    };
+
+    let known = known_objects();
+    let mut total = Census::new();
+    let (mut still, mut periodic, mut unsettled) = (0, 0, 0);
+    let result = writeln!(log, "seed,state,since,period,population,objects").and_then(|_| {
+        for i in 0..options.count {
+            let soup = SoupConfig {
+                seed: options.soup.seed + i,
+                ..options.soup.clone()
+            };
+            let outcome = run_soup(&soup, options.size, options.generations, &known);
+            total.merge(&outcome.census);
+            match outcome.stability {
+                Stability::Evolving => unsettled += 1,
+                Stability::Still { .. } => still += 1,
+                Stability::Cycle { .. } => periodic += 1,
+            }
+            write_outcome(&mut log, &outcome)?;
+        }
+        log.flush()
+    });
+
+    if let Err(error) = result {
+        println!("Could not write soup log: {}", error);
+        return;
+    }
+
+    println!(
+        "Ran {} {} soups: {} still, {} periodic, {} unsettled after {} generations",
+        options.count,
+        options.soup.symmetry.name(),
+        still,
+        periodic,
+        unsettled,
+        options.generations
+    );
+    for (name, count) in total.tally() {
+        println!("{:>8}  {}", count, name);
+    }
+}
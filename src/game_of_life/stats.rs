@@ -0,0 +1,236 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+    io::{self, Write},
+};
+
+use raylib::{
+    color::Color,
+    prelude::{RaylibDraw, RaylibDrawHandle},
+};
+
+use super::{editor::CellRect, CellState};
+
+/// Numbers for a single generation of a run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenerationStats {
+    pub generation: u64,
+    pub population: u32,
+    pub births: u32,
+    pub deaths: u32,
+    /// Smallest rectangle containing every alive cell, `None` for an empty board.
+    pub bounding_box: Option<CellRect>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Stability {
+    /// No earlier state has been repeated yet.
+    Evolving,
+    /// The board stopped changing at `since`.
+    Still { since: u64 },
+    /// The board repeats with `period` generations, first seen at `since`.
+    Cycle { period: u64, since: u64 },
+}
+
+/// Collects per-generation statistics and detects when the board becomes still or
+/// starts cycling. States are recognised by their hash, so only the hashes of
+/// earlier generations are kept, not the boards themselves.
+pub struct StatsSystem {
+    width: u32,
+    generation: u64,
+    previous: Vec<CellState>,
+    seen: HashMap<u64, u64>,
+    stability: Stability,
+    records: Vec<GenerationStats>,
+}
+
+impl StatsSystem {
+    /// Starts a run at generation 0 with the given board.
+    pub fn new(states: &[CellState], width: u32) -> Self {
+        let mut stats = Self {
+            width,
+            generation: 0,
+            previous: Vec::new(),
+            seen: HashMap::new(),
+            stability: Stability::Evolving,
+            records: Vec::new(),
+        };
+        stats.restart(states, 0);
+        stats
+    }
+
+    /// Records the board after a new generation was computed.
+    pub fn record(&mut self, states: &[CellState]) {
+        self.generation += 1;
+
+        let births = self
+            .previous
+            .iter()
+            .zip(states)
+            .filter(|(before, after)| **before != CellState::ALIVE && **after == CellState::ALIVE)
+            .count() as u32;
+        let deaths = self
+            .previous
+            .iter()
+            .zip(states)
+            .filter(|(before, after)| **before == CellState::ALIVE && **after != CellState::ALIVE)
+            .count() as u32;
+
+        self.push_record(states, births, deaths);
+        self.detect_repetition(states);
+        self.previous = states.to_vec();
+    }
+
+    /// Forgets earlier states after the board was edited or rewound, so cycle detection
+    /// starts over from `generation`, the position in the history. Records of later
+    /// generations were undone and are dropped, earlier ones are kept.
+    pub fn restart(&mut self, states: &[CellState], generation: u64) {
+        self.generation = generation;
+        self.records
+            .retain(|record| record.generation <= generation);
+        self.seen.clear();
+        self.stability = Stability::Evolving;
+        self.push_record(states, 0, 0);
+        self.seen.insert(hash_states(states), self.generation);
+        self.previous = states.to_vec();
+    }
+
+    pub fn stability(&self) -> Stability {
+        self.stability
+    }
+
+    pub fn latest(&self) -> Option<&GenerationStats> {
+        self.records.last()
+    }
+
+    pub fn write_csv(&self, mut writer: impl Write) -> io::Result<()> {
+        writeln!(
+            writer,
+            "generation,population,births,deaths,min_x,min_y,max_x,max_y"
+        )?;
+
+        for record in self.records.iter() {
+            let bounding_box = match record.bounding_box {
+                Some(rect) => format!(
+                    "{},{},{},{}",
+                    rect.x,
+                    rect.y,
+                    rect.x + rect.width - 1,
+                    rect.y + rect.height - 1
+                ),
+                None => ",,,".to_string(),
+            };
+
+            writeln!(
+                writer,
+                "{},{},{},{},{}",
+                record.generation, record.population, record.births, record.deaths, bounding_box
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn push_record(&mut self, states: &[CellState], births: u32, deaths: u32) {
+        let record = GenerationStats {
+            generation: self.generation,
+            population: states.iter().filter(|s| **s == CellState::ALIVE).count() as u32,
+            births,
+            deaths,
+            bounding_box: bounding_box(states, self.width),
+        };
+
+        // A restart right after a recorded generation describes the same board.
+        if let Some(last) = self.records.last_mut() {
+            if last.generation == record.generation {
+                last.population = record.population;
+                last.bounding_box = record.bounding_box;
+                return;
+            }
+        }
+
+        self.records.push(record);
+    }
+
+    fn detect_repetition(&mut self, states: &[CellState]) {
+        let hash = hash_states(states);
+
+        if let (Stability::Evolving, Some(&first_seen)) = (self.stability, self.seen.get(&hash)) {
+            let period = self.generation - first_seen;
+            self.stability = if period == 1 {
+                Stability::Still { since: first_seen }
+            } else {
+                Stability::Cycle {
+                    period,
+                    since: first_seen,
+                }
+            };
+        }
+
+        self.seen.entry(hash).or_insert(self.generation);
+    }
+}
+
+fn hash_states(states: &[CellState]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    states.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Smallest rectangle containing every alive cell of a board laid out as `x + y * width`.
+pub fn bounding_box(states: &[CellState], width: u32) -> Option<CellRect> {
+    let mut alive = states
+        .iter()
+        .enumerate()
+        .filter(|(_, state)| **state == CellState::ALIVE)
+        .map(|(i, _)| (i as u32 % width, i as u32 / width));
+
+    let first = alive.next()?;
+    let (min, max) = alive.fold((first, first), |(min, max), (x, y)| {
+        ((min.0.min(x), min.1.min(y)), (max.0.max(x), max.1.max(y)))
+    });
+
+    Some(CellRect::from_corners(min, max))
+}
+
+/// Draws the latest statistics in the top-left corner of the screen.
+pub struct StatsRenderSystem;
+
+impl StatsRenderSystem {
+    pub fn render(&self, rl: &mut RaylibDrawHandle, stats: &StatsSystem) {
+        let Some(latest) = stats.latest() else {
+            return;
+        };
+
+        let bounding_box = match latest.bounding_box {
+            Some(rect) => format!("{}x{} at ({}, {})", rect.width, rect.height, rect.x, rect.y),
+            None => "-".to_string(),
+        };
+        let stability = match stats.stability() {
+            Stability::Evolving => "evolving".to_string(),
+            Stability::Still { since } => format!("still since gen {}", since),
+            Stability::Cycle { period, since } => {
+                format!("period {} since gen {}", period, since)
+            }
+        };
+
+        let lines = [
+            format!("Generation: {}", latest.generation),
+            format!("Population: {}", latest.population),
+            format!("Births: {}  Deaths: {}", latest.births, latest.deaths),
+            format!("Bounding box: {}", bounding_box),
+            format!("State: {}", stability),
+        ];
+
+        rl.draw_rectangle(
+            5,
+            5,
+            260,
+            10 + lines.len() as i32 * 20,
+            Color::BLACK.fade(0.6),
+        );
+        for (i, line) in lines.iter().enumerate() {
+            rl.draw_text(line, 10, 10 + i as i32 * 20, 18, Color::WHITE);
+        }
+    }
+}
@@ -0,0 +1,1217 @@
+use crate::engine::{Entity, EntityBuilder, System};
+use crate::game_of_life::activity::*;
+use crate::game_of_life::bitgrid::*;
+use crate::game_of_life::census::*;
+use crate::game_of_life::editor::*;
+use crate::game_of_life::history::*;
+use crate::game_of_life::lenia::*;
+use crate::game_of_life::neighbourhood::*;
+use crate::game_of_life::rules::*;
+use crate::game_of_life::soup::*;
+use crate::game_of_life::stats::*;
+use crate::game_of_life::turmite::*;
+use crate::game_of_life::*;
+
+fn create_grid_from_rows(rows: &[&str]) -> Vec<Entity> {
+    let mut entities = Vec::new();
+
+    for (y, row) in rows.iter().enumerate() {
+        for (x, c) in row.chars().enumerate() {
+            let state = match c {
+                '#' => CellState::ALIVE,
+                '0'..='9' => CellState(c.to_digit(10).unwrap() as u8),
+                _ => CellState::DEAD,
+            };
+            entities.push(
+                EntityBuilder::new((x + y * row.len()) as u32)
+                    .with_component(Cell::new(state))
+                    .build(),
+            );
+        }
+    }
+
+    entities
+}
+
+fn alive_cells(entities: &[Entity]) -> Vec<bool> {
+    entities
+        .iter()
+        .map(|e| e.get_component::<Cell>().unwrap().state == CellState::ALIVE)
+        .collect()
+}
+
+#[test]
+fn test_bit_grid_blinker_oscillates() {
+    let mut grid = BitGrid::new(5, 5);
+    grid.set(1, 2, true);
+    grid.set(2, 2, true);
+    grid.set(3, 2, true);
+
+    grid.step();
+
+    assert!(grid.get(2, 1));
+    assert!(grid.get(2, 2));
+    assert!(grid.get(2, 3));
+    assert!(!grid.get(1, 2));
+    assert!(!grid.get(3, 2));
+}
+
+#[test]
+fn test_bit_grid_carries_neighbours_across_words() {
+    // A blinker on the top edge, straddling the boundary between the first and second word.
+    let mut grid = BitGrid::new(130, 4);
+    grid.set(63, 0, true);
+    grid.set(64, 0, true);
+    grid.set(65, 0, true);
+
+    grid.step();
+
+    assert!(grid.get(64, 0));
+    assert!(grid.get(64, 1));
+    assert!(!grid.get(63, 0));
+    assert!(!grid.get(65, 0));
+    assert!(!grid.get(64, 2));
+}
+
+#[test]
+fn test_bit_grid_does_not_wrap_at_right_edge() {
+    let mut grid = BitGrid::new(70, 3);
+    grid.set(69, 0, true);
+    grid.set(69, 1, true);
+    grid.set(69, 2, true);
+
+    grid.step();
+
+    assert!(grid.get(68, 1));
+    assert!(grid.get(69, 1));
+    assert!(!grid.get(0, 1));
+    assert!(!grid.get(69, 0));
+}
+
+#[test]
+fn test_bit_life_system_matches_life_system() {
+    let rows = [
+        "#..#...#.#",
+        ".##..#....",
+        "#.#.###..#",
+        "...#..##..",
+        "##...#...#",
+        ".#.##.#.#.",
+        "#....###..",
+        "..##...#.#",
+        "#.#.#.....",
+        ".##..#.##.",
+    ];
+    let mut expected = create_grid_from_rows(&rows);
+    let mut actual = create_grid_from_rows(&rows);
+    let mut bit_life_system = BitLifeSystem::new(10, 10);
+
+    for _ in 0..8 {
+        LifeSystem::new(Rule::life()).update(&mut expected, 0.0);
+        bit_life_system.update(&mut actual, 0.0);
+
+        assert_eq!(alive_cells(&actual), alive_cells(&expected));
+    }
+}
+
+#[test]
+fn test_pattern_from_rows() {
+    let glider = Pattern::from_rows(&[".#.", "..#", "###"]);
+
+    assert_eq!(glider.width, 3);
+    assert_eq!(glider.height, 3);
+    assert_eq!(
+        glider.alive_cells().collect::<Vec<_>>(),
+        vec![(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]
+    );
+}
+
+#[test]
+fn test_pattern_rotate_clockwise() {
+    let pattern = Pattern::from_rows(&["##.", "..."]);
+    let rotated = pattern.rotate_clockwise();
+
+    assert_eq!(rotated, Pattern::from_rows(&[".#", ".#", ".."]));
+    assert_eq!(
+        rotated
+            .rotate_clockwise()
+            .rotate_clockwise()
+            .rotate_clockwise(),
+        pattern
+    );
+}
+
+#[test]
+fn test_pattern_flips() {
+    let pattern = Pattern::from_rows(&["#..", "##."]);
+
+    assert_eq!(
+        pattern.flip_horizontal(),
+        Pattern::from_rows(&["..#", ".##"])
+    );
+    assert_eq!(pattern.flip_vertical(), Pattern::from_rows(&["##.", "#.."]));
+}
+
+#[test]
+fn test_pattern_from_board() {
+    let entities = create_grid_from_rows(&["....", ".##.", "..#.", "...."]);
+    let pattern = Pattern::from_board(&entities, 4, CellRect::from_corners((2, 2), (1, 1)));
+
+    assert_eq!(pattern, Pattern::from_rows(&["##", ".#"]));
+}
+
+#[test]
+fn test_place_pattern_leaves_out_unknown_states() {
+    let grid = Grid {
+        width: 3,
+        height: 1,
+        cell_size: 10,
+        show: false,
+        layout: Layout::Square,
+    };
+    let mut pattern = Pattern::new(3, 1);
+    pattern.set(0, 0, CellState(3));
+    pattern.set(1, 0, CellState::ALIVE);
+    pattern.set(2, 0, CellState(2));
+
+    assert_eq!(
+        place_pattern(&pattern, (0, 0), &grid, 2),
+        vec![(1, 0, CellState::ALIVE)]
+    );
+    assert_eq!(place_pattern(&pattern, (0, 0), &grid, 4).len(), 3);
+}
+
+#[test]
+fn test_undoing_a_rule_change_keeps_states_the_rule_has() {
+    let mut game = GameOfLife::with_rule(3, 1, "Wireworld", Rule::Wireworld);
+    for (index, state) in [(0, CellState(3)), (1, CellState::ALIVE)] {
+        game.cell_entities[index]
+            .get_component_mut::<Cell>()
+            .unwrap()
+            .state = state;
+    }
+
+    game.set_rule("Life", Rule::life());
+    assert_eq!(
+        game.history_system
+            .undo(&mut game.cell_entities, &mut game.ant_entities),
+        Some(ChangeKind::Edit)
+    );
+
+    assert_eq!(
+        board_from(&game.cell_entities),
+        vec![CellState::DEAD, CellState::ALIVE, CellState::DEAD]
+    );
+}
+
+#[test]
+fn test_stamps_have_expected_population() {
+    let populations: Vec<usize> = stamps()
+        .iter()
+        .map(|(_, pattern)| pattern.alive_cells().count())
+        .collect();
+
+    assert_eq!(populations, vec![5, 9, 36]);
+}
+
+#[test]
+fn test_line_cells() {
+    assert_eq!(
+        line_cells((0, 0), (3, 0)),
+        vec![(0, 0), (1, 0), (2, 0), (3, 0)]
+    );
+    assert_eq!(line_cells((2, 2), (0, 0)), vec![(2, 2), (1, 1), (0, 0)]);
+    assert_eq!(line_cells((1, 1), (1, 1)), vec![(1, 1)]);
+
+    let steep = line_cells((0, 0), (1, 4));
+    assert_eq!(steep.len(), 5);
+    assert_eq!(steep.first(), Some(&(0, 0)));
+    assert_eq!(steep.last(), Some(&(1, 4)));
+}
+
+fn board_from(entities: &[Entity]) -> Vec<CellState> {
+    cell_states(entities)
+}
+
+#[test]
+fn test_history_undo_redo_edit() {
+    let mut entities = create_grid_from_rows(&["#..", "...", "..."]);
+    let mut history_system = HistorySystem::new(10);
+    let before = board_from(&entities);
+
+    entities[4].get_component_mut::<Cell>().unwrap().state = CellState::ALIVE;
+    history_system.record(
+        ChangeKind::Edit,
+        diff_cell_states(&before, &board_from(&entities)),
+        false,
+    );
+    let after = board_from(&entities);
+
+    assert_eq!(
+        history_system.undo(&mut entities, &mut Vec::new()),
+        Some(ChangeKind::Edit)
+    );
+    assert_eq!(board_from(&entities), before);
+    assert_eq!(history_system.undo(&mut entities, &mut Vec::new()), None);
+
+    assert_eq!(
+        history_system.redo(&mut entities, &mut Vec::new()),
+        Some(ChangeKind::Edit)
+    );
+    assert_eq!(board_from(&entities), after);
+}
+
+#[test]
+fn test_history_step_back_and_forward_through_generations() {
+    let mut entities = create_grid_from_rows(&[".....", ".....", ".###.", ".....", "....."]);
+    let mut history_system = HistorySystem::new(10);
+    let generation_0 = board_from(&entities);
+
+    LifeSystem::new(Rule::life()).update(&mut entities, 0.0);
+    history_system.record(
+        ChangeKind::Generation,
+        diff_cell_states(&generation_0, &board_from(&entities)),
+        false,
+    );
+    let generation_1 = board_from(&entities);
+
+    // An edit made after the generation is undone as well when stepping back.
+    let before_edit = board_from(&entities);
+    entities[0].get_component_mut::<Cell>().unwrap().state = CellState::ALIVE;
+    history_system.record(
+        ChangeKind::Edit,
+        diff_cell_states(&before_edit, &board_from(&entities)),
+        false,
+    );
+
+    assert_eq!(history_system.generation(), 1);
+
+    history_system.step_back(&mut entities, &mut Vec::new());
+    assert_eq!(board_from(&entities), generation_0);
+    assert_eq!(history_system.generation(), 0);
+
+    assert!(history_system.step_forward(&mut entities, &mut Vec::new()));
+    assert_eq!(board_from(&entities), generation_1);
+    assert_eq!(history_system.generation(), 1);
+    assert!(!history_system.step_forward(&mut entities, &mut Vec::new()));
+}
+
+#[test]
+fn test_history_counts_generations_that_change_nothing() {
+    let mut entities = create_grid_from_rows(&["....", ".##.", ".##.", "...."]);
+    let mut history_system = HistorySystem::new(10);
+
+    for _ in 0..3 {
+        let before = board_from(&entities);
+        LifeSystem::new(Rule::life()).update(&mut entities, 0.0);
+        history_system.record(
+            ChangeKind::Generation,
+            diff_cell_states(&before, &board_from(&entities)),
+            false,
+        );
+    }
+    assert_eq!(history_system.generation(), 3);
+
+    assert!(history_system.step_back(&mut entities, &mut Vec::new()));
+    assert_eq!(history_system.generation(), 2);
+}
+
+#[test]
+fn test_history_merges_continuing_strokes_and_drops_oldest() {
+    let mut entities = create_grid_from_rows(&["...", "...", "..."]);
+    let mut history_system = HistorySystem::new(2);
+
+    for (i, merge) in [(0, false), (1, true), (2, false), (3, false)] {
+        let before = board_from(&entities);
+        entities[i].get_component_mut::<Cell>().unwrap().state = CellState::ALIVE;
+        history_system.record(
+            ChangeKind::Edit,
+            diff_cell_states(&before, &board_from(&entities)),
+            merge,
+        );
+    }
+
+    assert!(history_system
+        .undo(&mut entities, &mut Vec::new())
+        .is_some());
+    assert!(history_system
+        .undo(&mut entities, &mut Vec::new())
+        .is_some());
+    assert!(history_system
+        .undo(&mut entities, &mut Vec::new())
+        .is_none());
+
+    // The merged stroke over cells 0 and 1 fell out of the two-entry history.
+    let states = board_from(&entities);
+    assert_eq!(states[0], CellState::ALIVE);
+    assert_eq!(states[1], CellState::ALIVE);
+    assert_eq!(states[2], CellState::DEAD);
+    assert_eq!(states[3], CellState::DEAD);
+}
+
+#[test]
+fn test_stats_counts_births_deaths_and_bounding_box() {
+    let mut entities = create_grid_from_rows(&[".....", ".....", ".###.", ".....", "....."]);
+    let mut stats = StatsSystem::new(&board_from(&entities), 5);
+
+    LifeSystem::new(Rule::life()).update(&mut entities, 0.0);
+    stats.record(&board_from(&entities));
+
+    let latest = stats.latest().unwrap();
+    assert_eq!(latest.generation, 1);
+    assert_eq!(latest.population, 3);
+    assert_eq!(latest.births, 2);
+    assert_eq!(latest.deaths, 2);
+    assert_eq!(
+        latest.bounding_box,
+        Some(CellRect {
+            x: 2,
+            y: 1,
+            width: 1,
+            height: 3
+        })
+    );
+}
+
+#[test]
+fn test_stats_detects_still_life() {
+    let mut entities = create_grid_from_rows(&["....", ".##.", ".##.", "...."]);
+    let mut stats = StatsSystem::new(&board_from(&entities), 4);
+
+    LifeSystem::new(Rule::life()).update(&mut entities, 0.0);
+    stats.record(&board_from(&entities));
+
+    assert_eq!(stats.stability(), Stability::Still { since: 0 });
+}
+
+#[test]
+fn test_stats_detects_cycle_period() {
+    let mut entities = create_grid_from_rows(&[".....", ".....", ".###.", ".....", "....."]);
+    let mut stats = StatsSystem::new(&board_from(&entities), 5);
+
+    LifeSystem::new(Rule::life()).update(&mut entities, 0.0);
+    stats.record(&board_from(&entities));
+    assert_eq!(stats.stability(), Stability::Evolving);
+
+    LifeSystem::new(Rule::life()).update(&mut entities, 0.0);
+    stats.record(&board_from(&entities));
+    assert_eq!(
+        stats.stability(),
+        Stability::Cycle {
+            period: 2,
+            since: 0
+        }
+    );
+}
+
+#[test]
+fn test_stats_restart_forgets_earlier_states() {
+    let mut entities = create_grid_from_rows(&["....", ".##.", ".##.", "...."]);
+    let mut stats = StatsSystem::new(&board_from(&entities), 4);
+
+    LifeSystem::new(Rule::life()).update(&mut entities, 0.0);
+    stats.record(&board_from(&entities));
+    stats.restart(&board_from(&entities), 1);
+
+    assert_eq!(stats.stability(), Stability::Evolving);
+    assert_eq!(stats.latest().unwrap().generation, 1);
+}
+
+#[test]
+fn test_stats_restart_at_an_earlier_generation() {
+    let mut entities = create_grid_from_rows(&[".....", ".....", ".###.", ".....", "....."]);
+    let mut stats = StatsSystem::new(&board_from(&entities), 5);
+    for _ in 0..3 {
+        LifeSystem::new(Rule::life()).update(&mut entities, 0.0);
+        stats.record(&board_from(&entities));
+    }
+
+    stats.restart(&board_from(&entities), 1);
+    assert_eq!(stats.latest().unwrap().generation, 1);
+
+    LifeSystem::new(Rule::life()).update(&mut entities, 0.0);
+    stats.record(&board_from(&entities));
+    let mut csv = Vec::new();
+    stats.write_csv(&mut csv).unwrap();
+    let generations: Vec<&str> = std::str::from_utf8(&csv)
+        .unwrap()
+        .lines()
+        .skip(1)
+        .map(|line| line.split(',').next().unwrap())
+        .collect();
+    assert_eq!(generations, vec!["0", "1", "2"]);
+}
+
+#[test]
+fn test_stats_write_csv() {
+    let mut entities = create_grid_from_rows(&["...", "###", "..."]);
+    let mut stats = StatsSystem::new(&board_from(&entities), 3);
+    entities[3].get_component_mut::<Cell>().unwrap().state = CellState::DEAD;
+    entities[4].get_component_mut::<Cell>().unwrap().state = CellState::DEAD;
+    entities[5].get_component_mut::<Cell>().unwrap().state = CellState::DEAD;
+    stats.record(&board_from(&entities));
+
+    let mut csv = Vec::new();
+    stats.write_csv(&mut csv).unwrap();
+
+    assert_eq!(
+        String::from_utf8(csv).unwrap(),
+        "generation,population,births,deaths,min_x,min_y,max_x,max_y\n\
+         0,3,0,0,0,1,2,1\n\
+         1,0,0,3,,,,\n"
+    );
+}
+
+#[test]
+fn test_rule_parse() {
+    let mut birth = [false; 9];
+    birth[3] = true;
+    birth[6] = true;
+    let mut survival = [false; 9];
+    survival[2] = true;
+    survival[3] = true;
+
+    assert_eq!(
+        Rule::parse("B36/S23"),
+        Ok(Rule::LifeLike {
+            birth,
+            survival,
+            neighbourhood: Neighbourhood::Moore
+        })
+    );
+    assert_eq!(Rule::parse("B2/S/C3"), Ok(Rule::brians_brain()));
+    assert_eq!(Rule::brians_brain().states(), 3);
+    assert!(Rule::parse("B9/S23").is_err());
+    assert!(Rule::parse("B3/S23/C1").is_err());
+    assert!(Rule::parse("X3").is_err());
+}
+
+#[test]
+fn test_generations_cells_decay_before_dying() {
+    let rule = Rule::parse("B/S/C4").unwrap();
+
+    assert_eq!(rule.next_state(CellState::ALIVE, 0), CellState(2));
+    assert_eq!(rule.next_state(CellState(2), 0), CellState(3));
+    assert_eq!(rule.next_state(CellState(3), 0), CellState::DEAD);
+    assert_eq!(rule.palette().len(), 4);
+}
+
+#[test]
+fn test_brians_brain_dying_cells_are_not_neighbours() {
+    let mut entities = create_grid_from_rows(&["....", ".#2.", "....", "...."]);
+
+    LifeSystem::new(Rule::brians_brain()).update(&mut entities, 0.0);
+
+    assert_eq!(
+        board_from(&entities),
+        board_from(&create_grid_from_rows(&["....", ".2..", "....", "...."]))
+    );
+}
+
+#[test]
+fn test_wireworld_electron_moves_along_wire() {
+    let mut entities = create_grid_from_rows(&["....", "2133", "....", "...."]);
+
+    LifeSystem::new(Rule::wireworld()).update(&mut entities, 0.0);
+
+    assert_eq!(
+        board_from(&entities),
+        board_from(&create_grid_from_rows(&["....", "3213", "....", "...."]))
+    );
+}
+
+#[test]
+fn test_parse_turns() {
+    assert_eq!(parse_turns("RL"), Ok(vec![Turn::Right, Turn::Left]));
+    assert_eq!(
+        parse_turns("lrnu"),
+        Ok(vec![Turn::Left, Turn::Right, Turn::None, Turn::Back])
+    );
+    assert!(parse_turns("R").is_err());
+    assert!(parse_turns("RXL").is_err());
+}
+
+#[test]
+fn test_direction_turn() {
+    assert_eq!(Direction::Up.turn(Turn::Right), Direction::Right);
+    assert_eq!(Direction::Up.turn(Turn::Left), Direction::Left);
+    assert_eq!(Direction::Left.turn(Turn::Back), Direction::Right);
+    assert_eq!(Direction::Down.turn(Turn::None), Direction::Down);
+}
+
+#[test]
+fn test_langtons_ant_walks_a_square() {
+    let mut cells = create_grid_from_rows(&["....."; 5]);
+    let mut ants = vec![create_ant(0, 2, 2, Direction::Up)];
+    let mut turmite_system = TurmiteSystem::new(5, 5);
+    let turns = parse_turns("RL").unwrap();
+
+    for _ in 0..4 {
+        turmite_system.update(&mut cells, &mut ants, &turns);
+    }
+
+    assert_eq!(
+        board_from(&cells),
+        board_from(&create_grid_from_rows(&[
+            ".....", ".....", "..##.", "..##.", "....."
+        ]))
+    );
+    let ant = ants[0].get_component::<Ant>().unwrap();
+    assert_eq!((ant.x, ant.y, ant.direction), (2, 2, Direction::Up));
+}
+
+#[test]
+fn test_turmite_cycles_states_and_wraps_around() {
+    let mut cells = create_grid_from_rows(&["2..", "...", "..."]);
+    let mut ants = vec![create_ant(0, 0, 0, Direction::Up)];
+    let turns = parse_turns("RLN").unwrap();
+
+    TurmiteSystem::new(3, 3).update(&mut cells, &mut ants, &turns);
+
+    assert_eq!(board_from(&cells)[0], CellState::DEAD);
+    let ant = ants[0].get_component::<Ant>().unwrap();
+    assert_eq!((ant.x, ant.y, ant.direction), (0, 2, Direction::Up));
+}
+
+#[test]
+fn test_stepping_back_moves_ants_back() {
+    let mut cells = create_grid_from_rows(&["....."; 5]);
+    let mut ants = vec![create_ant(0, 2, 2, Direction::Up)];
+    let mut turmite_system = TurmiteSystem::new(5, 5);
+    let mut history_system = HistorySystem::new(10);
+    let turns = parse_turns("RL").unwrap();
+
+    for _ in 0..3 {
+        let (before, ants_before) = (board_from(&cells), ant_states(&ants));
+        turmite_system.update(&mut cells, &mut ants, &turns);
+        history_system.record_generation(
+            diff_cell_states(&before, &board_from(&cells)),
+            diff_ant_states(&ants_before, &ant_states(&ants)),
+        );
+    }
+    let after = (board_from(&cells), ant_states(&ants));
+
+    history_system.step_back(&mut cells, &mut ants);
+    history_system.step_back(&mut cells, &mut ants);
+    let ant = ants[0].get_component::<Ant>().unwrap();
+    assert_eq!((ant.x, ant.y, ant.direction), (3, 2, Direction::Right));
+
+    history_system.step_forward(&mut cells, &mut ants);
+    history_system.step_forward(&mut cells, &mut ants);
+    assert_eq!((board_from(&cells), ant_states(&ants)), after);
+}
+
+#[test]
+fn test_undo_and_step_back_remove_placed_ants() {
+    let mut cells = create_grid_from_rows(&["....."; 5]);
+    let mut ants = vec![create_ant(0, 2, 2, Direction::Up)];
+    let mut turmite_system = TurmiteSystem::new(5, 5);
+    let mut history_system = HistorySystem::new(10);
+    let turns = parse_turns("RL").unwrap();
+
+    let before = ant_states(&ants);
+    ants.push(create_ant(1, 0, 0, Direction::Up));
+    history_system.record_edit(Vec::new(), diff_ant_states(&before, &ant_states(&ants)));
+    let placed = ant_states(&ants);
+
+    assert_eq!(
+        history_system.undo(&mut cells, &mut ants),
+        Some(ChangeKind::Edit)
+    );
+    assert_eq!(ant_states(&ants), before);
+    history_system.redo(&mut cells, &mut ants);
+    assert_eq!(ant_states(&ants), placed);
+
+    let (board, ants_before) = (board_from(&cells), ant_states(&ants));
+    turmite_system.update(&mut cells, &mut ants, &turns);
+    history_system.record_generation(
+        diff_cell_states(&board, &board_from(&cells)),
+        diff_ant_states(&ants_before, &ant_states(&ants)),
+    );
+    let removed = ant_states(&ants);
+    ants.clear();
+    history_system.record_edit(Vec::new(), diff_ant_states(&removed, &[]));
+
+    assert!(history_system.step_back(&mut cells, &mut ants));
+    assert_eq!(ant_states(&ants), placed);
+    assert!(history_system.step_back(&mut cells, &mut ants));
+    assert_eq!(ant_states(&ants), before);
+}
+
+#[test]
+fn test_undoing_a_switch_to_a_turmite_removes_its_ant() {
+    let mut game = GameOfLife::new(4, 4);
+
+    game.set_rule("Langton's ant", Rule::turmite("RL").unwrap());
+    assert_eq!(game.ant_entities.len(), 1);
+
+    game.history_system
+        .undo(&mut game.cell_entities, &mut game.ant_entities);
+    assert!(game.ant_entities.is_empty());
+}
+
+#[test]
+fn test_lenia_kernel_is_normalised_ring() {
+    let kernel = Kernel::new(5, &[1.0]);
+    let total: f32 = kernel.weights().iter().map(|(_, _, weight)| weight).sum();
+
+    assert!((total - 1.0).abs() < 1e-5);
+    assert!(kernel
+        .weights()
+        .iter()
+        .all(|&(dx, dy, _)| (dx, dy) != (0, 0) && dx * dx + dy * dy < 25));
+}
+
+#[test]
+fn test_lenia_growth() {
+    assert!((growth(0.15, 0.15, 0.015) - 1.0).abs() < 1e-6);
+    assert!((growth(0.0, 0.15, 0.015) + 1.0).abs() < 1e-6);
+    assert!(growth(0.16, 0.15, 0.015) < growth(0.155, 0.15, 0.015));
+}
+
+#[test]
+fn test_lenia_empty_field_stays_empty() {
+    let mut field = Field::new(32, 32);
+
+    LeniaSystem::new(LeniaConfig::orbium()).step(&mut field);
+
+    assert_eq!(field.mass(), 0.0);
+}
+
+#[test]
+fn test_lenia_uniform_field_grows_at_mu() {
+    let config = LeniaConfig::orbium();
+    let mut field = Field::new(32, 32);
+    field.values.fill(config.mu);
+
+    LeniaSystem::new(config.clone()).step(&mut field);
+
+    let expected = config.mu + config.dt;
+    assert!(field
+        .values
+        .iter()
+        .all(|value| (value - expected).abs() < 1e-4));
+}
+
+#[test]
+fn test_lenia_gradient_endpoints() {
+    assert_eq!(gradient(0.0), Color::new(0, 0, 0, 255));
+    assert_eq!(gradient(1.0), Color::new(255, 255, 255, 255));
+}
+
+#[test]
+fn test_neighbourhood_sizes() {
+    assert_eq!(Neighbourhood::Moore.size(1), 8);
+    assert_eq!(Neighbourhood::VonNeumann.size(1), 4);
+    assert_eq!(Neighbourhood::Hexagonal.size(1), 6);
+    assert_eq!(Neighbourhood::Moore.size(5), 120);
+    assert_eq!(Neighbourhood::VonNeumann.size(2), 12);
+    assert_eq!(Neighbourhood::Hexagonal.size(2), 18);
+}
+
+#[test]
+fn test_alive_sums_match_brute_force_counts() {
+    let entities = create_grid_from_rows(&[
+        "#..#.##.", ".##..#..", "#.#.#..#", "..##...#", "#....##.", ".#.#.#..", "##..#..#",
+        "...##.#.",
+    ]);
+    let states = board_from(&entities);
+    let sums = AliveSums::new(&states, 8, 8);
+
+    for (neighbourhood, radius) in [
+        (Neighbourhood::Moore, 1),
+        (Neighbourhood::Moore, 3),
+        (Neighbourhood::VonNeumann, 2),
+        (Neighbourhood::Hexagonal, 1),
+        (Neighbourhood::Hexagonal, 2),
+    ] {
+        let r = radius as i32;
+        for y in 0..8i32 {
+            for x in 0..8i32 {
+                let mut expected = 0;
+                for dy in -r..=r {
+                    for dx in -r..=r {
+                        let inside = match neighbourhood {
+                            Neighbourhood::Moore => true,
+                            Neighbourhood::VonNeumann => dx.abs() + dy.abs() <= r,
+                            Neighbourhood::Hexagonal => (dx + dy).abs() <= r,
+                        };
+                        let (nx, ny) = (x + dx, y + dy);
+                        if inside
+                            && (dx, dy) != (0, 0)
+                            && (0..8).contains(&nx)
+                            && (0..8).contains(&ny)
+                            && states[(nx + ny * 8) as usize] == CellState::ALIVE
+                        {
+                            expected += 1;
+                        }
+                    }
+                }
+
+                assert_eq!(
+                    sums.count(neighbourhood, radius, x as u32, y as u32),
+                    expected,
+                    "{:?} radius {} at ({}, {})",
+                    neighbourhood,
+                    radius,
+                    x,
+                    y
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_rule_parse_neighbourhoods() {
+    assert_eq!(
+        Rule::parse("B2/S34H").unwrap().neighbourhood(),
+        (Neighbourhood::Hexagonal, 1)
+    );
+    assert_eq!(
+        Rule::parse("B1/S1V").unwrap().neighbourhood(),
+        (Neighbourhood::VonNeumann, 1)
+    );
+    assert!(Rule::parse("B5/S1V").is_err());
+
+    let bugs = Rule::parse("R5,C2,M1,S34..58,B34..45,NM").unwrap();
+    assert_eq!(
+        bugs,
+        Rule::Larger {
+            radius: 5,
+            birth: 34..=45,
+            survival: 34..=58,
+            states: 2,
+            counts_middle: true,
+            neighbourhood: Neighbourhood::Moore,
+        }
+    );
+    assert!(Rule::parse("R5,C2,M1,S34..58,NM").is_err());
+    assert!(Rule::parse("R5,C2,M2,S34..58,B34..45,NM").is_err());
+    assert!(Rule::parse("R5,C0,M1,S34..58,B34..45,NM").is_err());
+    assert!(Rule::parse("R5,C1,M1,S34..58,B34..45,NM").is_err());
+}
+
+#[test]
+fn test_larger_than_life_radius_one_matches_life() {
+    let rows = [
+        "..........",
+        "..#.......",
+        "...#...##.",
+        ".###...##.",
+        "..........",
+        "....###...",
+        "..........",
+        ".#........",
+        ".#....##..",
+        ".#....#...",
+    ];
+    let mut expected = create_grid_from_rows(&rows);
+    let mut actual = create_grid_from_rows(&rows);
+    let mut life = LifeSystem::new(Rule::life());
+    let mut larger = LifeSystem::new(Rule::parse("R1,C2,M0,S2..3,B3,NM").unwrap());
+
+    for _ in 0..6 {
+        life.update(&mut expected, 0.0);
+        larger.update(&mut actual, 0.0);
+
+        assert_eq!(board_from(&actual), board_from(&expected));
+    }
+}
+
+#[test]
+fn test_hexagonal_grid_picks_cell_under_its_centre() {
+    let grid = Grid {
+        width: 6,
+        height: 5,
+        cell_size: 10,
+        show: false,
+        layout: Layout::Hexagonal,
+    };
+
+    for y in 0..5 {
+        for x in 0..6 {
+            assert_eq!(grid.cell_at(grid.cell_center(x, y)), Some((x, y)));
+        }
+    }
+    assert_eq!(grid.cell_at(Vector2::new(-5.0, -5.0)), None);
+}
+
+#[test]
+fn test_click_picks_cell_after_zoom_and_pan() {
+    let grid = Grid {
+        width: 20,
+        height: 10,
+        cell_size: 10,
+        show: false,
+        layout: Layout::Square,
+    };
+    let mut camera = Camera::new();
+    let cell_under =
+        |camera: &Camera, x: f32, y: f32| grid.cell_at(camera.screen_to_world(Vector2::new(x, y)));
+
+    assert_eq!(cell_under(&camera, 35.0, 15.0), Some((3, 1)));
+
+    // Four times bigger with world point (30, 10) staying under the cursor at (30, 10),
+    // so the screen point (75, 55) is world point (41.25, 21.25).
+    camera.zoom_at(Vector2::new(30.0, 10.0), 4.0);
+    assert_eq!(cell_under(&camera, 30.0, 10.0), Some((3, 1)));
+    assert_eq!(cell_under(&camera, 75.0, 55.0), Some((4, 2)));
+
+    // Dragging the world 80 pixels left moves it 20 world units at this zoom.
+    camera.pan(Vector2::new(-80.0, 0.0));
+    assert_eq!(cell_under(&camera, 75.0, 55.0), Some((6, 2)));
+    assert_eq!(cell_under(&camera, -200.0, 55.0), None);
+}
+
+#[test]
+fn test_soup_is_reproducible_and_stays_in_its_region() {
+    let soup = SoupConfig {
+        density: 0.5,
+        region: Some((8, 6)),
+        symmetry: Symmetry::C1,
+        seed: 42,
+    };
+
+    let cells = soup.alive_cells(20, 20);
+    assert_eq!(cells, soup.alive_cells(20, 20));
+    assert!(!cells.is_empty());
+    assert!(cells
+        .iter()
+        .all(|(x, y)| (6..14).contains(x) && (7..13).contains(y)));
+
+    let other = SoupConfig { seed: 43, ..soup };
+    assert_ne!(cells, other.alive_cells(20, 20));
+}
+
+#[test]
+fn test_soup_density_extremes() {
+    let empty = SoupConfig {
+        density: 0.0,
+        region: None,
+        ..SoupConfig::default()
+    };
+    assert!(empty.alive_cells(10, 10).is_empty());
+
+    let full = SoupConfig {
+        density: 1.0,
+        ..empty
+    };
+    assert_eq!(full.alive_cells(10, 10).len(), 100);
+}
+
+/// Maps a cell of an `n` x `n` region to another.
+type RegionMap = fn(u32, u32, u32) -> (u32, u32);
+
+#[test]
+fn test_soup_symmetries() {
+    let transforms: [(Symmetry, &[RegionMap]); 4] = [
+        (Symmetry::C2, &[|x, y, n| (n - 1 - x, n - 1 - y)]),
+        (Symmetry::C4, &[|x, y, n| (n - 1 - y, x)]),
+        (
+            Symmetry::D4,
+            &[|x, y, n| (n - 1 - x, y), |x, y, n| (x, n - 1 - y)],
+        ),
+        (Symmetry::D8, &[|x, y, n| (n - 1 - x, y), |x, y, _| (y, x)]),
+    ];
+
+    for (symmetry, maps) in transforms {
+        let soup = SoupConfig {
+            region: None,
+            symmetry,
+            seed: 7,
+            ..SoupConfig::default()
+        };
+        let cells = soup.alive_cells(9, 9);
+        assert!(!cells.is_empty(), "{:?}", symmetry);
+
+        for map in maps {
+            for &(x, y) in cells.iter() {
+                assert!(
+                    cells.contains(&map(x, y, 9)),
+                    "{:?} at ({}, {})",
+                    symmetry,
+                    x,
+                    y
+                );
+            }
+        }
+    }
+
+    let square = SoupConfig {
+        region: Some((10, 6)),
+        symmetry: Symmetry::C4,
+        ..SoupConfig::default()
+    };
+    assert_eq!(
+        square.region(20, 20),
+        CellRect {
+            x: 7,
+            y: 7,
+            width: 6,
+            height: 6
+        }
+    );
+}
+
+#[test]
+fn test_run_soup_settles() {
+    let soup = SoupConfig {
+        density: 1.0,
+        region: Some((2, 2)),
+        ..SoupConfig::default()
+    };
+
+    let outcome = run_soup(&soup, 8, 100, &known_objects());
+    assert_eq!(outcome.stability, Stability::Still { since: 0 });
+    assert_eq!(outcome.population, 4);
+
+    let mut log = Vec::new();
+    write_outcome(&mut log, &outcome).unwrap();
+    assert_eq!(String::from_utf8(log).unwrap(), "0,still,0,1,4,Block x1\n");
+}
+
+fn alive_cells_of(rows: &[&str]) -> Vec<(i32, i32)> {
+    Pattern::from_rows(rows)
+        .alive_cells()
+        .map(|(x, y)| (x as i32, y as i32))
+        .collect()
+}
+
+#[test]
+fn test_components_split_distant_objects() {
+    let entities = create_grid_from_rows(&[
+        "##.......",
+        "##.......",
+        ".........",
+        ".........",
+        "......###",
+    ]);
+
+    let components = components(&board_from(&entities), 9);
+    assert_eq!(components.len(), 2);
+    assert_eq!(components[0], vec![(0, 0), (1, 0), (0, 1), (1, 1)]);
+    assert_eq!(components[1], vec![(6, 4), (7, 4), (8, 4)]);
+}
+
+#[test]
+fn test_classify_objects() {
+    assert_eq!(
+        Object::classify(&alive_cells_of(&["##", "##"])).kind,
+        ObjectKind::StillLife
+    );
+    assert_eq!(
+        Object::classify(&alive_cells_of(&["###"])).kind,
+        ObjectKind::Oscillator { period: 2 }
+    );
+    assert_eq!(
+        Object::classify(&alive_cells_of(&[".#.", "..#", "###"])).kind,
+        ObjectKind::Spaceship {
+            period: 4,
+            dx: 1,
+            dy: 1
+        }
+    );
+    assert_eq!(
+        Object::classify(&alive_cells_of(&[".#..#", "#....", "#...#", "####."])).kind,
+        ObjectKind::Spaceship {
+            period: 4,
+            dx: 2,
+            dy: 0
+        }
+    );
+    assert_eq!(
+        Object::classify(&alive_cells_of(&["#"])).kind,
+        ObjectKind::Unknown
+    );
+}
+
+#[test]
+fn test_object_names_ignore_orientation_and_phase() {
+    let known = known_objects();
+    let name = |rows: &[&str]| Object::classify(&alive_cells_of(rows)).name(&known);
+
+    assert_eq!(name(&["#", "#", "#"]), "Blinker");
+    assert_eq!(name(&["#.#", ".##", ".#."]), "Glider");
+    assert_eq!(name(&["###", "#..", ".#."]), "Glider");
+    assert_eq!(name(&[".#.", "#.#", "#.#", ".#."]), "Beehive");
+    assert_eq!(name(&["##.", "#.#", ".##"]), "Ship");
+    assert_eq!(name(&["##..", "#...", "...#", "..##"]), "Beacon");
+    assert_eq!(name(&[".##", "#.#", "##."]), "Ship");
+}
+
+#[test]
+fn test_census_tally() {
+    let entities = create_grid_from_rows(&[
+        "##....##.",
+        "##....##.",
+        ".........",
+        ".........",
+        "###......",
+        ".........",
+        ".........",
+        "......##.",
+        "......##.",
+    ]);
+
+    let mut census = Census::new();
+    census.take(&board_from(&entities), 9, &known_objects());
+    assert_eq!(census.tally(), vec![("Block", 3), ("Blinker", 1)]);
+
+    let mut total = Census::new();
+    total.merge(&census);
+    total.merge(&census);
+    assert_eq!(total.tally(), vec![("Block", 6), ("Blinker", 2)]);
+}
+
+#[test]
+fn test_grid_fades_out_when_zoomed_out() {
+    assert_eq!(grid_alpha(2.0), 0.0);
+    assert_eq!(grid_alpha(4.0), 0.0);
+    assert_eq!(grid_alpha(8.0), 0.5);
+    assert_eq!(grid_alpha(12.0), 1.0);
+    assert_eq!(grid_alpha(40.0), 1.0);
+}
+
+#[test]
+fn test_cell_pixels_leave_dead_cells_transparent() {
+    let entities = create_grid_from_rows(&["#.", ".2"]);
+    let palette = Rule::brians_brain().palette();
+    let coloring = CellColoring::new(RenderMode::State, &palette, &entities);
+    let mut pixels = vec![1, 2, 3];
+
+    cell_pixels(&entities, &coloring, &mut pixels);
+
+    let alive = palette[1];
+    let dying = palette[2];
+    assert_eq!(
+        pixels,
+        vec![
+            alive.r, alive.g, alive.b, alive.a, 0, 0, 0, 0, 0, 0, 0, 0, dying.r, dying.g, dying.b,
+            dying.a
+        ]
+    );
+}
+
+fn cell_at(entities: &[Entity], index: usize) -> &Cell {
+    entities[index].get_component::<Cell>().unwrap()
+}
+
+#[test]
+fn test_activity_tracks_age_changes_and_death() {
+    // A blinker: the centre stays alive, the ends die and come back every generation.
+    let mut entities = create_grid_from_rows(&[".....", ".....", ".###.", ".....", "....."]);
+    let mut life_system = LifeSystem::new(Rule::life());
+
+    for _ in 0..4 {
+        let before = board_from(&entities);
+        life_system.update(&mut entities, 0.0);
+        ActivitySystem.record(&mut entities, &before);
+    }
+
+    let centre = cell_at(&entities, 12);
+    assert_eq!(
+        (centre.age, centre.changes, centre.since_alive),
+        (4, 0, Some(0))
+    );
+
+    // Back in the horizontal phase, the ends were born one generation ago.
+    let end = cell_at(&entities, 11);
+    assert_eq!((end.age, end.changes, end.since_alive), (1, 4, Some(0)));
+    let top = cell_at(&entities, 7);
+    assert_eq!((top.age, top.changes, top.since_alive), (0, 4, Some(1)));
+
+    let corner = cell_at(&entities, 0);
+    assert_eq!(
+        (corner.age, corner.changes, corner.since_alive),
+        (0, 0, None)
+    );
+
+    ActivitySystem.reset(&mut entities);
+    let end = cell_at(&entities, 11);
+    assert_eq!((end.age, end.changes, end.since_alive), (0, 0, Some(0)));
+}
+
+#[test]
+fn test_cells_alive_from_outside_a_generation_leave_a_trail() {
+    let mut entities = create_grid_from_rows(&["...", "...", "..."]);
+    // Made alive the way undo does it, without going through the activity system.
+    entities[0].get_component_mut::<Cell>().unwrap().state = CellState::ALIVE;
+
+    let before = board_from(&entities);
+    LifeSystem::new(Rule::life()).update(&mut entities, 0.0);
+    ActivitySystem.record(&mut entities, &before);
+
+    assert_eq!(cell_at(&entities, 0).since_alive, Some(1));
+}
+
+#[test]
+fn test_activity_follows_edits() {
+    let mut entities = create_grid_from_rows(&["#..", "...", "..."]);
+    let before = board_from(&entities);
+    entities[0].get_component_mut::<Cell>().unwrap().state = CellState::DEAD;
+    entities[4].get_component_mut::<Cell>().unwrap().state = CellState::ALIVE;
+
+    let edits = diff_cell_states(&before, &board_from(&entities));
+    ActivitySystem.edited(&mut entities, &edits);
+
+    let erased = cell_at(&entities, 0);
+    assert_eq!(
+        (erased.age, erased.changes, erased.since_alive),
+        (0, 0, Some(1))
+    );
+    let drawn = cell_at(&entities, 4);
+    assert_eq!(
+        (drawn.age, drawn.changes, drawn.since_alive),
+        (1, 0, Some(0))
+    );
+}
+
+#[test]
+fn test_cell_coloring_modes() {
+    let palette = Rule::life().palette();
+    let mut entities = create_grid_from_rows(&["#.", ".."]);
+    if let Some(cell) = entities[0].get_component_mut::<Cell>() {
+        cell.age = 500;
+        cell.changes = 2;
+    }
+    if let Some(cell) = entities[1].get_component_mut::<Cell>() {
+        cell.changes = 1;
+        cell.since_alive = Some(3);
+    }
+    if let Some(cell) = entities[2].get_component_mut::<Cell>() {
+        cell.since_alive = Some(100);
+    }
+
+    let color = |mode: RenderMode, index: usize| {
+        CellColoring::new(mode, &palette, &entities).color(cell_at(&entities, index))
+    };
+
+    assert_eq!(color(RenderMode::State, 0), Some(palette[1]));
+    assert_eq!(color(RenderMode::State, 1), None);
+    assert_eq!(
+        color(RenderMode::Age, 0),
+        Some(Color::new(120, 0, 160, 255))
+    );
+    assert_eq!(color(RenderMode::Age, 1), None);
+    assert_eq!(color(RenderMode::Heat, 0), Some(Color::WHITE));
+    assert!(color(RenderMode::Heat, 1).is_some());
+    assert_eq!(color(RenderMode::Heat, 3), None);
+    assert_eq!(color(RenderMode::Trails, 0), Some(palette[1]));
+    assert!(color(RenderMode::Trails, 1).is_some());
+    assert_eq!(color(RenderMode::Trails, 2), None);
+    assert_eq!(color(RenderMode::Trails, 3), None);
+}
+
+#[test]
+fn test_render_image_colours_unknown_states_magenta() {
+    let mut game = GameOfLife::new(2, 1);
+    if let Some(cell) = game.cell_entities[1].get_component_mut::<Cell>() {
+        cell.state = CellState(3);
+    }
+
+    let image = game.render_image(2);
+
+    assert_eq!(image.get(1, 1), game.palette[0]);
+    assert_eq!(image.get(3, 1), Color::MAGENTA);
+}
@@ -0,0 +1,156 @@
+use raylib::{
+    color::Color,
+    prelude::{RaylibDraw, RaylibDrawHandle},
+};
+
+use crate::engine::{camera::Camera, Entity, EntityBuilder};
+
+use super::{Cell, CellState, Grid};
+
+/// How a turmite turns after reading the cell it stands on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Turn {
+    Left,
+    Right,
+    /// Keeps going straight.
+    None,
+    /// Turns around.
+    Back,
+}
+
+/// Parses a turmite rulestring such as "RL" (Langton's ant) or "RLR".
+/// The n-th letter is the turn taken on a cell in state n, which then goes to state n + 1,
+/// wrapping around to 0 after the last one.
+pub fn parse_turns(rule: &str) -> Result<Vec<Turn>, String> {
+    let turns = rule
+        .chars()
+        .map(|c| match c.to_ascii_uppercase() {
+            'L' => Ok(Turn::Left),
+            'R' => Ok(Turn::Right),
+            'N' => Ok(Turn::None),
+            'U' => Ok(Turn::Back),
+            _ => Err(format!("Invalid turn '{}' in \"{}\"", c, rule)),
+        })
+        .collect::<Result<Vec<Turn>, String>>()?;
+
+    if turns.len() < 2 || turns.len() > u8::MAX as usize {
+        return Err(format!(
+            "A turmite needs between 2 and {} turns, got {}",
+            u8::MAX,
+            turns.len()
+        ));
+    }
+
+    Ok(turns)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    pub fn turn(self, turn: Turn) -> Self {
+        let quarter_turns = match turn {
+            Turn::None => 0,
+            Turn::Right => 1,
+            Turn::Back => 2,
+            Turn::Left => 3,
+        };
+
+        let directions = [
+            Direction::Up,
+            Direction::Right,
+            Direction::Down,
+            Direction::Left,
+        ];
+        directions[(self as usize + quarter_turns) % 4]
+    }
+
+    /// Rotation in degrees for drawing, with 0 pointing right like raylib's polygons.
+    fn rotation(self) -> f32 {
+        match self {
+            Direction::Right => 0.0,
+            Direction::Down => 90.0,
+            Direction::Left => 180.0,
+            Direction::Up => 270.0,
+        }
+    }
+}
+
+/// An agent walking over the cells. Ants are entities of their own so they can be
+/// drawn on top of the board. Generations record how the ants moved, so stepping back
+/// puts them where they were.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ant {
+    pub x: u32,
+    pub y: u32,
+    pub direction: Direction,
+}
+
+pub fn create_ant(id: u32, x: u32, y: u32, direction: Direction) -> Entity {
+    EntityBuilder::new(id)
+        .with_component(Ant { x, y, direction })
+        .build()
+}
+
+/// Moves the ants one step each. An ant turns according to the state of its cell,
+/// advances that cell to the next state and walks forward, wrapping around the edges.
+pub struct TurmiteSystem {
+    width: u32,
+    height: u32,
+}
+
+impl TurmiteSystem {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self { width, height }
+    }
+
+    pub fn update(&mut self, cells: &mut [Entity], ants: &mut [Entity], turns: &[Turn]) {
+        for entity in ants.iter_mut() {
+            let Some(ant) = entity.get_component_mut::<Ant>() else {
+                continue;
+            };
+
+            let index = (ant.x + ant.y * self.width) as usize;
+            if let Some(cell) = cells[index].get_component_mut::<Cell>() {
+                let state = cell.state.0 as usize % turns.len();
+                ant.direction = ant.direction.turn(turns[state]);
+                cell.state = CellState(((state + 1) % turns.len()) as u8);
+            }
+
+            match ant.direction {
+                Direction::Up => ant.y = (ant.y + self.height - 1) % self.height,
+                Direction::Right => ant.x = (ant.x + 1) % self.width,
+                Direction::Down => ant.y = (ant.y + 1) % self.height,
+                Direction::Left => ant.x = (ant.x + self.width - 1) % self.width,
+            }
+        }
+    }
+}
+
+/// Draws the ants as triangles pointing where they are heading.
+pub struct AntRenderSystem;
+
+impl AntRenderSystem {
+    pub fn render(&self, rl: &mut RaylibDrawHandle, ants: &[Entity], grid: &Grid, camera: &Camera) {
+        let cell_size = grid.cell_size as f32;
+
+        for entity in ants.iter() {
+            if let Some(ant) = entity.get_component::<Ant>() {
+                let center = camera.world_to_screen(grid.cell_center(ant.x, ant.y));
+
+                rl.draw_poly(
+                    center,
+                    3,
+                    cell_size * camera.zoom * 0.5,
+                    ant.direction.rotation(),
+                    Color::RED,
+                );
+            }
+        }
+    }
+}
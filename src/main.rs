@@ -3,6 +3,15 @@ mod evolution;
 mod game_of_life;
 
 fn main() {
-    // game_of_life::run();
-    evolution::run();
+    match std::env::args().nth(1).as_deref() {
+        Some("life") => game_of_life::run(),
+        Some("life-bench") => game_of_life::bench::run(),
+        Some("life-export") => game_of_life::headless::run(std::env::args().skip(2).collect()),
+        Some("life-soup") => game_of_life::soup::run(std::env::args().skip(2).collect()),
+        Some("lenia") => game_of_life::lenia::run(),
+        Some("evolution-ga") => {
+            evolution::genetic_algorithm::run(std::env::args().skip(2).collect())
+        }
+        _ => evolution::run(std::env::args().skip(1).collect()),
+    }
 }